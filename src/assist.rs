@@ -0,0 +1,99 @@
+use nalgebra::UnitQuaternion;
+
+use crate::{attitude::triad, sky::Star};
+
+/// the moves added to the score each time the pairs of stars align the view
+pub const PENALTY: usize = 10;
+
+/// Stars matched by the player, each picked first in the window and then in
+/// the target, as directions in the frame of the camera of each. Two pairs
+/// tell the rotation that takes the window to the target.
+#[derive(Clone, Debug, Default)]
+pub struct PairAssist {
+    /// the star picked in the window, waiting for its counterpart
+    picked: Option<Star>,
+    pairs: Vec<(Star, Star)>,
+    /// why the last pairs did not tell the rotation
+    error: Option<&'static str>,
+}
+
+impl PairAssist {
+    /// Picks `direction` in the window, instead of the one picked before if
+    /// it had no counterpart yet.
+    pub fn pick_window(&mut self, direction: Star) {
+        self.picked = Some(direction);
+        self.error = None;
+    }
+
+    /// Picks `direction` in the target as the counterpart of the star picked
+    /// in the window, if there is one. With the second pair, returns the
+    /// rotation that takes the window to the target, unless the two stars
+    /// of either view are too close together to tell it; then it starts over.
+    pub fn pick_target(&mut self, direction: Star) -> Option<UnitQuaternion<f32>> {
+        let picked = self.picked.take()?;
+        self.pairs.push((picked, direction));
+        let [(w1, t1), (w2, t2)] = self.pairs[..] else {
+            return None;
+        };
+        self.pairs.clear();
+        let rotation = triad([w1, w2], [t1, t2]);
+        self.error = rotation
+            .is_none()
+            .then_some("the stars of a pair are in the same direction");
+        rotation
+    }
+
+    /// what the player should pick next
+    pub fn prompt(&self) -> String {
+        let next = if self.picked.is_some() {
+            "the same star in the target"
+        } else {
+            "a star in the window"
+        };
+        let error = self.error.map(|e| format!("{e}, ")).unwrap_or_default();
+        format!("{error}pair {} of 2: pick {next}", self.pairs.len() + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::UnitQuaternion;
+
+    use super::PairAssist;
+    use crate::{attitude::geodesic_distance, sky::Star};
+
+    #[test]
+    fn test_pair_assist() {
+        let q = UnitQuaternion::from_euler_angles(0.3, 0.2, -0.9);
+        let (a, b) = (Star::new(0.1, 0.0, 1.0), Star::new(-0.2, 0.3, 1.0));
+        let mut assist = PairAssist::default();
+        assert_eq!(assist.prompt(), "pair 1 of 2: pick a star in the window");
+        // a target without a star in the window is not a pair
+        assert!(assist.pick_target(q * a).is_none());
+        assist.pick_window(b);
+        assist.pick_window(a);
+        assert_eq!(
+            assist.prompt(),
+            "pair 1 of 2: pick the same star in the target"
+        );
+        assert!(assist.pick_target(q * a).is_none());
+        assist.pick_window(b);
+        assert_eq!(
+            assist.prompt(),
+            "pair 2 of 2: pick the same star in the target"
+        );
+        let found = assist.pick_target(q * b).unwrap();
+        assert_relative_eq!(geodesic_distance(found, q), 0.0, epsilon = 1e-5);
+
+        // the same star twice tells nothing, and it starts over
+        assist.pick_window(a);
+        assist.pick_target(q * a);
+        assist.pick_window(a);
+        assert!(assist.pick_target(q * a).is_none());
+        assert_eq!(
+            assist.prompt(),
+            "the stars of a pair are in the same direction, pair 1 of 2: pick a star in the window"
+        );
+    }
+}
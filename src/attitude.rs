@@ -0,0 +1,344 @@
+use std::{f32::consts::PI, fmt};
+
+use nalgebra::{convert, Matrix3, Quaternion, RealField, Rotation3, UnitQuaternion};
+use serde::{Deserialize, Serialize};
+
+use crate::sky::{angle_between, attitude_distance, Star};
+
+/// How the distance between the attitude reached and the target is measured.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Metric {
+    /// the angle of the rotation that takes one attitude to the other
+    #[default]
+    Geodesic,
+    /// the norm of the roll, pitch and yaw between them, as scored before
+    EulerAngles,
+}
+
+impl Metric {
+    pub fn distance(self, target_q: UnitQuaternion<f32>, real_q: UnitQuaternion<f32>) -> f32 {
+        match self {
+            Metric::Geodesic => geodesic_distance(target_q, real_q),
+            Metric::EulerAngles => attitude_distance(target_q, real_q),
+        }
+    }
+}
+
+/// The angle, in radians from 0 to π, of the smallest rotation between two
+/// attitudes. Unlike the norm of Euler angles, it is the same whatever axis
+/// the rotation is about, and it does not jump near ±π.
+/// It works in `f64` as well, for attitudes finer than `f32` can tell apart.
+pub fn geodesic_distance<T: RealField + Copy>(
+    target_q: UnitQuaternion<T>,
+    real_q: UnitQuaternion<T>,
+) -> T {
+    let missing = target_q / real_q;
+    // as accurate as the attitudes for small angles, unlike `2·acos(|w|)`
+    convert::<f64, T>(2.0) * missing.imag().norm().atan2(missing.w.abs())
+}
+
+/// Whether `real_q` is within `tolerance` degrees of `target_q`, the game
+/// solved. With no tolerance, it is never.
+pub fn is_aligned(
+    target_q: UnitQuaternion<f32>,
+    real_q: UnitQuaternion<f32>,
+    tolerance: f32,
+) -> bool {
+    tolerance > 0.0 && geodesic_distance(target_q, real_q).to_degrees() < tolerance
+}
+
+/// The attitude a fraction `t` (from 0 to 1) of the way from `from` to `to`,
+/// turning at a steady rate about a fixed axis, the shortest way.
+pub fn slerp<T: RealField + Copy>(
+    from: UnitQuaternion<T>,
+    to: UnitQuaternion<T>,
+    t: T,
+) -> UnitQuaternion<T> {
+    let t = t.max(T::zero()).min(T::one());
+    // when they are so close that the axis is lost in rounding
+    from.try_slerp(&to, t, T::default_epsilon())
+        .unwrap_or_else(|| from.nlerp(&to, t))
+}
+
+/// `t` (from 0 to 1) eased in and out, to start and stop an animation smoothly
+pub fn ease(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// `steps` attitudes from `from` to `to`, both included, evenly spaced in time
+pub fn slerp_path<T: RealField + Copy>(
+    from: UnitQuaternion<T>,
+    to: UnitQuaternion<T>,
+    steps: usize,
+) -> Vec<UnitQuaternion<T>> {
+    match steps {
+        0 => vec![],
+        1 => vec![to],
+        _ => (0..steps)
+            .map(|i| slerp(from, to, convert(i as f64 / (steps - 1) as f64)))
+            .collect(),
+    }
+}
+
+/// The attitude typed in `text`: roll, pitch and yaw in degrees, or the
+/// four components w, i, j and k of a quaternion, which need not be unit,
+/// separated by spaces or commas.
+pub fn parse_attitude(text: &str) -> Result<UnitQuaternion<f32>, String> {
+    let numbers = text
+        .split([' ', ','])
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.parse::<f32>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => Err(format!("{word} is not a number")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match numbers[..] {
+        [roll, pitch, yaw] => Ok(UnitQuaternion::from_euler_angles(
+            roll.to_radians(),
+            pitch.to_radians(),
+            yaw.to_radians(),
+        )),
+        [w, i, j, k] => UnitQuaternion::try_new(Quaternion::new(w, i, j, k), 1e-6)
+            .ok_or_else(|| String::from("the quaternion cannot be 0")),
+        _ => Err(format!(
+            "{} numbers, instead of roll, pitch and yaw or w, i, j and k",
+            numbers.len()
+        )),
+    }
+}
+
+/// The rotation that takes `from[0]` to `to[0]`, and `from[1]` as close as
+/// it can to `to[1]`: the TRIAD method, which tells an attitude from two
+/// directions seen in two frames. None if the directions of a pair are parallel.
+pub fn triad(from: [Star; 2], to: [Star; 2]) -> Option<UnitQuaternion<f32>> {
+    let frame = |[first, second]: [Star; 2]| {
+        let t1 = first.try_normalize(1e-6)?;
+        let t2 = first.cross(&second).try_normalize(1e-6)?;
+        Some(Matrix3::from_columns(&[t1, t2, t1.cross(&t2)]))
+    };
+    let rotation = frame(to)? * frame(from)?.transpose();
+    Some(UnitQuaternion::from_rotation_matrix(
+        &Rotation3::from_matrix_unchecked(rotation),
+    ))
+}
+
+/// The shortest turn that brings `direction`, in the frame of the camera,
+/// to the center of the view, as when centering on a star.
+pub fn centering(direction: &Star) -> UnitQuaternion<f32> {
+    let boresight = Star::z_axis().into_inner();
+    UnitQuaternion::rotation_between(direction, &boresight).unwrap_or_else(|| {
+        // right behind, any half turn across the boresight will do
+        UnitQuaternion::from_axis_angle(&Star::x_axis(), PI)
+    })
+}
+
+/// What is missing to reach the target, split in how far the boresight
+/// points from where it should and how much the view must still turn about
+/// it. Both are in radians; the roll is positive counterclockwise, as seen
+/// through the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AttitudeError {
+    pub pointing: f32,
+    pub roll: f32,
+}
+
+impl AttitudeError {
+    /// Splits the rotation from `real_q` to `target_q` in a rotation about
+    /// the boresight (the z axis of the view) followed by one across it.
+    pub fn between(target_q: UnitQuaternion<f32>, real_q: UnitQuaternion<f32>) -> Self {
+        let missing = target_q / real_q;
+        let boresight = Star::z_axis().into_inner();
+        let pointing = angle_between(&(missing * boresight), &boresight);
+        // the twist of the swing-twist decomposition about the boresight
+        let roll = 2.0 * missing.k.atan2(missing.w);
+        let roll = if roll > PI {
+            roll - 2.0 * PI
+        } else if roll <= -PI {
+            roll + 2.0 * PI
+        } else {
+            roll
+        };
+        Self { pointing, roll }
+    }
+}
+
+impl fmt::Display for AttitudeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pointing: {:.3}°, roll: {:+.3}°",
+            self.pointing.to_degrees(),
+            self.roll.to_degrees()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use approx::assert_relative_eq;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    use crate::sky::Star;
+
+    use super::{
+        centering, ease, geodesic_distance, is_aligned, parse_attitude, slerp, slerp_path, triad,
+        AttitudeError, Metric,
+    };
+
+    #[test]
+    fn test_is_aligned() {
+        let target = UnitQuaternion::from_euler_angles(0.4, -1.0, 2.0);
+        let near = UnitQuaternion::from_euler_angles(0.3f32.to_radians(), 0.0, 0.0) * target;
+        assert!(is_aligned(target, near, 0.5));
+        assert!(!is_aligned(target, near, 0.2));
+        // without a tolerance, not even the target itself
+        assert!(!is_aligned(target, target, 0.0));
+    }
+
+    #[test]
+    fn test_geodesic_distance() {
+        let id = UnitQuaternion::identity();
+        assert_relative_eq!(geodesic_distance(id, id), 0.0);
+        for axis in [Vector3::x_axis(), Vector3::y_axis(), Vector3::z_axis()] {
+            let q = UnitQuaternion::from_axis_angle(&axis, 0.3);
+            assert_relative_eq!(geodesic_distance(q, id), 0.3, epsilon = 1e-5);
+            assert_relative_eq!(geodesic_distance(id, q), 0.3, epsilon = 1e-5);
+        }
+        // a turn of almost π either way is almost π away, not 2π
+        let a = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI - 0.01);
+        let b = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -PI + 0.01);
+        assert_relative_eq!(geodesic_distance(a, b), 0.02, epsilon = 1e-3);
+        // q and -q are the same attitude
+        let q = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+        let minus_q = UnitQuaternion::new_unchecked(-q.into_inner());
+        assert_relative_eq!(geodesic_distance(q, minus_q), 0.0, epsilon = 1e-5);
+
+        let roll = UnitQuaternion::from_euler_angles(0.2, 0.0, 0.0);
+        assert_relative_eq!(Metric::EulerAngles.distance(roll, id), 0.2, epsilon = 1e-5);
+        assert_relative_eq!(Metric::default().distance(roll, id), 0.2, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let from = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+        let to = UnitQuaternion::from_euler_angles(-1.0, 0.5, 2.0);
+        assert_relative_eq!(slerp(from, to, 0.0), from, epsilon = 1e-5);
+        assert_relative_eq!(slerp(from, to, 1.0), to, epsilon = 1e-5);
+        assert_relative_eq!(slerp(from, to, 2.0), to, epsilon = 1e-5);
+        let half = slerp(from, to, 0.5);
+        assert_relative_eq!(
+            geodesic_distance(from, half),
+            geodesic_distance(half, to),
+            epsilon = 1e-4
+        );
+        // the shortest way, even if the quaternions have opposite signs
+        let minus_to = UnitQuaternion::new_unchecked(-to.into_inner());
+        let half_too = slerp(from, minus_to, 0.5);
+        assert_relative_eq!(geodesic_distance(half, half_too), 0.0, epsilon = 1e-5);
+        // half a turn
+        let turned = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI) * from;
+        assert_relative_eq!(
+            geodesic_distance(from, slerp(from, turned, 0.5)),
+            PI / 2.0,
+            epsilon = 1e-3
+        );
+        assert_relative_eq!(slerp(from, from, 0.3), from, epsilon = 1e-6);
+
+        assert_eq!(ease(0.0), 0.0);
+        assert_eq!(ease(0.5), 0.5);
+        assert_eq!(ease(1.0), 1.0);
+        assert!(ease(0.1) < 0.1);
+
+        let path = slerp_path(from, to, 5);
+        assert_eq!(path.len(), 5);
+        assert_relative_eq!(path[4], to, epsilon = 1e-5);
+        assert_relative_eq!(path[2], half, epsilon = 1e-5);
+        assert!(slerp_path(from, to, 0).is_empty());
+    }
+
+    #[test]
+    fn test_attitude_error() {
+        let start = UnitQuaternion::from_euler_angles(0.4, -1.0, 2.0);
+        // only about the boresight
+        let twist = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.3);
+        let error = AttitudeError::between(twist * start, start);
+        assert_relative_eq!(error.pointing, 0.0, epsilon = 1e-3);
+        assert_relative_eq!(error.roll, 0.3, epsilon = 1e-5);
+        let error = AttitudeError::between(twist.inverse() * start, start);
+        assert_relative_eq!(error.roll, -0.3, epsilon = 1e-5);
+        // only across it
+        let swing = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.2);
+        let error = AttitudeError::between(swing * start, start);
+        assert_relative_eq!(error.pointing, 0.2, epsilon = 1e-5);
+        assert_relative_eq!(error.roll, 0.0, epsilon = 1e-5);
+        // both
+        let error = AttitudeError::between(swing * twist * start, start);
+        assert_relative_eq!(error.pointing, 0.2, epsilon = 1e-5);
+        assert_relative_eq!(error.roll, 0.3, epsilon = 1e-5);
+        // a roll of almost π either way
+        let flip = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI - 0.01);
+        let error = AttitudeError::between(flip.inverse() * start, start);
+        assert_relative_eq!(error.roll, -PI + 0.01, epsilon = 1e-4);
+        assert_eq!(
+            AttitudeError {
+                pointing: 0.0,
+                roll: 0.1f32.to_radians()
+            }
+            .to_string(),
+            "pointing: 0.000°, roll: +0.100°"
+        );
+    }
+
+    #[test]
+    fn test_parse_attitude() {
+        let q = parse_attitude("10 -20, 30").unwrap();
+        let (roll, pitch, yaw) = q.euler_angles();
+        assert_relative_eq!(roll.to_degrees(), 10.0, epsilon = 1e-4);
+        assert_relative_eq!(pitch.to_degrees(), -20.0, epsilon = 1e-4);
+        assert_relative_eq!(yaw.to_degrees(), 30.0, epsilon = 1e-4);
+
+        let q = parse_attitude("2 0 0 2").unwrap();
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), PI / 2.0);
+        assert_relative_eq!(q, expected, epsilon = 1e-6);
+
+        assert!(parse_attitude("0 0 0 0").is_err());
+        assert!(parse_attitude("1 2").is_err());
+        assert_eq!(parse_attitude("1 x 3").unwrap_err(), "x is not a number");
+        assert!(parse_attitude("1 NaN 3").is_err());
+    }
+
+    #[test]
+    fn test_triad() {
+        let q = UnitQuaternion::from_euler_angles(0.4, -1.1, 2.5);
+        let from = [Star::new(1.0, 0.2, 0.1), Star::new(-0.3, 1.0, 0.5)];
+        let found = triad(from, from.map(|d| q * d)).unwrap();
+        assert_relative_eq!(geodesic_distance(found, q), 0.0, epsilon = 1e-5);
+        // the first pair is matched exactly, even if the second is off
+        let off = [q * from[0], q * from[1] + Star::new(0.0, 0.05, 0.0)];
+        let found = triad(from, off).unwrap();
+        assert_relative_eq!(
+            found * from[0].normalize(),
+            off[0].normalize(),
+            epsilon = 1e-5
+        );
+        assert!(geodesic_distance(found, q) < 0.1);
+        assert!(triad([from[0], from[0] * 2.0], from).is_none());
+    }
+
+    #[test]
+    fn test_centering() {
+        let boresight = Star::z_axis().into_inner();
+        for direction in [
+            Star::new(0.3, -0.2, 1.0),
+            Star::new(1.0, 0.0, 0.0),
+            Star::new(0.0, 0.0, -2.0),
+        ] {
+            let centered = centering(&direction) * direction.normalize();
+            assert_relative_eq!(centered, boresight, epsilon = 1e-5);
+        }
+        assert_relative_eq!(centering(&boresight).angle(), 0.0);
+    }
+}
@@ -0,0 +1,187 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use crate::sky::{ra_dec, SkyError, Star, CONSTELLATIONS};
+
+/// The region of the sky that belongs to a constellation, as the polygon of
+/// its boundary in right ascension and declination (degrees).
+#[derive(Clone, Debug)]
+struct Region {
+    constellation: &'static str,
+    vertices: Vec<(f32, f32)>,
+}
+
+/// the difference `to - from` between two right ascensions, in (-180, 180]
+fn ra_delta(from: f32, to: f32) -> f32 {
+    let d = (to - from).rem_euclid(360.0);
+    if d > 180.0 {
+        d - 360.0
+    } else {
+        d
+    }
+}
+
+impl Region {
+    fn edges(&self) -> impl Iterator<Item = (&(f32, f32), &(f32, f32))> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    /// whether the boundary goes around the north pole, as that of UMi
+    fn around_north_pole(&self) -> bool {
+        let winding: f32 = self.edges().map(|(a, b)| ra_delta(a.0, b.0)).sum();
+        winding.abs() > 180.0 && self.vertices.iter().map(|v| v.1).sum::<f32>() > 0.0
+    }
+
+    /// Counts the edges met going north from (`ra`, `dec`): an odd number means
+    /// the point is inside, unless the region is the one around the north pole.
+    fn contains(&self, ra: f32, dec: f32) -> bool {
+        let crossings = self
+            .edges()
+            .filter(|(a, b)| {
+                let d = ra_delta(a.0, b.0);
+                let t = ra_delta(a.0, ra);
+                let between = if d > 0.0 {
+                    (0.0..d).contains(&t)
+                } else {
+                    d < t && t <= 0.0
+                };
+                between && a.1 + (b.1 - a.1) * t / d > dec
+            })
+            .count();
+        (crossings % 2 == 1) != self.around_north_pole()
+    }
+}
+
+/// The IAU constellation boundaries, as in the `bound_20.dat` file of
+/// VizieR catalog VI/49: one vertex per line, with right ascension in hours,
+/// declination in degrees and the constellation abbreviation, for instance
+/// `22.8714282 +35.1682587 AND`, maybe followed by whether the vertex is
+/// an original or an interpolated one, which is not needed. Consecutive vertices of a constellation
+/// make its boundary; Serpens comes in two parts, `SER1` and `SER2`.
+#[derive(Clone, Debug)]
+pub struct Boundaries {
+    regions: Vec<Region>,
+}
+
+impl Boundaries {
+    /// where the boundaries are looked for when a catalog is loaded
+    pub const DEFAULT_FILE: &'static str = "assets/constbnd.dat";
+
+    /// The boundaries in `DEFAULT_FILE`, read once and shared by every sky,
+    /// or none if it cannot be read.
+    pub fn shared() -> Option<&'static Self> {
+        static SHARED: OnceLock<Option<Boundaries>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Self::from_file(Self::DEFAULT_FILE).ok())
+            .as_ref()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(fname: P) -> Result<Self, SkyError> {
+        Self::parse(&fs::read_to_string(fname)?)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, SkyError> {
+        let mut regions: Vec<(String, Region)> = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let bad_line = || SkyError::bad_line(line).at_line(i + 1);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [ra, dec, name, ..] = fields[..] else {
+                if fields.is_empty() {
+                    continue;
+                }
+                return Err(bad_line());
+            };
+            let ra: f32 = ra.parse::<f32>().map_err(|_| bad_line())? * 15.0;
+            let dec: f32 = dec.parse().map_err(|_| bad_line())?;
+            let abbrev = name.trim_end_matches(char::is_numeric);
+            let constellation = *CONSTELLATIONS
+                .iter()
+                .find(|c| c.eq_ignore_ascii_case(abbrev))
+                .ok_or_else(bad_line)?;
+            match regions.last_mut() {
+                Some((last, region)) if last == name => region.vertices.push((ra, dec)),
+                _ => regions.push((
+                    name.to_string(),
+                    Region {
+                        constellation,
+                        vertices: vec![(ra, dec)],
+                    },
+                )),
+            }
+        }
+        Ok(Self {
+            regions: regions.into_iter().map(|(_, region)| region).collect(),
+        })
+    }
+
+    /// the abbreviation of the constellation `direction` is in
+    pub fn constellation_at(&self, direction: &Star) -> Option<&'static str> {
+        let (ra, dec) = ra_dec(direction);
+        self.regions
+            .iter()
+            .find(|region| region.contains(ra, dec))
+            .map(|region| region.constellation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Boundaries;
+    use crate::sky::direction_at;
+
+    #[test]
+    fn test_boundaries() {
+        // a box across RA 0h, a cap around the north pole and one around the south pole
+        let input = "23.0 +10.0 PSC\n1.0 +10.0 PSC\n1.0 +20.0 PSC\n23.0 +20.0 PSC\n\
+                     0.0 +80.0 UMI\n8.0 +80.0 UMI\n16.0 +80.0 UMI\n\
+                     0.0 -80.0 OCT\n8.0 -80.0 OCT\n16.0 -80.0 OCT\n";
+        let boundaries = Boundaries::parse(input).unwrap();
+        assert_eq!(
+            boundaries.constellation_at(&direction_at(0.0, 15.0)),
+            Some("Psc")
+        );
+        assert_eq!(
+            boundaries.constellation_at(&direction_at(350.0, 12.0)),
+            Some("Psc")
+        );
+        assert_eq!(boundaries.constellation_at(&direction_at(20.0, 15.0)), None);
+        assert_eq!(boundaries.constellation_at(&direction_at(0.0, 25.0)), None);
+        assert_eq!(
+            boundaries.constellation_at(&direction_at(100.0, 85.0)),
+            Some("UMi")
+        );
+        assert_eq!(
+            boundaries.constellation_at(&direction_at(100.0, -85.0)),
+            Some("Oct")
+        );
+        assert_eq!(boundaries.constellation_at(&direction_at(100.0, 0.0)), None);
+
+        let serpens = Boundaries::parse("15.0 0.0 SER1\n16.0 0.0 SER1\n16.0 10.0 SER1").unwrap();
+        assert_eq!(serpens.regions[0].constellation, "Ser");
+        assert!(Boundaries::parse("1.0 2.0 XYZ").is_err());
+        assert!(Boundaries::parse("1.0 AND").is_err());
+        let interpolated = Boundaries::parse("15.0 0.0 SER1 O\n16.0 0.0 SER1 I").unwrap();
+        assert_eq!(interpolated.regions[0].vertices.len(), 2);
+    }
+
+    /// The boundaries are not shipped with the sources: `cargo run --features
+    /// net fetch` downloads them, and `cargo test -- --ignored` checks them.
+    #[test]
+    #[ignore]
+    fn test_default_file() {
+        let boundaries = Boundaries::from_file(Boundaries::DEFAULT_FILE).unwrap();
+        // Betelgeuse, Sirius, Polaris and Acrux
+        for (ra, dec, constellation) in [
+            (88.79, 7.41, "Ori"),
+            (101.29, -16.72, "CMa"),
+            (37.95, 89.26, "UMi"),
+            (186.65, -63.1, "Cru"),
+        ] {
+            assert_eq!(
+                boundaries.constellation_at(&direction_at(ra, dec)),
+                Some(constellation)
+            );
+        }
+    }
+}
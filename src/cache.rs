@@ -0,0 +1,238 @@
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::sky::{Brightness, CatalogStar, Sky, Star};
+
+/// A parsed catalog stored in binary. The file starts with `MAGIC`, the
+/// `Sources` it was made from and the number of stars; then each star is its position, brightness, HR number (`u32::MAX`
+/// if none), color index (NaN if none), spectral class (0 if none), proper
+/// motion (NaN if none), whether it is variable and name, all little endian.
+const MAGIC: &[u8; 8] = b"cuyat\x00\x00\x05";
+
+/// What a cache is valid for: the modification times of the catalog and of
+/// its `.toml` format descriptor, if it has one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sources {
+    catalog: Duration,
+    format: Option<Duration>,
+}
+
+impl Sources {
+    pub fn new(catalog: SystemTime, format: Option<SystemTime>) -> Self {
+        Self {
+            catalog: timestamp(catalog),
+            format: format.map(timestamp),
+        }
+    }
+
+    /// the sources of the catalog in `catalog` as they are now
+    pub fn of(catalog: &Path) -> io::Result<Self> {
+        let format = fs::metadata(Sky::format_descriptor(catalog))
+            .and_then(|m| m.modified())
+            .ok();
+        Ok(Self::new(fs::metadata(catalog)?.modified()?, format))
+    }
+}
+
+pub fn write(fname: &Path, sources: &Sources, sky: &Sky) -> io::Result<()> {
+    fs::write(fname, encode(sources, sky))
+}
+
+/// the sky in `fname`, if it was made from `sources`
+pub fn read(fname: &Path, sources: &Sources) -> Option<Sky> {
+    decode(&fs::read(fname).ok()?, sources)
+}
+
+fn timestamp(time: SystemTime) -> Duration {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+fn encode_time(out: &mut Vec<u8>, time: Duration) {
+    out.extend(time.as_secs().to_le_bytes());
+    out.extend(time.subsec_nanos().to_le_bytes());
+}
+
+fn encode(sources: &Sources, sky: &Sky) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    encode_time(&mut out, sources.catalog);
+    out.push(sources.format.is_some() as u8);
+    encode_time(&mut out, sources.format.unwrap_or_default());
+    out.extend((sky.len() as u32).to_le_bytes());
+    for star in sky {
+        star.position()
+            .iter()
+            .for_each(|x| out.extend(x.to_le_bytes()));
+        out.extend(star.brightness().value().to_le_bytes());
+        out.extend(star.hr().unwrap_or(u32::MAX).to_le_bytes());
+        out.extend(star.color_index().unwrap_or(f32::NAN).to_le_bytes());
+        out.push(star.spectral_class().map_or(0, |c| c as u8));
+        let (pm_ra, pm_dec) = star.proper_motion().unwrap_or((f32::NAN, f32::NAN));
+        out.extend(pm_ra.to_le_bytes());
+        out.extend(pm_dec.to_le_bytes());
+        out.push(star.is_variable() as u8);
+        let name = star.name().as_bytes();
+        out.extend((name.len() as u16).to_le_bytes());
+        out.extend(name);
+    }
+    out
+}
+
+/// Reads little endian values from the start of a slice.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*head)
+    }
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+    /// a duration, unless its nanoseconds are a whole second or more
+    fn duration(&mut self) -> Option<Duration> {
+        let (secs, nanos) = (self.u64()?, self.u32()?);
+        (nanos < 1_000_000_000).then(|| Duration::new(secs, nanos))
+    }
+    fn f32(&mut self) -> Option<f32> {
+        self.take().map(f32::from_le_bytes)
+    }
+    fn string(&mut self, len: usize) -> Option<String> {
+        let bytes = self.0.get(..len)?;
+        self.0 = &self.0[len..];
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+fn decode(input: &[u8], sources: &Sources) -> Option<Sky> {
+    let mut reader = Reader(input);
+    if &reader.take::<8>()? != MAGIC {
+        return None;
+    }
+    let catalog = reader.duration()?;
+    let has_format = reader.take::<1>()?[0] != 0;
+    let format = reader.duration()?;
+    let cached = Sources {
+        catalog,
+        format: has_format.then_some(format),
+    };
+    if cached != *sources {
+        return None;
+    }
+    let nstars = reader.u32()?;
+    let stars = (0..nstars)
+        .map(|_| {
+            let position = Star::new(reader.f32()?, reader.f32()?, reader.f32()?);
+            let brightness = Brightness::new(reader.f32()?);
+            let hr = reader.u32()?;
+            let color_index = reader.f32()?;
+            let class = reader.take::<1>()?[0];
+            let (pm_ra, pm_dec) = (reader.f32()?, reader.f32()?);
+            let variable = reader.take::<1>()?[0] != 0;
+            let len = reader.u16()? as usize;
+            let mut star = CatalogStar::new(position, brightness, reader.string(len)?);
+            if hr != u32::MAX {
+                star = star.with_hr(hr);
+            }
+            if !color_index.is_nan() {
+                star = star.with_color_index(color_index);
+            }
+            if class != 0 {
+                star = star.with_spectral_class(class as char);
+            }
+            if !pm_ra.is_nan() {
+                star = star.with_proper_motion(pm_ra, pm_dec);
+            }
+            if variable {
+                star = star.with_variable();
+            }
+            Some(star)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    reader.0.is_empty().then(|| Sky::from(&stars))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{decode, encode, read, Sources, MAGIC};
+    use crate::sky::{Brightness, CatalogStar, Sky, Star};
+
+    #[test]
+    fn test_roundtrip() {
+        let sky = Sky::from(&[
+            CatalogStar::new(Star::new(0.0, 0.6, 0.8), Brightness::new(0.5), "α Ori")
+                .with_hr(2061)
+                .with_color_index(1.85)
+                .with_spectral_class('M')
+                .with_proper_motion(0.026, 0.009)
+                .with_variable(),
+            CatalogStar::new(Star::new(1.0, 0.0, 0.0), Brightness::new(0.01), ""),
+        ]);
+        let modified = UNIX_EPOCH + Duration::new(1_700_000_000, 42);
+        let sources = Sources::new(modified, None);
+        let bytes = encode(&sources, &sky);
+        let decoded = decode(&bytes, &sources).unwrap();
+        assert_eq!(decoded.stars, sky.stars);
+
+        let later = Sources::new(modified + Duration::from_secs(1), None);
+        assert!(decode(&bytes, &later).is_none());
+        assert!(decode(&bytes, &Sources::new(modified, Some(modified))).is_none());
+        assert!(decode(&bytes[..bytes.len() - 1], &sources).is_none());
+        assert!(decode(b"not a cache", &sources).is_none());
+
+        let described = Sources::new(modified, Some(modified + Duration::from_secs(5)));
+        let bytes = encode(&described, &sky);
+        assert!(decode(&bytes, &described).is_some());
+        assert!(decode(&bytes, &sources).is_none());
+        assert!(decode(&bytes, &Sources::new(modified, Some(modified))).is_none());
+    }
+
+    #[test]
+    fn test_bad_nanoseconds() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(u64::MAX.to_le_bytes());
+        bytes.extend(u32::MAX.to_le_bytes());
+        bytes.extend([0; 13]);
+        bytes.extend(0u32.to_le_bytes());
+        assert!(decode(&bytes, &Sources::new(UNIX_EPOCH, None)).is_none());
+    }
+
+    #[test]
+    fn test_cache_file() {
+        // the cache goes next to the catalog, so both are kept out of the sources
+        let dir = std::env::temp_dir();
+        let catalog = dir.join("cuyat_cached.csv");
+        let cache = dir.join("cuyat_cached.cache");
+        let _ = std::fs::remove_file(&cache);
+        std::fs::write(
+            &catalog,
+            "# epoch 2000.0\nα Ori,055510.3,+072425,0.50\nα CMa,064508.9,-164258,-1.46",
+        )
+        .unwrap();
+        let filename = Some(catalog.to_string_lossy().into_owned());
+        let (sky, _) = Sky::load_cached(&filename, 10).unwrap();
+        let cached = read(&cache, &Sources::of(&catalog).unwrap()).unwrap();
+        assert_eq!(cached.len(), 2);
+        let (again, _) = Sky::load_cached(&filename, 10).unwrap();
+        assert_eq!(again.stars, sky.stars);
+
+        // a new format descriptor makes the cache stale
+        let descriptor = dir.join("cuyat_cached.toml");
+        std::fs::write(&descriptor, "").unwrap();
+        assert!(read(&cache, &Sources::of(&catalog).unwrap()).is_none());
+        std::fs::remove_file(&descriptor).unwrap();
+        std::fs::remove_file(&catalog).unwrap();
+        std::fs::remove_file(&cache).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{config_dir, load_toml, save_toml},
+    difficulty::{Difficulty, Sight},
+    level::{Level, Rules},
+    sky::random_quaternion,
+    view::{cursive_window, Scoring, SkyView},
+};
+
+/// One level of the campaign: a level with how much of the sky is shown.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    /// its stars and rules, and in `par` the score to reach to unlock the next
+    pub level: Level,
+    pub sight: Sight,
+}
+
+impl Stage {
+    fn new(name: &str, sight: Sight) -> Self {
+        Self {
+            level: Level {
+                name: String::from(name),
+                catalog: Some(String::from("assets/bsc5.csv")),
+                nstars: sight.nstars,
+                seed: None,
+                target: None,
+                rules: Rules::default(),
+                par: None,
+            },
+            sight,
+        }
+    }
+
+    fn with_par(mut self, par: f32) -> Self {
+        self.level.par = Some(par);
+        self
+    }
+
+    fn with_time_limit(mut self, seconds: u64) -> Self {
+        self.level.rules.time_limit = Some(Duration::from_secs(seconds));
+        self
+    }
+
+    /// the score to reach, or less, to pass it
+    pub fn par(&self) -> f32 {
+        self.level.par.unwrap_or(f32::INFINITY)
+    }
+}
+
+/// The levels of the campaign, each harder than the one before: more stars,
+/// no names, a narrower field of view, less time: from the sights of the
+/// difficulties on.
+pub fn stages() -> Vec<Stage> {
+    let nameless = Sight {
+        show_star_names: false,
+        ..Difficulty::Medium.sight()
+    };
+    let finder = Sight {
+        nstars: 600,
+        fov_scale: 0.6,
+        ..Difficulty::Hard.sight()
+    };
+    let telescope = Sight {
+        nstars: 1000,
+        fov_scale: 0.3,
+        ..Difficulty::Hard.sight()
+    };
+    vec![
+        Stage::new(
+            "First light",
+            Sight {
+                nstars: 200,
+                ..Difficulty::Easy.sight()
+            },
+        )
+        .with_par(1.5),
+        Stage::new("A crowded sky", Difficulty::Medium.sight()).with_par(1.2),
+        Stage::new("Nameless", nameless).with_par(1.0),
+        Stage::new("Through the finder", finder).with_par(0.8),
+        Stage::new("Against the clock", finder)
+            .with_par(0.6)
+            .with_time_limit(120),
+        Stage::new("The telescope", telescope)
+            .with_par(0.5)
+            .with_time_limit(90),
+    ]
+}
+
+/// How far a player got in the campaign, kept between runs.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Progress {
+    /// how many stages were passed; the one after them is unlocked too
+    pub passed: usize,
+    /// the best score of each stage played, from the first one: as only
+    /// those unlocked can be played, there are no gaps
+    pub best: Vec<f32>,
+}
+
+impl Progress {
+    /// where it is kept: `campaign.toml` next to the profiles
+    pub fn path() -> Option<PathBuf> {
+        Some(config_dir()?.join("campaign.toml"))
+    }
+
+    /// whether `stage` (from 0) can be played
+    pub fn is_unlocked(&self, stage: usize) -> bool {
+        stage <= self.passed
+    }
+
+    /// Keeps `score` for `stage` if it is its best, and tells whether it
+    /// passed it for the first time, unlocking the next one.
+    pub fn record(&mut self, stage: usize, score: f32, par: f32) -> bool {
+        match self.best.get_mut(stage) {
+            Some(best) => *best = best.min(score),
+            None => self.best.push(score),
+        }
+        let unlocked = score <= par && stage == self.passed;
+        if unlocked {
+            self.passed += 1;
+        }
+        unlocked
+    }
+}
+
+/// Plays `stage` of the campaign (from 1), or the last one unlocked, in the
+/// terminal: its best game counts, and a score within its par unlocks the
+/// next stage. Prints how it went and the stages so far.
+pub fn run(stage: Option<usize>, scoring: Rc<RefCell<Scoring>>) -> Result<(), String> {
+    let path = Progress::path().ok_or("no config directory")?;
+    let mut progress: Progress = load_toml(&path)?;
+    let stages = stages();
+    let last = progress.passed.min(stages.len() - 1);
+    let index = match stage {
+        Some(0) => return Err(String::from("stages start at 1")),
+        Some(stage) if stage > stages.len() => {
+            return Err(format!("there are {} stages", stages.len()))
+        }
+        Some(stage) if !progress.is_unlocked(stage - 1) => {
+            return Err(format!(
+                "stage {stage} is locked, pass stage {} first",
+                last + 1
+            ))
+        }
+        Some(stage) => stage - 1,
+        None => last,
+    };
+    let chosen = &stages[index];
+    let games_before = (*scoring).borrow().games();
+    let sky_view = SkyView::with_attitudes(
+        chosen.level.catalog.clone(),
+        chosen.level.nstars,
+        Rc::clone(&scoring),
+        random_quaternion(),
+        random_quaternion(),
+    )
+    .with_rules(chosen.level.rules.clone())
+    .with_sight(chosen.sight);
+    cursive_window(sky_view);
+
+    let best = (*scoring).borrow().total[games_before..]
+        .iter()
+        .copied()
+        .reduce(f32::min);
+    if let Some(best) = best {
+        let unlocked = progress.record(index, best, chosen.par());
+        save_toml(&path, &progress)?;
+        println!(
+            "stage {} ({}): best score {best:.6}, par {:.6}{}",
+            index + 1,
+            chosen.level.name,
+            chosen.par(),
+            if unlocked && index + 1 < stages.len() {
+                format!(", stage {} unlocked!", index + 2)
+            } else {
+                String::new()
+            }
+        );
+    }
+    for (i, stage) in stages.iter().enumerate() {
+        let state = match progress.best.get(i) {
+            _ if !progress.is_unlocked(i) => String::from("locked"),
+            Some(best) => format!("best {best:.6}"),
+            None => String::from("not played"),
+        };
+        println!("  {}. {}: {state}", i + 1, stage.level.name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{stages, Progress};
+
+    #[test]
+    fn test_stages() {
+        let stages = stages();
+        for pair in stages.windows(2) {
+            let (easier, harder) = (&pair[0], &pair[1]);
+            assert!(easier.sight.nstars <= harder.sight.nstars);
+            assert!(easier.sight.fov_scale >= harder.sight.fov_scale);
+            assert!(easier.par() > harder.par());
+            assert!(easier.sight.show_star_names || !harder.sight.show_star_names);
+            assert_eq!(easier.level.nstars, easier.sight.nstars);
+        }
+        assert!(stages.last().unwrap().level.rules.time_limit.is_some());
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress::default();
+        assert!(progress.is_unlocked(0));
+        assert!(!progress.is_unlocked(1));
+        // above par, nothing is unlocked, but the score is kept
+        assert!(!progress.record(0, 2.0, 1.5));
+        assert_eq!(progress.best, [2.0]);
+        assert!(progress.record(0, 1.0, 1.5));
+        assert!(progress.is_unlocked(1));
+        // passing it again unlocks nothing more, and keeps the best score
+        assert!(!progress.record(0, 1.2, 1.5));
+        assert_eq!(progress.best, [1.0]);
+        assert_eq!(progress.passed, 1);
+    }
+}
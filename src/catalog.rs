@@ -0,0 +1,88 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::sky::SkyError;
+
+/// The Yale Bright Star Catalog at VizieR, in the fixed width format read by
+/// `Sky::from_catalog_file`.
+pub const BSC_URL: &str = "https://cdsarc.cds.unistra.fr/ftp/V/50/catalog";
+
+/// The IAU constellation boundaries at VizieR, as `Boundaries::parse` reads them.
+pub const BOUNDARIES_URL: &str = "https://cdsarc.cds.unistra.fr/ftp/VI/49/bound_20.dat";
+
+/// where downloaded catalogs are kept: `$XDG_CACHE_HOME/cuyat`, or `~/.cache/cuyat`
+pub fn cache_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+    Some(dir.join("cuyat"))
+}
+
+/// the file in `dir` a catalog downloaded from `url` is saved to
+fn cached_file(dir: &Path, url: &str) -> PathBuf {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path
+        .rsplit('/')
+        .find(|segment| !segment.is_empty() && !segment.contains(':'))
+        .unwrap_or("catalog");
+    dir.join(name)
+}
+
+/// Downloads the catalog at `url` to the cache directory, unless it was
+/// already downloaded, and returns where it is. It runs `curl`, so there
+/// are no TLS dependencies to build.
+pub fn fetch(url: &str) -> Result<PathBuf, SkyError> {
+    let dir = cache_dir().ok_or_else(|| io::Error::other("no cache directory"))?;
+    let file = cached_file(&dir, url);
+    if file.exists() {
+        return Ok(file);
+    }
+    fs::create_dir_all(&dir)?;
+    // a partial download must not look like a cached catalog
+    let partial = file.with_extension("part");
+    let status = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--output",
+        ])
+        .arg(&partial)
+        .arg(url)
+        .status()?;
+    if !status.success() {
+        let _ = fs::remove_file(&partial);
+        return Err(io::Error::other(format!("could not download {url}: curl {status}")).into());
+    }
+    fs::rename(&partial, &file)?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::cached_file;
+
+    #[test]
+    fn test_cached_file() {
+        let dir = Path::new("/cache");
+        assert_eq!(
+            cached_file(dir, super::BSC_URL),
+            Path::new("/cache/catalog")
+        );
+        assert_eq!(
+            cached_file(dir, "https://example.org/stars/tycho.csv?rows=10#top"),
+            Path::new("/cache/tycho.csv")
+        );
+        assert_eq!(
+            cached_file(dir, "https://example.org/"),
+            Path::new("/cache/example.org")
+        );
+    }
+}
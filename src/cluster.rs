@@ -0,0 +1,149 @@
+use nalgebra::Vector3;
+use rand::Rng;
+
+use crate::sky::{Brightness, Sky, Star};
+
+/// how close, in radii of the cluster, the view has to come to where the
+/// target is seen from
+pub const TOLERANCE: f32 = 0.02;
+/// the closest a star is taken to be, in radii, so that it is not
+/// infinitely bright
+const MIN_DISTANCE: f32 = 0.05;
+/// how much a radius away from where the target is seen from counts against
+/// a radian off its attitude, when both are to be matched: about as much as
+/// being just out of both tolerances
+pub const POSITION_WEIGHT: f32 = 0.5;
+
+/// A cluster of stars about the origin, where both the target and the view
+/// are seen from somewhere in it: they point the same way, and what is to
+/// be found is where the target is seen from.
+#[derive(Clone, Debug)]
+pub struct Cluster {
+    /// where its stars are, within a radius of 1, and how bright they are
+    /// from 1 away
+    stars: Sky,
+    /// where the target is seen from
+    pub target: Star,
+    /// where the view is seen from
+    pub position: Star,
+}
+
+impl Cluster {
+    /// A cluster of `nstars` at random, with the target and the view seen
+    /// from two points at random within half of its radius.
+    pub fn random_with<R: Rng>(nstars: usize, rng: &mut R) -> Self {
+        let stars = Sky::random_with_rng(nstars, rng)
+            .iter()
+            .map(|star| star.moved_to(star.position() * rng.gen::<f32>().cbrt()))
+            .collect();
+        Self {
+            stars,
+            target: random_point(rng) / 2.0,
+            position: random_point(rng) / 2.0,
+        }
+    }
+
+    /// The cluster as seen from `from`: each star in its direction from
+    /// there, and fainter the farther it is.
+    pub fn seen_from(&self, from: Star) -> Sky {
+        self.stars
+            .seen_from(from)
+            .iter()
+            .map(|star| {
+                let distance = star.position().norm().max(MIN_DISTANCE);
+                let magnitude = star.magnitude() + 5.0 * distance.log10();
+                star.moved_to(star.position() / distance)
+                    .with_brightness(Brightness::from_magnitude(magnitude))
+            })
+            .collect()
+    }
+
+    pub fn target_sky(&self) -> Sky {
+        self.seen_from(self.target)
+    }
+
+    pub fn view_sky(&self) -> Sky {
+        self.seen_from(self.position)
+    }
+
+    /// Moves the view by `offset`, in radii of the cluster.
+    pub fn translate(&mut self, offset: Star) {
+        self.position += offset;
+    }
+
+    /// how far the view is from where the target is seen from, in radii
+    pub fn distance(&self) -> f32 {
+        (self.position - self.target).norm()
+    }
+
+    pub fn is_reached(&self) -> bool {
+        self.distance() < TOLERANCE
+    }
+
+    /// how far the view is from the target in both attitude, `angle` off it,
+    /// and position
+    pub fn pose_distance(&self, angle: f32) -> f32 {
+        angle + POSITION_WEIGHT * self.distance()
+    }
+}
+
+/// a point at random within a radius of 1
+fn random_point<R: Rng>(rng: &mut R) -> Star {
+    loop {
+        let point = Vector3::from_fn(|_, _| rng.gen_range(-1.0..1.0));
+        if point.norm() <= 1.0 {
+            return point;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::Cluster;
+    use crate::sky::{Brightness, CatalogStar, Sky, Star};
+
+    #[test]
+    fn test_cluster() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut cluster = Cluster::random_with(200, &mut rng);
+        assert_eq!(cluster.target_sky().len(), 200);
+        assert!(cluster.target.norm() <= 0.5 && cluster.position.norm() <= 0.5);
+        assert!(cluster
+            .stars
+            .iter()
+            .all(|star| star.position().norm() <= 1.0));
+
+        cluster.translate(cluster.target - cluster.position + Star::new(0.01, 0.0, 0.0));
+        assert_relative_eq!(cluster.distance(), 0.01, epsilon = 1e-5);
+        assert!(cluster.is_reached());
+        cluster.translate(Star::new(0.0, 0.1, 0.0));
+        assert!(!cluster.is_reached());
+        // off both where and how it is seen, it is farther than off either
+        let off = cluster.pose_distance(0.2);
+        assert!(off > 0.2 && off > cluster.pose_distance(0.0));
+        cluster.position = cluster.target;
+        assert_relative_eq!(cluster.pose_distance(0.2), 0.2);
+    }
+
+    #[test]
+    fn test_seen_from() {
+        let cluster = Cluster {
+            stars: Sky::from(&[CatalogStar::new(
+                Star::new(0.0, 0.0, 1.0),
+                Brightness::from_magnitude(2.0),
+                "a",
+            )]),
+            target: Star::zeros(),
+            position: Star::new(0.0, 0.0, -9.0),
+        };
+        // as bright as it is from 1 away, 5 magnitudes fainter 10 times as far
+        let near = &cluster.target_sky()[0];
+        assert_relative_eq!(near.magnitude(), 2.0, epsilon = 1e-3);
+        let far = &cluster.view_sky()[0];
+        assert_relative_eq!(far.magnitude(), 7.0, epsilon = 1e-3);
+        assert_relative_eq!(far.position().norm(), 1.0);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{path::PathBuf, str::FromStr};
+
+/// the commands there are, to tell when one is not known
+const NAMES: &str = "catalog, nstars, seed, save, load, go, find, record, replay";
+
+/// A command typed after `:` in the terminal, for the options that do not
+/// need a key of their own.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// play with the stars of a catalog file, or random ones with none
+    Catalog(Option<String>),
+    /// play with this many stars
+    NStars(usize),
+    /// new games drawn from a generator with this seed
+    Seed(u64),
+    /// save the game as JSON, to this file or to the usual one
+    Save(Option<PathBuf>),
+    /// go back to a game saved as JSON, in this file or in the usual one
+    Load(Option<PathBuf>),
+    /// jump to the attitude written
+    Go(String),
+    /// select the star with this name
+    Find(String),
+    /// start recording the turns of the view, or stop
+    Record,
+    /// replay the turns recorded, each this many times as large
+    Replay(f32),
+}
+
+impl Command {
+    /// Reads a command, its name and then its argument. An attitude alone,
+    /// starting with a number, is a `go`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (name, argument) = text.split_once(' ').unwrap_or((text, ""));
+        let argument = argument.trim();
+        let path = || (!argument.is_empty()).then(|| PathBuf::from(argument));
+        match name {
+            "" => Err(String::from("no command")),
+            "catalog" if argument.is_empty() || argument == "random" => Ok(Command::Catalog(None)),
+            "catalog" => Ok(Command::Catalog(Some(argument.to_owned()))),
+            "nstars" => match number(name, argument)? {
+                0 => Err(String::from("nstars needs at least one star")),
+                n => Ok(Command::NStars(n)),
+            },
+            "seed" => Ok(Command::Seed(number(name, argument)?)),
+            "save" => Ok(Command::Save(path())),
+            "load" => Ok(Command::Load(path())),
+            "go" => Ok(Command::Go(argument.to_owned())),
+            "find" => Ok(Command::Find(argument.to_owned())),
+            "record" => Ok(Command::Record),
+            "replay" if argument.is_empty() => Ok(Command::Replay(1.0)),
+            "replay" => Ok(Command::Replay(number(name, argument)?)),
+            _ if name.parse::<f32>().is_ok() => Ok(Command::Go(text.to_owned())),
+            _ => Err(format!("unknown command '{name}' (try {NAMES})")),
+        }
+    }
+}
+
+/// the argument of the command `name` as a number
+fn number<T: FromStr>(name: &str, argument: &str) -> Result<T, String> {
+    argument
+        .parse()
+        .map_err(|_| format!("{name} needs a number, not '{argument}'"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::Command;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Command::parse("catalog assets/hip.csv"),
+            Ok(Command::Catalog(Some(String::from("assets/hip.csv"))))
+        );
+        assert_eq!(
+            Command::parse(" catalog random"),
+            Ok(Command::Catalog(None))
+        );
+        assert_eq!(Command::parse("nstars 500"), Ok(Command::NStars(500)));
+        assert_eq!(Command::parse("seed  42 "), Ok(Command::Seed(42)));
+        assert_eq!(
+            Command::parse("save game.json"),
+            Ok(Command::Save(Some(PathBuf::from("game.json"))))
+        );
+        assert_eq!(Command::parse("load"), Ok(Command::Load(None)));
+        assert_eq!(
+            Command::parse("go 10 20 30"),
+            Ok(Command::Go(String::from("10 20 30")))
+        );
+        // an attitude alone, as before there were commands
+        assert_eq!(
+            Command::parse("-10 20 30"),
+            Ok(Command::Go(String::from("-10 20 30")))
+        );
+        assert_eq!(
+            Command::parse("find betelgeuse"),
+            Ok(Command::Find(String::from("betelgeuse")))
+        );
+
+        assert_eq!(Command::parse("record"), Ok(Command::Record));
+        assert_eq!(Command::parse("replay"), Ok(Command::Replay(1.0)));
+        assert_eq!(Command::parse("replay -0.5"), Ok(Command::Replay(-0.5)));
+        assert_eq!(
+            Command::parse("nstars many"),
+            Err(String::from("nstars needs a number, not 'many'"))
+        );
+        assert!(Command::parse("nstars 0").is_err());
+        assert!(Command::parse("seed").is_err());
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("fly away")
+            .unwrap_err()
+            .starts_with("unknown command 'fly'"));
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use nalgebra::UnitQuaternion;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    sky::{FoV, Loaded, Sky, SkyError},
+    view::{Options, Scoring},
+};
+
+/// What is remembered between runs, for each profile.
+/// It is kept in `$XDG_CONFIG_HOME/cuyat/<profile>.toml`
+/// (or `~/.config/cuyat/<profile>.toml`).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub cli: Option<ViewConfig>,
+    pub gui: Option<ViewConfig>,
+    pub window: WindowConfig,
+    pub gamepad: GamepadConfig,
+    pub invert: InvertConfig,
+}
+
+/// Geometry of the GUI window
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+    /// frames per second to draw at most. With 0, the screen refresh (vsync) sets the pace.
+    pub target_fps: u32,
+    /// how much bigger than usual stars and texts are drawn, at any pixel density
+    pub render_scale: f32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 600,
+            fullscreen: false,
+            target_fps: 0,
+            render_scale: 1.0,
+        }
+    }
+}
+
+/// How far the sticks and triggers of a gamepad move before they count
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct GamepadConfig {
+    /// the part of the way to the edge a stick can rest at without turning the view
+    pub stick_dead_zone: f32,
+    /// the part of the way a trigger can rest at without zooming
+    pub trigger_dead_zone: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            stick_dead_zone: 0.15,
+            trigger_dead_zone: 0.1,
+        }
+    }
+}
+
+/// Which ways of turning, by keys or gamepad, go the other way, for the
+/// players who see them so
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct InvertConfig {
+    pub pitch: bool,
+    pub yaw: bool,
+    pub roll: bool,
+}
+
+impl InvertConfig {
+    /// turns of pitch, yaw and roll, each the other way if it is inverted
+    pub fn apply(&self, [pitch, yaw, roll]: [f32; 3]) -> [f32; 3] {
+        let sign = |inverted| if inverted { -1.0 } else { 1.0 };
+        [
+            sign(self.pitch) * pitch,
+            sign(self.yaw) * yaw,
+            sign(self.roll) * roll,
+        ]
+    }
+}
+
+/// The state of a view that outlives a game
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ViewConfig {
+    pub options: Options,
+    pub fov: (f32, f32),
+    pub step: f32,
+}
+
+/// A game in progress, with the score so far, as saved to a file.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Session {
+    pub options: Options,
+    pub fov: FoV,
+    pub step: f32,
+    pub target_q: UnitQuaternion<f32>,
+    pub real_q: UnitQuaternion<f32>,
+    pub scoring: Scoring,
+    /// the stars as the view keeps them, that is, already rotated by `target_q`
+    pub sky: Sky,
+}
+
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("cuyat"))
+}
+
+/// Where users add stars of their own to whatever catalog is loaded:
+/// `custom_stars.csv` next to the profiles, in the converted catalog format
+/// or in one described by `custom_stars.toml`.
+pub fn custom_stars_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("custom_stars.csv"))
+}
+
+/// the stars in `path`, or none if there is no such file, with the lines
+/// that were skipped
+pub fn custom_stars(path: &Path) -> Result<Loaded, SkyError> {
+    if !path.exists() {
+        return Ok((Sky::from(&[]), vec![]));
+    }
+    let fname = path.to_string_lossy();
+    let format = Sky::catalog_format(&fname)?;
+    Sky::from_file_with_format(&fname, &format)
+}
+
+pub(crate) fn write(path: &Path, output: String) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    fs::write(path, output).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// The value kept as TOML in `path`, or the default one if there is no such
+/// file. One that cannot be read is an error, as it would be saved over.
+pub(crate) fn load_toml<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    match fs::read_to_string(path) {
+        Ok(input) => toml::from_str(&input).map_err(|e| format!("{}: {e}", path.display())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+pub(crate) fn save_toml<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    write(path, toml::to_string(value).map_err(|e| e.to_string())?)
+}
+
+impl Session {
+    /// where the game is saved: `session.json` next to the profiles
+    pub fn path() -> Option<PathBuf> {
+        Some(config_dir()?.join("session.json"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let input = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        serde_json::from_str(&input).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        write(
+            path,
+            serde_json::to_string(self).map_err(|e| e.to_string())?,
+        )
+    }
+}
+
+impl Config {
+    pub fn path(profile: &str) -> Option<PathBuf> {
+        Some(config_dir()?.join(format!("{profile}.toml")))
+    }
+
+    /// The saved configuration of `profile`, or the default one if there is
+    /// none. One that cannot be read is an error, not the default: that would
+    /// be saved over it when the game ends.
+    pub fn load(profile: &str) -> Result<Self, String> {
+        match Self::path(profile) {
+            Some(path) => load_toml(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, profile: &str) -> Result<(), String> {
+        save_toml(&Self::path(profile).ok_or("no config directory")?, self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::UnitQuaternion;
+
+    use super::{
+        custom_stars, load_toml, save_toml, Config, GamepadConfig, InvertConfig, Session,
+        ViewConfig, WindowConfig,
+    };
+    use crate::{
+        sky::{Brightness, CatalogStar, FoV, Sky, Star},
+        view::{GameMetadata, Options, Scoring},
+    };
+
+    #[test]
+    fn test_roundtrip() {
+        let config = Config {
+            cli: Some(ViewConfig {
+                options: Options::default(),
+                fov: (2.0, 1.5),
+                step: 0.25,
+            }),
+            gui: None,
+            window: WindowConfig {
+                width: 800,
+                height: 500,
+                fullscreen: true,
+                target_fps: 0,
+                render_scale: 1.5,
+            },
+            gamepad: GamepadConfig {
+                stick_dead_zone: 0.25,
+                trigger_dead_zone: 0.1,
+            },
+            invert: InvertConfig {
+                pitch: true,
+                ..InvertConfig::default()
+            },
+        };
+        let output = toml::to_string(&config).unwrap();
+        let restored: Config = toml::from_str(&output).unwrap();
+        let cli = restored.cli.unwrap();
+        assert_eq!(cli.fov, (2.0, 1.5));
+        assert_eq!(cli.step, 0.25);
+        assert_eq!(cli.options.nstars, Options::default().nstars);
+        assert!(restored.gui.is_none());
+        assert_eq!(restored.window, config.window);
+        assert_eq!(restored.gamepad, config.gamepad);
+        assert_eq!(restored.invert, config.invert);
+
+        let empty: Config = toml::from_str("").unwrap();
+        assert!(empty.cli.is_none());
+        assert_eq!(empty.window, WindowConfig::default());
+        assert_eq!(empty.gamepad, GamepadConfig::default());
+        assert_eq!(empty.invert, InvertConfig::default());
+    }
+
+    #[test]
+    fn test_invert() {
+        let turns = [0.1, -0.2, 0.3];
+        assert_eq!(InvertConfig::default().apply(turns), turns);
+        let invert: InvertConfig = toml::from_str("pitch = true\nroll = true").unwrap();
+        assert_eq!(invert.apply(turns), [-0.1, -0.2, -0.3]);
+    }
+
+    #[test]
+    fn test_session() {
+        let mut scoring = Scoring::default();
+        scoring.add_move();
+        scoring.score_and_reset(0.5, GameMetadata::default());
+        scoring.add_move();
+        let session = Session {
+            options: Options::default(),
+            fov: FoV::new(2.0, 1.0).with_vignetting(true),
+            step: 0.5,
+            target_q: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+            real_q: UnitQuaternion::identity(),
+            scoring,
+            sky: Sky::from(&[
+                CatalogStar::new(Star::new(0.0, 0.6, 0.8), Brightness::new(0.5), "α Ori")
+                    .with_hr(2061),
+                CatalogStar::new(Star::new(1.0, 0.0, 0.0), Brightness::new(0.1), ""),
+            ]),
+        };
+        let path = std::env::temp_dir().join("cuyat_session.json");
+        session.save(&path).unwrap();
+        let restored = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.sky.stars, session.sky.stars);
+        assert_eq!(restored.target_q, session.target_q);
+        assert_eq!(restored.fov.half_fov(), (2.0, 1.0));
+        assert!(restored.fov.vignetting());
+        assert_eq!(restored.scoring.total, vec![10.5]);
+        assert_eq!(restored.scoring.moves, 1);
+    }
+
+    #[test]
+    fn test_toml_file() {
+        let path = std::env::temp_dir().join("cuyat_invert.toml");
+        let invert = InvertConfig {
+            pitch: true,
+            ..InvertConfig::default()
+        };
+        save_toml(&path, &invert).unwrap();
+        assert_eq!(load_toml::<InvertConfig>(&path).unwrap(), invert);
+        std::fs::write(&path, "pitch = 3").unwrap();
+        assert!(load_toml::<InvertConfig>(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        // none yet
+        assert_eq!(
+            load_toml::<InvertConfig>(&path).unwrap(),
+            InvertConfig::default()
+        );
+        // but a profile that cannot be read is not taken for a new one
+        let unreadable = std::env::temp_dir().join("cuyat_unreadable_profile");
+        std::fs::create_dir_all(&unreadable).unwrap();
+        let error = load_toml::<Config>(&unreadable).unwrap_err();
+        assert!(error.starts_with(&unreadable.display().to_string()));
+    }
+
+    #[test]
+    fn test_custom_stars() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cuyat_custom_stars.csv");
+        std::fs::write(&path, "Home,120000.0,+450000,1.0\nbad line").unwrap();
+        let (sky, skipped) = custom_stars(&path).unwrap();
+        assert_eq!(sky.len(), 1);
+        assert_eq!(sky[0].name(), "Home");
+        assert_eq!(skipped[0].to_string(), "line 2: no star in \"bad line\"");
+
+        let missing = dir.join("cuyat_no_custom_stars.csv");
+        assert!(custom_stars(&missing).unwrap().0.is_empty());
+    }
+}
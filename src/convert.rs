@@ -0,0 +1,202 @@
+use std::{fs, path::Path};
+
+use crate::{
+    cache,
+    format::CatalogFormat,
+    sky::{Epoch, Sky, SkyError},
+};
+
+/// The catalogs `convert` reads.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    /// the Yale Bright Star Catalog, as distributed
+    Bsc,
+    /// the Hipparcos main catalog, as distributed
+    Hipparcos,
+    /// anything `Sky::load` reads: converted catalogs, Gaia exports and
+    /// catalogs with a format descriptor
+    Csv,
+}
+
+impl InputFormat {
+    /// the format of a catalog that starts with `line`
+    pub fn detect(line: &str) -> Self {
+        if line.starts_with("H|") {
+            InputFormat::Hipparcos
+        } else if line.len() >= 107 && !line.contains(',') {
+            InputFormat::Bsc
+        } else {
+            InputFormat::Csv
+        }
+    }
+}
+
+/// What `convert` writes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// the CSV read by default, as written by `Sky::convert_catalog_file`
+    Csv,
+    /// the binary cache `Sky::load_cached` reads, valid for the input catalog
+    Cache,
+}
+
+/// A conversion of a catalog, as given to `cuyat convert`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conversion {
+    pub input: String,
+    pub output: String,
+    pub input_format: InputFormat,
+    pub output_format: OutputFormat,
+    pub max_magnitude: f32,
+}
+
+impl Conversion {
+    /// Reads `convert <input> [<output>] [--max-magnitude <m>] [--output-format csv|cache]`.
+    /// The input format is guessed from its first line; the output is the
+    /// input with a `.csv` or `.cache` extension unless it is given.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let usage =
+            "usage: convert <input> [<output>] [--max-magnitude <m>] [--output-format csv|cache]";
+        let mut files = vec![];
+        let mut max_magnitude = 6.5;
+        let mut output_format = OutputFormat::Csv;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--max-magnitude" => {
+                    max_magnitude = args
+                        .next()
+                        .and_then(|m| m.parse().ok())
+                        .ok_or(format!("--max-magnitude needs a number; {usage}"))?;
+                }
+                "--output-format" => {
+                    output_format = match args.next().map(String::as_str) {
+                        Some("csv") => OutputFormat::Csv,
+                        Some("cache") => OutputFormat::Cache,
+                        _ => return Err(format!("--output-format is csv or cache; {usage}")),
+                    };
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {arg}; {usage}")),
+                _ => files.push(arg.clone()),
+            }
+        }
+        let (input, output) = match &files[..] {
+            [input] => {
+                let extension = match output_format {
+                    OutputFormat::Csv => "csv",
+                    OutputFormat::Cache => "cache",
+                };
+                let output = Path::new(input).with_extension(extension);
+                (input.clone(), output.to_string_lossy().into_owned())
+            }
+            [input, output] => (input.clone(), output.clone()),
+            _ => return Err(usage.to_string()),
+        };
+        if input == output {
+            return Err(format!("{input} would be overwritten"));
+        }
+        let first_line = fs::read_to_string(&input)
+            .map_err(|e| format!("{input}: {e}"))?
+            .lines()
+            .next()
+            .map(InputFormat::detect);
+        Ok(Self {
+            input,
+            output,
+            input_format: first_line.unwrap_or(InputFormat::Csv),
+            output_format,
+            max_magnitude,
+        })
+    }
+
+    /// Converts the catalog, returning how many stars were written.
+    pub fn run(&self) -> Result<usize, SkyError> {
+        let sky = self.read()?;
+        match self.output_format {
+            OutputFormat::Csv => sky.write_converted(&self.output)?,
+            OutputFormat::Cache => {
+                let sources = cache::Sources::of(Path::new(&self.input))?;
+                cache::write(Path::new(&self.output), &sources, &sky)?;
+            }
+        }
+        Ok(sky.len())
+    }
+
+    /// the stars of the input up to the maximum magnitude
+    fn read(&self) -> Result<Sky, SkyError> {
+        let (sky, _) = match self.input_format {
+            InputFormat::Bsc => Sky::from_bsc_file(&self.input, Epoch::J2000)?,
+            InputFormat::Hipparcos => {
+                Sky::from_file_with_format(&self.input, &CatalogFormat::hipparcos())?
+            }
+            InputFormat::Csv => {
+                Sky::from_file_with_format(&self.input, &Sky::catalog_format(&self.input)?)?
+            }
+        };
+        let mut sky = sky.magnitude_cut(self.max_magnitude);
+        if self.input_format == InputFormat::Hipparcos {
+            sky = sky
+                .iter()
+                .map(|star| star.renamed(format!("HIP {}", star.name())))
+                .collect();
+        }
+        Ok(sky)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Conversion, InputFormat, OutputFormat};
+    use crate::sky::Sky;
+
+    #[test]
+    fn test_convert() {
+        let dir = std::env::temp_dir();
+        let input = dir.join("cuyat_hip_main.dat");
+        let mut fields = vec![""; 78];
+        fields[0] = "H";
+        fields[1] = "  32349";
+        fields[5] = "-1.44";
+        fields[8] = "101.28854105";
+        fields[9] = "-16.71314306";
+        fields[37] = "0.009";
+        fields[76] = "A0m...";
+        let sirius = fields.join("|");
+        fields[1] = "  27989";
+        fields[5] = " 0.45";
+        fields[8] = "088.79287161";
+        fields[9] = "+07.40703634";
+        let betelgeuse = fields.join("|");
+        fields[5] = " 9.45";
+        let faint = fields.join("|");
+        std::fs::write(&input, [sirius, betelgeuse, faint].join("\n")).unwrap();
+
+        let args = [
+            input.to_string_lossy().into_owned(),
+            "--max-magnitude".into(),
+            "6".into(),
+        ];
+        let conversion = Conversion::from_args(&args).unwrap();
+        assert_eq!(conversion.input_format, InputFormat::Hipparcos);
+        assert_eq!(conversion.output_format, OutputFormat::Csv);
+        assert!(conversion.output.ends_with("cuyat_hip_main.csv"));
+        assert_eq!(conversion.run().unwrap(), 2);
+        let (sky, skipped) = Sky::from_converted_file(&conversion.output, 10).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(sky[1].name(), "HIP 32349");
+        assert_eq!(sky[1].spectral_class(), Some('A'));
+        assert_eq!(sky[0].color_index(), Some(0.01));
+
+        let args = [args[0].clone(), "--output-format".into(), "cache".into()];
+        let conversion = Conversion::from_args(&args).unwrap();
+        assert!(conversion.output.ends_with("cuyat_hip_main.cache"));
+        assert_eq!(conversion.run().unwrap(), 2);
+
+        assert!(Conversion::from_args(&["--output-format".into(), "xml".into()]).is_err());
+        assert!(Conversion::from_args(&[]).is_err());
+        assert_eq!(
+            InputFormat::detect("α Ori,055510.3,+072425,0.50"),
+            InputFormat::Csv
+        );
+    }
+}
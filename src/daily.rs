@@ -0,0 +1,201 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{config_dir, load_toml, save_toml, write},
+    ephemeris::julian_date,
+    view::{cursive_window, Scoring, SkyView},
+};
+
+/// how many stars the sky of the challenge has, the brightest of the catalog
+const NSTARS: usize = 400;
+
+/// The number of the julian day `jd` is in, from midnight to midnight UT.
+pub fn day_of(jd: f64) -> i64 {
+    (jd + 0.5).floor() as i64
+}
+
+/// the day numbered `day`, as 2024-03-20
+pub fn date_of(day: i64) -> String {
+    // from the days since 1970-01-01, by the algorithm of Howard Hinnant
+    let z = day - 2_440_588 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// mixed with the day into its seed, so that `--seed` with the number of a
+/// day does not play its challenge
+const SALT: &[u8] = b"cuyat daily challenge";
+
+/// The seed the sky and target of `day` are drawn with, the same for every
+/// player: a FNV-1a hash of the salt and the day, which unlike the hasher of
+/// std does not change between versions.
+pub fn seed(day: i64) -> u64 {
+    SALT.iter()
+        .chain(&day.to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// How the challenge of a day went: its first game.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Entry {
+    pub score: f32,
+    pub moves: usize,
+    pub seconds: f32,
+    pub solved: bool,
+}
+
+/// The challenges played, by their date, kept between runs.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Results {
+    pub days: BTreeMap<String, Entry>,
+}
+
+impl Results {
+    /// where they are kept: `daily.toml` next to the profiles
+    pub fn path() -> Option<PathBuf> {
+        Some(config_dir()?.join("daily.toml"))
+    }
+
+    /// Keeps `entry` for `date` unless the challenge of that day was played
+    /// before, and tells whether it did: only the first try counts.
+    pub fn record(&mut self, date: &str, entry: Entry) -> bool {
+        if self.days.contains_key(date) {
+            return false;
+        }
+        self.days.insert(String::from(date), entry);
+        true
+    }
+
+    /// the results as CSV, one day in each line after a header, to compare
+    /// with those of other players
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,score,moves,seconds,solved\n");
+        for (date, entry) in &self.days {
+            csv.push_str(&format!(
+                "{date},{:.6},{},{:.1},{}\n",
+                entry.score, entry.moves, entry.seconds, entry.solved
+            ));
+        }
+        csv
+    }
+}
+
+/// Plays in the terminal the challenge of the day of `jd`, or of today: the
+/// same sky and target for everyone that day. Its first game is kept as the
+/// result of the day, if it was not played before, and printed.
+pub fn run(jd: Option<f64>, scoring: Rc<RefCell<Scoring>>) -> Result<(), String> {
+    let path = Results::path().ok_or("no config directory")?;
+    let mut results: Results = load_toml(&path)?;
+    let day = day_of(jd.unwrap_or_else(|| julian_date(SystemTime::now())));
+    let date = date_of(day);
+    let games_before = (*scoring).borrow().games();
+    cursive_window(
+        SkyView::new(
+            Some(String::from("assets/bsc5.csv")),
+            NSTARS,
+            Rc::clone(&scoring),
+        )
+        .with_seed(seed(day)),
+    );
+
+    let scoring = (*scoring).borrow();
+    let (Some(&score), Some(metadata)) = (
+        scoring.total.get(games_before),
+        scoring.metadata.get(games_before),
+    ) else {
+        println!("daily {date}: not played");
+        return Ok(());
+    };
+    let entry = Entry {
+        score,
+        moves: metadata.moves,
+        seconds: metadata.seconds,
+        solved: metadata.solved,
+    };
+    if results.record(&date, entry) {
+        save_toml(&path, &results)?;
+        println!("daily {date}: score {score:.6} in {} moves", metadata.moves);
+    } else {
+        println!(
+            "daily {date}: score {score:.6}, not kept: the first try of the day, {:.6}, counts",
+            results.days[&date].score
+        );
+    }
+    Ok(())
+}
+
+/// Writes the results of the challenges played as CSV to `path`, or prints
+/// them without one.
+pub fn export(path: Option<&str>) -> Result<(), String> {
+    let results: Results = load_toml(&Results::path().ok_or("no config directory")?)?;
+    match path {
+        Some(path) => write(Path::new(path), results.to_csv()),
+        None => {
+            print!("{}", results.to_csv());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{date_of, day_of, seed, Entry, Results};
+    use crate::ephemeris::parse_date;
+
+    #[test]
+    fn test_date_of() {
+        for date in ["1970-01-01", "2000-02-29", "2024-03-20", "1899-12-31"] {
+            let day = day_of(parse_date(date).unwrap());
+            assert_eq!(date_of(day), date);
+        }
+        // the same day until midnight
+        let morning = day_of(parse_date("2024-03-20T00:30").unwrap());
+        let night = day_of(parse_date("2024-03-20T23:30").unwrap());
+        assert_eq!(morning, night);
+        assert_ne!(seed(night), seed(night + 1));
+        assert_ne!(seed(night), night as u64);
+        // fixed, for every player to get the same challenge
+        assert_eq!(night, 2_460_390);
+        assert_eq!(seed(night), 15_284_312_542_811_122_304);
+    }
+
+    #[test]
+    fn test_results() {
+        let entry = |score| Entry {
+            score,
+            moves: 12,
+            seconds: 30.0,
+            solved: true,
+        };
+        let mut results = Results::default();
+        assert!(results.record("2024-03-21", entry(1.5)));
+        assert!(results.record("2024-03-20", entry(0.5)));
+        // only the first try of a day counts
+        assert!(!results.record("2024-03-20", entry(0.1)));
+        assert_eq!(results.days["2024-03-20"].score, 0.5);
+        assert_eq!(
+            results.to_csv(),
+            "date,score,moves,seconds,solved\n\
+             2024-03-20,0.500000,12,30.0,true\n\
+             2024-03-21,1.500000,12,30.0,true\n"
+        );
+    }
+}
@@ -0,0 +1,470 @@
+use crate::sky::{direction_at, Star};
+
+/// The kinds of deep-sky objects, each drawn with its own glyph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeepSkyKind {
+    Galaxy,
+    OpenCluster,
+    GlobularCluster,
+    Nebula,
+    PlanetaryNebula,
+}
+
+/// A galaxy, cluster or nebula: its Messier or NGC designation, its common
+/// name (maybe empty), where it is, its magnitude and its angular size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeepSkyObject {
+    designation: String,
+    name: String,
+    kind: DeepSkyKind,
+    position: Star,
+    magnitude: f32,
+    size: f32,
+}
+
+impl DeepSkyObject {
+    /// `size` is the largest angular dimension, in arcminutes
+    pub fn new<S: Into<String>>(
+        designation: S,
+        name: S,
+        kind: DeepSkyKind,
+        position: Star,
+        magnitude: f32,
+        size: f32,
+    ) -> Self {
+        Self {
+            designation: designation.into(),
+            name: name.into(),
+            kind,
+            position,
+            magnitude,
+            size,
+        }
+    }
+
+    /// the same object seen at `position`
+    pub fn moved_to(&self, position: Star) -> Self {
+        Self {
+            position,
+            ..self.clone()
+        }
+    }
+
+    pub fn designation(&self) -> &str {
+        &self.designation
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> DeepSkyKind {
+        self.kind
+    }
+
+    pub fn position(&self) -> &Star {
+        &self.position
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.magnitude
+    }
+
+    /// the angular size, in radians
+    pub fn angular_size(&self) -> f32 {
+        (self.size / 60.0).to_radians()
+    }
+}
+
+/// designation, name, kind, right ascension and declination (J2000, degrees),
+/// magnitude and size (arcminutes) of the brightest Messier and NGC objects
+const BRIGHT_OBJECTS: [(&str, &str, DeepSkyKind, f32, f32, f32, f32); 42] = [
+    (
+        "M1",
+        "Crab Nebula",
+        DeepSkyKind::Nebula,
+        83.62,
+        22.02,
+        8.4,
+        6.0,
+    ),
+    (
+        "M2",
+        "",
+        DeepSkyKind::GlobularCluster,
+        323.38,
+        -0.82,
+        6.5,
+        16.0,
+    ),
+    (
+        "M3",
+        "",
+        DeepSkyKind::GlobularCluster,
+        205.55,
+        28.38,
+        6.2,
+        18.0,
+    ),
+    (
+        "M4",
+        "",
+        DeepSkyKind::GlobularCluster,
+        245.90,
+        -26.53,
+        5.6,
+        36.0,
+    ),
+    (
+        "M5",
+        "",
+        DeepSkyKind::GlobularCluster,
+        229.65,
+        2.08,
+        5.6,
+        23.0,
+    ),
+    (
+        "M6",
+        "Butterfly Cluster",
+        DeepSkyKind::OpenCluster,
+        265.02,
+        -32.22,
+        4.2,
+        25.0,
+    ),
+    (
+        "M7",
+        "Ptolemy Cluster",
+        DeepSkyKind::OpenCluster,
+        268.48,
+        -34.82,
+        3.3,
+        80.0,
+    ),
+    (
+        "M8",
+        "Lagoon Nebula",
+        DeepSkyKind::Nebula,
+        270.95,
+        -24.38,
+        6.0,
+        90.0,
+    ),
+    (
+        "M11",
+        "Wild Duck Cluster",
+        DeepSkyKind::OpenCluster,
+        282.77,
+        -6.27,
+        5.8,
+        14.0,
+    ),
+    (
+        "M13",
+        "Hercules Cluster",
+        DeepSkyKind::GlobularCluster,
+        250.43,
+        36.47,
+        5.8,
+        20.0,
+    ),
+    (
+        "M15",
+        "",
+        DeepSkyKind::GlobularCluster,
+        322.50,
+        12.17,
+        6.2,
+        18.0,
+    ),
+    (
+        "M16",
+        "Eagle Nebula",
+        DeepSkyKind::Nebula,
+        274.70,
+        -13.78,
+        6.0,
+        7.0,
+    ),
+    (
+        "M17",
+        "Omega Nebula",
+        DeepSkyKind::Nebula,
+        275.20,
+        -16.18,
+        6.0,
+        11.0,
+    ),
+    (
+        "M20",
+        "Trifid Nebula",
+        DeepSkyKind::Nebula,
+        270.65,
+        -23.03,
+        6.3,
+        28.0,
+    ),
+    (
+        "M22",
+        "",
+        DeepSkyKind::GlobularCluster,
+        279.10,
+        -23.90,
+        5.1,
+        32.0,
+    ),
+    (
+        "M27",
+        "Dumbbell Nebula",
+        DeepSkyKind::PlanetaryNebula,
+        299.90,
+        22.72,
+        7.5,
+        8.0,
+    ),
+    (
+        "M31",
+        "Andromeda Galaxy",
+        DeepSkyKind::Galaxy,
+        10.68,
+        41.27,
+        3.4,
+        178.0,
+    ),
+    (
+        "M33",
+        "Triangulum Galaxy",
+        DeepSkyKind::Galaxy,
+        23.47,
+        30.65,
+        5.7,
+        73.0,
+    ),
+    ("M35", "", DeepSkyKind::OpenCluster, 92.22, 24.33, 5.3, 28.0),
+    ("M36", "", DeepSkyKind::OpenCluster, 84.03, 34.13, 6.3, 12.0),
+    ("M37", "", DeepSkyKind::OpenCluster, 88.10, 32.55, 6.2, 24.0),
+    ("M38", "", DeepSkyKind::OpenCluster, 82.17, 35.83, 7.4, 21.0),
+    (
+        "M39",
+        "",
+        DeepSkyKind::OpenCluster,
+        323.05,
+        48.43,
+        4.6,
+        32.0,
+    ),
+    (
+        "M41",
+        "",
+        DeepSkyKind::OpenCluster,
+        101.50,
+        -20.73,
+        4.5,
+        38.0,
+    ),
+    (
+        "M42",
+        "Orion Nebula",
+        DeepSkyKind::Nebula,
+        83.85,
+        -5.45,
+        4.0,
+        85.0,
+    ),
+    (
+        "M44",
+        "Beehive Cluster",
+        DeepSkyKind::OpenCluster,
+        130.03,
+        19.98,
+        3.7,
+        95.0,
+    ),
+    (
+        "M45",
+        "Pleiades",
+        DeepSkyKind::OpenCluster,
+        56.75,
+        24.12,
+        1.6,
+        110.0,
+    ),
+    (
+        "M47",
+        "",
+        DeepSkyKind::OpenCluster,
+        114.15,
+        -14.50,
+        4.2,
+        30.0,
+    ),
+    (
+        "M51",
+        "Whirlpool Galaxy",
+        DeepSkyKind::Galaxy,
+        202.47,
+        47.20,
+        8.4,
+        11.0,
+    ),
+    (
+        "M57",
+        "Ring Nebula",
+        DeepSkyKind::PlanetaryNebula,
+        283.40,
+        33.03,
+        8.8,
+        1.4,
+    ),
+    (
+        "M81",
+        "Bode's Galaxy",
+        DeepSkyKind::Galaxy,
+        148.90,
+        69.07,
+        6.9,
+        26.0,
+    ),
+    (
+        "M82",
+        "Cigar Galaxy",
+        DeepSkyKind::Galaxy,
+        148.95,
+        69.68,
+        8.4,
+        11.0,
+    ),
+    (
+        "M101",
+        "Pinwheel Galaxy",
+        DeepSkyKind::Galaxy,
+        210.80,
+        54.35,
+        7.9,
+        29.0,
+    ),
+    (
+        "M104",
+        "Sombrero Galaxy",
+        DeepSkyKind::Galaxy,
+        190.00,
+        -11.62,
+        8.0,
+        9.0,
+    ),
+    (
+        "NGC 104",
+        "47 Tucanae",
+        DeepSkyKind::GlobularCluster,
+        6.03,
+        -72.08,
+        4.0,
+        31.0,
+    ),
+    (
+        "NGC 253",
+        "Sculptor Galaxy",
+        DeepSkyKind::Galaxy,
+        11.90,
+        -25.28,
+        7.1,
+        27.0,
+    ),
+    (
+        "NGC 869",
+        "h Persei",
+        DeepSkyKind::OpenCluster,
+        34.75,
+        57.15,
+        3.7,
+        30.0,
+    ),
+    (
+        "NGC 884",
+        "χ Persei",
+        DeepSkyKind::OpenCluster,
+        35.60,
+        57.12,
+        3.8,
+        30.0,
+    ),
+    (
+        "NGC 2070",
+        "Tarantula Nebula",
+        DeepSkyKind::Nebula,
+        84.67,
+        -69.10,
+        8.0,
+        40.0,
+    ),
+    (
+        "NGC 3372",
+        "Carina Nebula",
+        DeepSkyKind::Nebula,
+        161.28,
+        -59.87,
+        3.0,
+        120.0,
+    ),
+    (
+        "NGC 5139",
+        "ω Centauri",
+        DeepSkyKind::GlobularCluster,
+        201.70,
+        -47.48,
+        3.9,
+        36.0,
+    ),
+    (
+        "NGC 7293",
+        "Helix Nebula",
+        DeepSkyKind::PlanetaryNebula,
+        337.40,
+        -20.83,
+        7.6,
+        16.0,
+    ),
+];
+
+/// the brightest Messier and NGC objects
+pub fn bright_objects() -> Vec<DeepSkyObject> {
+    BRIGHT_OBJECTS
+        .iter()
+        .map(|&(designation, name, kind, ra, dec, magnitude, size)| {
+            DeepSkyObject::new(
+                designation,
+                name,
+                kind,
+                direction_at(ra, dec),
+                magnitude,
+                size,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{bright_objects, DeepSkyKind};
+    use crate::sky::{angle_between, direction_at};
+
+    #[test]
+    fn test_bright_objects() {
+        let objects = bright_objects();
+        let m42 = objects.iter().find(|o| o.designation() == "M42").unwrap();
+        assert_eq!(m42.name(), "Orion Nebula");
+        assert_eq!(m42.kind(), DeepSkyKind::Nebula);
+        // just south of the belt of Orion
+        assert!(angle_between(m42.position(), &direction_at(83.8, -5.4)).to_degrees() < 0.1);
+        let m45 = objects.iter().find(|o| o.name() == "Pleiades").unwrap();
+        assert_relative_eq!(
+            m45.angular_size().to_degrees(),
+            110.0 / 60.0,
+            epsilon = 1e-5
+        );
+        assert!(objects.iter().all(|o| o.magnitude() < 9.0));
+    }
+}
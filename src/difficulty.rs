@@ -0,0 +1,170 @@
+use std::{fmt, str::FromStr};
+
+use nalgebra::{Unit, UnitQuaternion, Vector3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::sky::random_quaternion_with;
+
+/// What a player is shown: how many stars, how much wider than usual the
+/// field of view is and whether the stars are named.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sight {
+    pub nstars: usize,
+    pub fov_scale: f32,
+    pub show_star_names: bool,
+}
+
+/// How hard the games are: how many stars there are, how wide the view is,
+/// whether they are named, how far from the target they start and how
+/// their scores count.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Medium,
+            Difficulty::Medium => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn nstars(self) -> usize {
+        match self {
+            Difficulty::Easy => 300,
+            Difficulty::Medium => 400,
+            Difficulty::Hard => 800,
+        }
+    }
+
+    /// how much wider than usual the field of view is
+    pub fn fov_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Medium => 1.0,
+            Difficulty::Hard => 0.5,
+        }
+    }
+
+    pub fn show_star_names(self) -> bool {
+        self != Difficulty::Hard
+    }
+
+    /// what is shown at this difficulty
+    pub fn sight(self) -> Sight {
+        Sight {
+            nstars: self.nstars(),
+            fov_scale: self.fov_scale(),
+            show_star_names: self.show_star_names(),
+        }
+    }
+
+    /// the farthest, in radians, a game starts from the target, if not anywhere
+    pub fn max_error(self) -> Option<f32> {
+        match self {
+            Difficulty::Easy => Some(45f32.to_radians()),
+            Difficulty::Medium | Difficulty::Hard => None,
+        }
+    }
+
+    /// what the score of a game is multiplied by: as lower is better,
+    /// easier games count for less
+    pub fn score_weight(self) -> f32 {
+        match self {
+            Difficulty::Easy => 2.0,
+            Difficulty::Medium => 1.0,
+            Difficulty::Hard => 0.5,
+        }
+    }
+
+    /// An attitude to start a game with `target_q` from: anywhere, or
+    /// between half and all of the largest error away from it.
+    pub fn starting_attitude<R: Rng>(
+        self,
+        target_q: UnitQuaternion<f32>,
+        rng: &mut R,
+    ) -> UnitQuaternion<f32> {
+        let Some(max_error) = self.max_error() else {
+            return random_quaternion_with(rng);
+        };
+        let axis = loop {
+            let v = Vector3::from_fn(|_, _| rng.gen_range(-1.0..1.0));
+            if v.norm() > 0.1 && v.norm() <= 1.0 {
+                break Unit::new_normalize(v);
+            }
+        };
+        let angle = rng.gen_range(0.5..=1.0) * max_error;
+        UnitQuaternion::from_axis_angle(&axis, angle) * target_q
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| d.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("no difficulty {s}: easy, medium or hard"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::UnitQuaternion;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::Difficulty;
+    use crate::{attitude::geodesic_distance, sky::random_quaternion_with};
+
+    #[test]
+    fn test_difficulty() {
+        assert_eq!(Difficulty::default().next(), Difficulty::Hard);
+        assert_eq!(Difficulty::Hard.next(), Difficulty::Easy);
+        assert_eq!("Hard".parse(), Ok(Difficulty::Hard));
+        assert_eq!(" easy".parse(), Ok(Difficulty::Easy));
+        assert!("impossible".parse::<Difficulty>().is_err());
+        // harder: more stars, a narrower view, no names, better scores
+        for pair in Difficulty::ALL.windows(2) {
+            let (easier, harder) = (pair[0], pair[1]);
+            assert!(easier.nstars() < harder.nstars());
+            assert!(easier.fov_scale() > harder.fov_scale());
+            assert!(easier.score_weight() > harder.score_weight());
+        }
+        assert!(!Difficulty::Hard.show_star_names());
+    }
+
+    #[test]
+    fn test_starting_attitude() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let target = UnitQuaternion::from_euler_angles(0.2, 1.0, -2.1);
+        for _ in 0..50 {
+            let start = Difficulty::Easy.starting_attitude(target, &mut rng);
+            let error = geodesic_distance(target, start);
+            assert!(error >= 22f32.to_radians() && error <= 45.01f32.to_radians());
+        }
+        // anywhere, as before there were difficulties
+        let (mut rng, mut same) = (StdRng::seed_from_u64(5), StdRng::seed_from_u64(5));
+        let start = Difficulty::Hard.starting_attitude(target, &mut rng);
+        assert_eq!(start, random_quaternion_with(&mut same));
+    }
+}
@@ -0,0 +1,296 @@
+use std::f64::consts::PI;
+
+use crate::sky::{julian_year, precess, Brightness, CatalogStar, Star};
+
+/// The bodies of the solar system that can be seen in the sky.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Body {
+    Sun,
+    Moon,
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+}
+
+/// Orbital elements at day 0 and their daily change, in degrees and AU
+/// (Earth radii for the Moon), as in Paul Schlyter's "How to compute
+/// planetary positions": longitude of the ascending node, inclination,
+/// argument of perihelion, semi-major axis, eccentricity and mean anomaly.
+type Elements = [(f64, f64); 6];
+
+impl Body {
+    pub const ALL: [Body; 9] = [
+        Body::Sun,
+        Body::Moon,
+        Body::Mercury,
+        Body::Venus,
+        Body::Mars,
+        Body::Jupiter,
+        Body::Saturn,
+        Body::Uranus,
+        Body::Neptune,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Body::Sun => "Sun",
+            Body::Moon => "Moon",
+            Body::Mercury => "Mercury",
+            Body::Venus => "Venus",
+            Body::Mars => "Mars",
+            Body::Jupiter => "Jupiter",
+            Body::Saturn => "Saturn",
+            Body::Uranus => "Uranus",
+            Body::Neptune => "Neptune",
+        }
+    }
+
+    /// a typical magnitude. It changes with the distance and the phase, which
+    /// are not taken into account.
+    pub fn magnitude(&self) -> f32 {
+        match self {
+            Body::Sun => -26.7,
+            Body::Moon => -12.7,
+            Body::Mercury => 0.0,
+            Body::Venus => -4.2,
+            Body::Mars => 0.7,
+            Body::Jupiter => -2.3,
+            Body::Saturn => 0.6,
+            Body::Uranus => 5.7,
+            Body::Neptune => 7.8,
+        }
+    }
+
+    fn elements(&self) -> Elements {
+        match self {
+            Body::Sun => [
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (282.9404, 4.70935e-5),
+                (1.0, 0.0),
+                (0.016709, -1.151e-9),
+                (356.0470, 0.985_600_258_5),
+            ],
+            Body::Moon => [
+                (125.1228, -0.052_953_808_3),
+                (5.1454, 0.0),
+                (318.0634, 0.164_357_322_3),
+                (60.2666, 0.0),
+                (0.054900, 0.0),
+                (115.3654, 13.064_992_950_9),
+            ],
+            Body::Mercury => [
+                (48.3313, 3.24587e-5),
+                (7.0047, 5.00e-8),
+                (29.1241, 1.01444e-5),
+                (0.387098, 0.0),
+                (0.205635, 5.59e-10),
+                (168.6562, 4.092_334_436_8),
+            ],
+            Body::Venus => [
+                (76.6799, 2.46590e-5),
+                (3.3946, 2.75e-8),
+                (54.8910, 1.38374e-5),
+                (0.723330, 0.0),
+                (0.006773, -1.302e-9),
+                (48.0052, 1.602_130_224_4),
+            ],
+            Body::Mars => [
+                (49.5574, 2.11081e-5),
+                (1.8497, -1.78e-8),
+                (286.5016, 2.92961e-5),
+                (1.523688, 0.0),
+                (0.093405, 2.516e-9),
+                (18.6021, 0.524_020_776_6),
+            ],
+            Body::Jupiter => [
+                (100.4542, 2.76854e-5),
+                (1.3030, -1.557e-7),
+                (273.8777, 1.64505e-5),
+                (5.20256, 0.0),
+                (0.048498, 4.469e-9),
+                (19.8950, 0.083_085_300_1),
+            ],
+            Body::Saturn => [
+                (113.6634, 2.38980e-5),
+                (2.4886, -1.081e-7),
+                (339.3939, 2.97661e-5),
+                (9.55475, 0.0),
+                (0.055546, -9.499e-9),
+                (316.9670, 0.033_444_228_2),
+            ],
+            Body::Uranus => [
+                (74.0005, 1.3978e-5),
+                (0.7733, 1.9e-8),
+                (96.6612, 3.0565e-5),
+                (19.18171, -1.55e-8),
+                (0.047318, 7.45e-9),
+                (142.5905, 0.011_725_806),
+            ],
+            Body::Neptune => [
+                (131.7806, 3.0173e-5),
+                (1.7700, -2.55e-7),
+                (272.8461, -6.027e-6),
+                (30.05826, 3.313e-8),
+                (0.008606, 2.15e-9),
+                (260.2471, 0.005_995_147),
+            ],
+        }
+    }
+
+    /// position in the plane of the ecliptic of date, around the Sun for the
+    /// planets and around the Earth for the Sun and the Moon
+    fn orbit_position(&self, d: f64) -> [f64; 3] {
+        let [n, i, w, a, e, m] = self.elements().map(|(x0, dx)| x0 + dx * d);
+        let (n, i, w, m) = (
+            n.to_radians(),
+            i.to_radians(),
+            w.to_radians(),
+            m.to_radians(),
+        );
+        // Kepler's equation
+        let mut ea = m + e * m.sin() * (1.0 + e * m.cos());
+        for _ in 0..10 {
+            ea -= (ea - e * ea.sin() - m) / (1.0 - e * ea.cos());
+        }
+        let xv = a * (ea.cos() - e);
+        let yv = a * (1.0 - e * e).sqrt() * ea.sin();
+        let (v, r) = (yv.atan2(xv), xv.hypot(yv));
+        let vw = v + w;
+        [
+            r * (n.cos() * vw.cos() - n.sin() * vw.sin() * i.cos()),
+            r * (n.sin() * vw.cos() + n.cos() * vw.sin() * i.cos()),
+            r * vw.sin() * i.sin(),
+        ]
+    }
+
+    /// direction of the body seen from the Earth at julian date `jd`, in
+    /// J2000 equatorial coordinates. Good to a fraction of a degree for the
+    /// planets; the Moon can be off by a couple of degrees.
+    pub fn direction(&self, jd: f64) -> Star {
+        let d = jd - 2_451_543.5;
+        let [x, y, z] = match self {
+            Body::Sun | Body::Moon => self.orbit_position(d),
+            _ => {
+                let [xh, yh, zh] = self.orbit_position(d);
+                let [xs, ys, _] = Body::Sun.orbit_position(d);
+                [xh + xs, yh + ys, zh]
+            }
+        };
+        let ecl = (23.4393 - 3.563e-7 * d) * PI / 180.0;
+        let equatorial = Star::new(
+            x as f32,
+            (y * ecl.cos() - z * ecl.sin()) as f32,
+            (y * ecl.sin() + z * ecl.cos()) as f32,
+        )
+        .normalize();
+        precess(&equatorial, julian_year(jd), 2000.0)
+    }
+}
+
+/// the Sun, the Moon and the planets at julian date `jd`, as catalog stars
+pub fn solar_system(jd: f64) -> Vec<CatalogStar> {
+    Body::ALL
+        .iter()
+        .map(|body| {
+            CatalogStar::new(
+                body.direction(jd),
+                Brightness::from_magnitude(body.magnitude()),
+                body.name(),
+            )
+        })
+        .collect()
+}
+
+/// the julian date of `time`
+pub fn julian_date(time: std::time::SystemTime) -> f64 {
+    let unix = match time.duration_since(std::time::UNIX_EPOCH) {
+        Ok(since) => since.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    };
+    2_440_587.5 + unix / 86_400.0
+}
+
+/// The julian date of `text`, a day of the gregorian calendar with a time
+/// of day in UT or none, as `2024-03-20` or `2024-03-20T21:30`.
+pub fn parse_date(text: &str) -> Result<f64, String> {
+    let error = || format!("no date '{text}': write it as 2024-03-20 or 2024-03-20T21:30");
+    let text = text.trim();
+    let (date, time) = text.split_once(['T', ' ']).unwrap_or((text, "0:0"));
+    let numbers = |part: &str, separator| {
+        part.split(separator)
+            .map(|n| n.trim().parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()
+    };
+    let (Some(&[year, month, day]), Some(&[hour, minute])) =
+        (numbers(date, '-').as_deref(), numbers(time, ':').as_deref())
+    else {
+        return Err(error());
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return Err(error());
+    }
+    // as in Meeus, chapter 7: January and February count as the 13th and
+    // 14th months of the year before
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+    let century = y.div_euclid(100);
+    let gregorian = 2 - century + century.div_euclid(4);
+    Ok((365.25 * (y + 4716) as f64).floor()
+        + (30.6001 * (m + 1) as f64).floor()
+        + (day + gregorian) as f64
+        - 1524.5
+        + (hour * 60 + minute) as f64 / 1440.0)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use approx::assert_relative_eq;
+
+    use super::{julian_date, parse_date, solar_system, Body};
+    use crate::sky::{angle_between, direction_at, ra_dec};
+
+    #[test]
+    fn test_ephemeris() {
+        // 1990 April 19, 0h UT: the worked example of Schlyter's text
+        let jd = 2_448_000.5;
+        let (ra, dec) = ra_dec(&Body::Sun.direction(jd));
+        // RA 26.658°, Dec +11.008° of date there, plus the precession back to J2000
+        assert_relative_eq!(ra, 26.79, epsilon = 0.05);
+        assert_relative_eq!(dec, 11.06, epsilon = 0.05);
+
+        // Jupiter was in Gemini, near μ Gem
+        let jupiter = Body::Jupiter.direction(jd);
+        assert!(angle_between(&jupiter, &direction_at(95.7, 23.4)).to_degrees() < 0.5);
+
+        let bodies = solar_system(jd);
+        assert_eq!(bodies.len(), Body::ALL.len());
+        assert_eq!(bodies[1].name(), "Moon");
+        assert_eq!(bodies[0].brightness().value(), 1.0);
+
+        assert_eq!(julian_date(UNIX_EPOCH), 2_440_587.5);
+        assert_eq!(
+            julian_date(UNIX_EPOCH + Duration::from_secs(86_400)),
+            2_440_588.5
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2000-01-01T12:00"), Ok(2_451_545.0));
+        assert_eq!(parse_date(" 1990-04-19"), Ok(2_448_000.5));
+        assert_eq!(parse_date("1987-04-10 19:21"), Ok(2_446_896.306_25));
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("2024-03-20T25:00").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    observer::Observer,
+    sky::{Brightness, Sky},
+};
+
+/// naked eye limiting magnitude at the zenith for each Bortle class, from 1 to 9
+const LIMITING_MAGNITUDES: [f32; 9] = [7.8, 7.3, 6.8, 6.3, 5.8, 5.3, 4.8, 4.3, 4.0];
+
+/// The air mass towards altitude `alt` (degrees): 1 at the zenith and about
+/// 38 at the horizon, as in Kasten and Young (1989).
+pub fn airmass(alt: f32) -> f32 {
+    let alt = alt.max(0.0);
+    1.0 / (alt.to_radians().sin() + 0.505_72 * (alt + 6.079_95).powf(-1.636_4))
+}
+
+/// How the sky looks from where it is seen: `bortle` is the class of the
+/// [Bortle scale](https://en.wikipedia.org/wiki/Bortle_scale), from 1 (the
+/// darkest sites) to 9 (inner cities), or 0 for a perfect sky with all the
+/// stars of the catalog; `extinction` is how much stars dim, in magnitudes,
+/// per air mass.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct SkyConditions {
+    pub bortle: u8,
+    pub extinction: f32,
+}
+
+impl Default for SkyConditions {
+    fn default() -> Self {
+        Self {
+            bortle: 0,
+            extinction: 0.25,
+        }
+    }
+}
+
+impl SkyConditions {
+    pub fn new(bortle: u8, extinction: f32) -> Self {
+        Self {
+            bortle: bortle.min(9),
+            extinction,
+        }
+    }
+
+    /// whether all the stars are shown as in the catalog
+    pub fn is_perfect(&self) -> bool {
+        self.bortle == 0
+    }
+
+    /// the next Bortle class, to cycle through them
+    pub fn next(self) -> Self {
+        Self {
+            bortle: (self.bortle + 1) % 10,
+            ..self
+        }
+    }
+
+    /// the faintest magnitude that can be seen at the zenith
+    pub fn limiting_magnitude(&self) -> f32 {
+        match self.bortle {
+            0 => f32::INFINITY,
+            b => LIMITING_MAGNITUDES[(b.min(9) - 1) as usize],
+        }
+    }
+
+    /// The magnitude a star of magnitude `m` has at altitude `alt`
+    /// (degrees). The limiting magnitudes are at the zenith, so stars are
+    /// only dimmed by the air they cross beyond one air mass.
+    pub fn apparent_magnitude(&self, m: f32, alt: f32) -> f32 {
+        if self.is_perfect() {
+            m
+        } else {
+            m + self.extinction * (airmass(alt) - 1.0)
+        }
+    }
+
+    /// the Bortle class, to show it
+    pub fn label(&self) -> String {
+        match self.bortle {
+            0 => "perfect".to_string(),
+            b => format!("bortle {b}"),
+        }
+    }
+}
+
+impl Sky {
+    /// The stars `observer` sees at julian date `jd` under `conditions`:
+    /// dimmed by the atmosphere, and without those fainter than the sky.
+    pub fn under(&self, conditions: &SkyConditions, observer: &Observer, jd: f64) -> Self {
+        let limit = conditions.limiting_magnitude();
+        self.iter()
+            .filter_map(|star| {
+                let (alt, _) = observer.alt_az(star.position(), jd);
+                let m = conditions.apparent_magnitude(star.magnitude(), alt);
+                (m <= limit).then(|| star.with_brightness(Brightness::from_magnitude(m)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{airmass, SkyConditions};
+    use crate::{
+        observer::Observer,
+        sky::{direction_at, Brightness, CatalogStar, Sky},
+    };
+
+    #[test]
+    fn test_extinction() {
+        assert_relative_eq!(airmass(90.0), 1.0, epsilon = 1e-3);
+        assert_relative_eq!(airmass(30.0), 2.0, epsilon = 1e-2);
+        assert_relative_eq!(airmass(0.0), 38.0, epsilon = 0.1);
+        assert_eq!(airmass(-10.0), airmass(0.0));
+
+        let perfect = SkyConditions::default();
+        assert!(perfect.is_perfect());
+        assert_eq!(perfect.apparent_magnitude(3.0, 0.0), 3.0);
+        let suburban = SkyConditions::new(5, 0.25);
+        assert_eq!(suburban.limiting_magnitude(), 5.8);
+        assert_relative_eq!(suburban.apparent_magnitude(3.0, 30.0), 3.25, epsilon = 1e-2);
+        assert_eq!(suburban.next().next().next().next().next(), perfect);
+        assert_eq!(SkyConditions::new(12, 0.25).bortle, 9);
+
+        let jd = 2_451_545.0;
+        let observer = Observer::new(51.48, 0.0, 0.0);
+        let zenith = observer.zenith(jd);
+        let lst = observer.local_sidereal_time(jd);
+        let low = direction_at(lst, 51.48 - 89.0);
+        let sky = Sky::from(&[
+            CatalogStar::new(zenith, Brightness::from_magnitude(3.0), "bright"),
+            CatalogStar::new(zenith, Brightness::from_magnitude(6.0), "faint"),
+            CatalogStar::new(low, Brightness::from_magnitude(3.0), "low"),
+        ]);
+        assert_eq!(sky.under(&perfect, &observer, jd).len(), 3);
+        let seen = sky.under(&suburban, &observer, jd);
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].name(), "bright");
+        assert_relative_eq!(seen[0].magnitude(), 3.0, epsilon = 1e-2);
+    }
+}
@@ -0,0 +1,410 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::sky::{direction_at, spectral_class, Brightness, CatalogStar, Epoch, SkyError};
+
+/// How an angle is written in a catalog field.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    #[default]
+    Degrees,
+    Hours,
+    /// `HHMMSS.s`, as right ascensions in the BSC
+    Hms,
+    /// `±DDMMSS`, as declinations in the BSC. Missing seconds are taken as 0.
+    Dms,
+}
+
+/// A column of a delimited catalog, by position (starting at 0) or by its
+/// name in the header.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+/// Where a field is in a line: a `column` if the catalog is delimited, or a
+/// byte `range` (end excluded) if it has fixed width columns.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    column: Option<Column>,
+    range: Option<(usize, usize)>,
+    #[serde(default)]
+    unit: Unit,
+}
+
+/// The layout of a catalog file, as written in a TOML descriptor. For
+/// instance, for a CSV file with a header:
+///
+/// ```toml
+/// delimiter = ","
+/// header = true
+/// ra = { column = "ra" }
+/// dec = { column = "dec" }
+/// magnitude = { column = "vmag" }
+/// name = { column = 0 }
+/// ```
+///
+/// and for a file with fixed width columns:
+///
+/// ```toml
+/// ra = { range = [75, 83], unit = "hms" }
+/// dec = { range = [83, 90], unit = "dms" }
+/// magnitude = { range = [102, 107] }
+/// ```
+///
+/// Lines starting with `#` are skipped.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogFormat {
+    delimiter: Option<char>,
+    #[serde(default)]
+    header: bool,
+    ra: Field,
+    dec: Field,
+    magnitude: Field,
+    name: Option<Field>,
+    hr: Option<Field>,
+    /// B-V
+    color_index: Option<Field>,
+    /// a spectral type, of which only the class is kept
+    spectral_class: Option<Field>,
+    /// yearly proper motion in arcseconds, along the right ascension
+    proper_motion_ra: Option<Field>,
+    proper_motion_dec: Option<Field>,
+    /// a variability flag or designation: the star is variable unless it is blank
+    variable: Option<Field>,
+}
+
+impl CatalogFormat {
+    pub fn from_file<P: AsRef<Path>>(fname: P) -> Result<Self, SkyError> {
+        Self::from_toml(&fs::read_to_string(fname)?)
+    }
+
+    pub fn from_toml(input: &str) -> Result<Self, SkyError> {
+        let format: Self = toml::from_str(input).map_err(|e| SkyError::BadFormat(e.to_string()))?;
+        // checks the fields can be found, as far as it can be known without a header
+        match format.parser(None) {
+            Ok(_) => Ok(format),
+            Err(SkyError::MissingColumn(_)) if format.header => Ok(format),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// the Yale Bright Star Catalog, J2000 coordinates
+    pub fn bsc() -> Self {
+        Self::bsc_at(Epoch::J2000)
+    }
+
+    /// the Yale Bright Star Catalog, taking the coordinates of `columns`
+    pub fn bsc_at(columns: Epoch) -> Self {
+        let ra = columns.bsc_column();
+        let range = |start, end, unit| Field {
+            column: None,
+            range: Some((start, end)),
+            unit,
+        };
+        Self {
+            delimiter: None,
+            header: false,
+            ra: range(ra, ra + 8, Unit::Hms),
+            dec: range(ra + 8, ra + 15, Unit::Dms),
+            magnitude: range(102, 107, Unit::Degrees),
+            name: Some(range(7, 14, Unit::Degrees)),
+            hr: Some(range(0, 4, Unit::Degrees)),
+            color_index: Some(range(109, 114, Unit::Degrees)),
+            spectral_class: Some(range(127, 147, Unit::Degrees)),
+            proper_motion_ra: Some(range(148, 154, Unit::Degrees)),
+            proper_motion_dec: Some(range(154, 160, Unit::Degrees)),
+            variable: Some(range(51, 60, Unit::Degrees)),
+        }
+    }
+
+    /// the files written by `Sky::convert_catalog_file`
+    pub fn converted() -> Self {
+        let column = |i, unit| Field {
+            column: Some(Column::Index(i)),
+            range: None,
+            unit,
+        };
+        Self {
+            delimiter: Some(','),
+            header: false,
+            ra: column(1, Unit::Hms),
+            dec: column(2, Unit::Dms),
+            magnitude: column(3, Unit::Degrees),
+            name: Some(column(0, Unit::Degrees)),
+            hr: None,
+            color_index: Some(column(4, Unit::Degrees)),
+            spectral_class: Some(column(5, Unit::Degrees)),
+            proper_motion_ra: Some(column(6, Unit::Degrees)),
+            proper_motion_dec: Some(column(7, Unit::Degrees)),
+            variable: None,
+        }
+    }
+
+    /// The Hipparcos main catalog (`hip_main.dat` of VizieR I/239), with
+    /// fields split by `|`. Its positions are those of epoch J1991.25 and
+    /// its proper motions, in milliarcseconds, are left out.
+    pub fn hipparcos() -> Self {
+        let column = |i| Field {
+            column: Some(Column::Index(i)),
+            range: None,
+            unit: Unit::Degrees,
+        };
+        Self {
+            delimiter: Some('|'),
+            header: false,
+            ra: column(8),
+            dec: column(9),
+            magnitude: column(5),
+            name: Some(column(1)),
+            hr: None,
+            color_index: Some(column(37)),
+            spectral_class: Some(column(76)),
+            proper_motion_ra: None,
+            proper_motion_dec: None,
+            variable: Some(column(6)),
+        }
+    }
+
+    /// a CSV file exported from the Gaia archive
+    pub fn gaia() -> Self {
+        let column = |name: &str| Field {
+            column: Some(Column::Name(name.to_string())),
+            range: None,
+            unit: Unit::Degrees,
+        };
+        Self {
+            delimiter: Some(','),
+            header: true,
+            ra: column("ra"),
+            dec: column("dec"),
+            magnitude: column(Self::GAIA_MAGNITUDE),
+            name: None,
+            hr: None,
+            color_index: None,
+            spectral_class: None,
+            proper_motion_ra: None,
+            proper_motion_dec: None,
+            variable: None,
+        }
+    }
+
+    const GAIA_MAGNITUDE: &'static str = "phot_g_mean_mag";
+
+    /// whether `line` is the header of a Gaia CSV file
+    pub fn is_gaia_header(line: &str) -> bool {
+        line.split(',').any(|f| clean(f) == Self::GAIA_MAGNITUDE)
+    }
+
+    pub fn has_header(&self) -> bool {
+        self.header
+    }
+
+    /// a parser for the lines after `header`
+    pub(crate) fn parser(&self, header: Option<&str>) -> Result<LineParser, SkyError> {
+        let names: Vec<&str> = match (header, self.delimiter) {
+            (Some(header), Some(d)) => header.split(d).map(clean).collect(),
+            _ => vec![],
+        };
+        let slot = |field: &Field| -> Result<Slot, SkyError> {
+            match (&field.column, field.range, self.delimiter) {
+                (Some(Column::Index(i)), None, Some(_)) => Ok(Slot::Column(*i)),
+                (Some(Column::Name(name)), None, Some(_)) => names
+                    .iter()
+                    .position(|n| n == name)
+                    .map(Slot::Column)
+                    .ok_or_else(|| SkyError::MissingColumn(name.clone())),
+                (None, Some((start, end)), None) if start < end => Ok(Slot::Range(start, end)),
+                _ => Err(SkyError::BadFormat(String::from(
+                    "fields need a column if there is a delimiter and a range if there is not",
+                ))),
+            }
+        };
+        Ok(LineParser {
+            delimiter: self.delimiter,
+            ra: (slot(&self.ra)?, self.ra.unit),
+            dec: (slot(&self.dec)?, self.dec.unit),
+            magnitude: slot(&self.magnitude)?,
+            name: self.name.as_ref().map(slot).transpose()?,
+            hr: self.hr.as_ref().map(slot).transpose()?,
+            color_index: self.color_index.as_ref().map(slot).transpose()?,
+            spectral_class: self.spectral_class.as_ref().map(slot).transpose()?,
+            proper_motion: match (&self.proper_motion_ra, &self.proper_motion_dec) {
+                (Some(ra), Some(dec)) => Some((slot(ra)?, slot(dec)?)),
+                _ => None,
+            },
+            variable: self.variable.as_ref().map(slot).transpose()?,
+        })
+    }
+}
+
+fn clean(field: &str) -> &str {
+    field.trim().trim_matches('"')
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Slot {
+    Column(usize),
+    Range(usize, usize),
+}
+
+/// Reads stars from the lines of a catalog with a known layout.
+pub(crate) struct LineParser {
+    delimiter: Option<char>,
+    ra: (Slot, Unit),
+    dec: (Slot, Unit),
+    magnitude: Slot,
+    name: Option<Slot>,
+    hr: Option<Slot>,
+    color_index: Option<Slot>,
+    spectral_class: Option<Slot>,
+    proper_motion: Option<(Slot, Slot)>,
+    variable: Option<Slot>,
+}
+
+impl LineParser {
+    pub(crate) fn star(&self, line: &str) -> Result<CatalogStar, SkyError> {
+        self.try_star(line).ok_or_else(|| SkyError::bad_line(line))
+    }
+
+    /// the magnitude in `line`, whether it is sensible or not
+    pub(crate) fn magnitude(&self, line: &str) -> Option<f32> {
+        field(&self.fields(line), line, self.magnitude)?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    fn fields<'a>(&self, line: &'a str) -> Vec<&'a str> {
+        match self.delimiter {
+            Some(d) => line.split(d).map(clean).collect(),
+            None => vec![],
+        }
+    }
+
+    fn try_star(&self, line: &str) -> Option<CatalogStar> {
+        let fields = self.fields(line);
+        let get = |slot: Slot| field(&fields, line, slot);
+        let ra = angle(get(self.ra.0)?, self.ra.1)?;
+        let dec = angle(get(self.dec.0)?, self.dec.1)?;
+        let mag: f32 = get(self.magnitude)?.trim().parse().ok()?;
+        let name = self.name.and_then(get).unwrap_or_default().trim();
+        let mut star =
+            CatalogStar::new(direction_at(ra, dec), Brightness::from_magnitude(mag), name);
+        if let Some(Ok(hr)) = self.hr.and_then(get).map(|hr| hr.trim().parse()) {
+            star = star.with_hr(hr);
+        }
+        if let Some(Ok(bv)) = self.color_index.and_then(get).map(|bv| bv.trim().parse()) {
+            star = star.with_color_index(bv);
+        }
+        if let Some(class) = self.spectral_class.and_then(get).and_then(spectral_class) {
+            star = star.with_spectral_class(class);
+        }
+        if let Some((ra, dec)) = self.proper_motion {
+            let value = |slot| get(slot).and_then(|pm| pm.trim().parse().ok());
+            if let (Some(ra), Some(dec)) = (value(ra), value(dec)) {
+                star = star.with_proper_motion(ra, dec);
+            }
+        }
+        if self
+            .variable
+            .and_then(get)
+            .is_some_and(|v| !v.trim().is_empty())
+        {
+            star = star.with_variable();
+        }
+        Some(star)
+    }
+}
+
+/// the text in `slot` of a line split in `fields`
+fn field<'a>(fields: &[&'a str], line: &'a str, slot: Slot) -> Option<&'a str> {
+    match slot {
+        Slot::Column(i) => fields.get(i).copied(),
+        Slot::Range(start, end) => line.get(start..end),
+    }
+}
+
+/// the angle in degrees written in `field`
+fn angle(field: &str, unit: Unit) -> Option<f32> {
+    match unit {
+        Unit::Degrees => field.trim().parse().ok(),
+        Unit::Hours => field.trim().parse().ok().map(|h: f32| h * 15.0),
+        Unit::Hms => {
+            let hh: u8 = field.get(0..2)?.parse().ok()?;
+            let mm: u8 = field.get(2..4)?.parse().ok()?;
+            let ss: f32 = field.get(4..)?.trim().parse().ok()?;
+            Some((hh as f32) * 15.0 + (mm as f32) / 4.0 + ss / 240.0)
+        }
+        Unit::Dms => {
+            let sgn: f32 = match field.get(0..1)? {
+                "+" => 1.0,
+                "-" => -1.0,
+                _ => return None,
+            };
+            let dd: u8 = field.get(1..3)?.parse().ok()?;
+            let mm: u8 = field.get(3..5)?.parse().ok()?;
+            let ss: u8 = field.get(5..)?.trim().parse().unwrap_or(0);
+            Some(sgn * ((dd as f32) + (mm as f32) / 60.0 + (ss as f32) / 3600.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{angle, CatalogFormat, Unit};
+    use crate::sky::SkyError;
+
+    #[test]
+    fn test_format() {
+        let format = CatalogFormat::from_toml(
+            "delimiter = \";\"\n\
+             ra = { column = 1, unit = \"hours\" }\n\
+             dec = { column = 2 }\n\
+             magnitude = { column = 0 }",
+        )
+        .unwrap();
+        let star = format.parser(None).unwrap().star("4.5; 6;-30").unwrap();
+        assert_eq!(star.magnitude(), 4.5);
+        assert_eq!(star.name(), "");
+
+        let with_header = CatalogFormat::from_toml(
+            "delimiter = \",\"\nheader = true\n\
+             ra = { column = \"ra\" }\ndec = { column = \"dec\" }\nmagnitude = { column = \"v\" }",
+        )
+        .unwrap();
+        assert!(with_header.parser(Some("ra,dec,v")).is_ok());
+        assert!(matches!(
+            with_header.parser(Some("ra,dec,g")),
+            Err(SkyError::MissingColumn(c)) if c == "v"
+        ));
+
+        // ranges make no sense with a delimiter
+        let bad = "delimiter = \",\"\nra = { range = [0, 2] }\ndec = { column = 1 }\nmagnitude = { column = 2 }";
+        assert!(matches!(
+            CatalogFormat::from_toml(bad),
+            Err(SkyError::BadFormat(_))
+        ));
+        assert!(matches!(
+            CatalogFormat::from_toml("ra = 3"),
+            Err(SkyError::BadFormat(_))
+        ));
+
+        assert_relative_eq!(
+            angle("055510.3", Unit::Hms).unwrap(),
+            88.792916,
+            epsilon = 1e-5
+        );
+        assert_eq!(angle("-1630  ", Unit::Dms), Some(-16.5));
+        assert_eq!(angle("2.5", Unit::Hours), Some(37.5));
+        assert_eq!(angle(" 1630", Unit::Dms), None);
+    }
+}
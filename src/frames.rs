@@ -0,0 +1,160 @@
+use nalgebra::{MatrixView3xX, UnitQuaternion};
+
+use crate::sky::{FoV, Fpp, SkyMat, Star};
+
+/// A direction in the frame of the catalog: x towards the vernal equinox and
+/// z towards the north celestial pole, as the stars of a `Sky` are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Equatorial(Star);
+
+/// A direction in the frame of the spacecraft, once turned to its attitude.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Body(Star);
+
+/// A direction in the frame of the camera, with z along its boresight: the
+/// only frame directions can be projected from. Only turning one to it makes
+/// it, so that none can be projected unturned:
+///
+/// ```compile_fail
+/// use cuyat::{frames::Camera, sky::Star};
+///
+/// let ahead = Camera(Star::new(0.0, 0.0, 1.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera(Star);
+
+/// The directions of many stars in the frame of the camera, turned all at
+/// once by `Equatorial::all_to_camera`.
+#[derive(Clone, Copy, Debug)]
+pub struct Cameras<'a>(MatrixView3xX<'a, f32>);
+
+impl Equatorial {
+    /// a direction of the catalog, as those of its stars
+    pub fn new(direction: Star) -> Self {
+        Equatorial(direction)
+    }
+
+    pub fn direction(&self) -> Star {
+        self.0
+    }
+
+    /// The `directions` of the catalog, one in each column, as seen by the
+    /// camera of a spacecraft with attitude `attitude`: turned into the first
+    /// columns of `turned` with a single product of matrices.
+    pub fn all_to_camera<'a>(
+        directions: MatrixView3xX<'_, f32>,
+        attitude: UnitQuaternion<f32>,
+        turned: &'a mut SkyMat,
+    ) -> Cameras<'a> {
+        let n = directions.ncols();
+        attitude
+            .to_rotation_matrix()
+            .matrix()
+            .mul_to(&directions, &mut turned.columns_mut(0, n));
+        Cameras(turned.columns(0, n))
+    }
+
+    /// the direction as seen from a spacecraft with attitude `attitude`
+    pub fn to_body(self, attitude: UnitQuaternion<f32>) -> Body {
+        Body(attitude * self.0)
+    }
+}
+
+impl Body {
+    pub fn direction(&self) -> Star {
+        self.0
+    }
+
+    /// the direction in the catalog, from a spacecraft with attitude `attitude`
+    pub fn to_equatorial(self, attitude: UnitQuaternion<f32>) -> Equatorial {
+        Equatorial(attitude.inverse() * self.0)
+    }
+
+    /// the direction as seen by the camera, which looks along the z axis of the spacecraft
+    pub fn to_camera(self) -> Camera {
+        Camera(self.0)
+    }
+}
+
+impl Camera {
+    /// where the camera points at
+    pub fn boresight() -> Self {
+        Camera(Star::z_axis().into_inner())
+    }
+
+    pub fn to_body(self) -> Body {
+        Body(self.0)
+    }
+
+    pub fn direction(&self) -> Star {
+        self.0
+    }
+
+    /// whether the direction is in front of the camera, so that it can be projected
+    pub fn is_ahead(&self) -> bool {
+        self.0[2] > 0.0
+    }
+}
+
+impl Cameras<'_> {
+    pub fn len(&self) -> usize {
+        self.0.ncols()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.ncols() == 0
+    }
+
+    /// the direction of the star in column `i`
+    pub fn get(&self, i: usize) -> Camera {
+        Camera(self.0.column(i).into_owned())
+    }
+}
+
+impl FoV {
+    /// where `direction` falls on the focal plane, if the lens takes it in
+    pub fn project_camera(&self, direction: &Camera) -> Option<Fpp> {
+        self.faces(direction).then(|| self.project(direction))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    use super::{Body, Camera, Equatorial};
+    use crate::sky::{direction_at, FoV, Star};
+
+    #[test]
+    fn test_frames() {
+        let attitude = UnitQuaternion::from_euler_angles(0.3, -0.2, 1.0);
+        let vega = Equatorial(direction_at(279.23, 38.78));
+        let camera = vega.to_body(attitude).to_camera();
+        assert_relative_eq!(
+            camera.to_body().to_equatorial(attitude).0,
+            vega.0,
+            epsilon = 1e-6
+        );
+
+        // pointing at a star puts it in the middle
+        let boresight = Camera::boresight().to_body().to_equatorial(attitude);
+        let fov = FoV::new(0.5, 0.5);
+        let center = boresight.to_body(attitude).to_camera();
+        assert_relative_eq!(
+            fov.project_camera(&center).unwrap().norm(),
+            0.0,
+            epsilon = 1e-6
+        );
+        assert_eq!(fov.to_screen(&center, 100, 100), Some((50, 50)));
+        let behind = Body(-Star::z_axis().into_inner()).to_camera();
+        assert!(fov.project_camera(&behind).is_none());
+
+        let turn = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.1);
+        let turned = Equatorial(Star::z_axis().into_inner())
+            .to_body(turn)
+            .to_camera();
+        assert!(turned.is_ahead());
+        assert_relative_eq!(fov.project_camera(&turned).unwrap()[0], 0.1f32.tan() / 0.5);
+    }
+}
@@ -0,0 +1,85 @@
+use nalgebra::{Matrix3, Rotation3};
+
+use crate::sky::{direction_at, ra_dec, Sky, Star};
+
+/// The rotation from equatorial coordinates (J2000) to galactic ones, as
+/// defined for the Hipparcos catalog: x points to the galactic center and z
+/// to the north galactic pole.
+pub fn to_galactic() -> Rotation3<f32> {
+    Rotation3::from_matrix_unchecked(Matrix3::new(
+        -0.054_875_56,
+        -0.873_437_1,
+        -0.483_835,
+        0.494_109_43,
+        -0.444_829_6,
+        0.746_982_2,
+        -0.867_666_1,
+        -0.198_076_4,
+        0.455_983_8,
+    ))
+}
+
+/// the north galactic pole, in equatorial coordinates
+pub fn galactic_pole() -> Star {
+    to_galactic().inverse() * Star::z_axis().into_inner()
+}
+
+/// galactic longitude and latitude of `direction` (equatorial), in degrees
+pub fn galactic_coords(direction: &Star) -> (f32, f32) {
+    ra_dec(&(to_galactic() * direction))
+}
+
+/// the equatorial direction at galactic longitude `l` and latitude `b`, in degrees
+pub fn from_galactic(l: f32, b: f32) -> Star {
+    to_galactic().inverse() * direction_at(l, b)
+}
+
+impl Sky {
+    /// the sky in galactic coordinates
+    pub fn to_galactic(&self) -> Self {
+        self.with_attitude(to_galactic().into())
+    }
+
+    /// the sky in equatorial coordinates, when it is in galactic ones
+    pub fn to_equatorial(&self) -> Self {
+        self.with_attitude(to_galactic().inverse().into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{from_galactic, galactic_coords, galactic_pole};
+    use crate::sky::{angle_between, direction_at, ra_dec, Brightness, CatalogStar, Sky};
+
+    #[test]
+    fn test_galactic() {
+        // the north galactic pole is at 12h 51m 26.3s, +27° 07' 42"
+        let (ra, dec) = ra_dec(&galactic_pole());
+        assert_relative_eq!(ra, 192.859_5, epsilon = 1e-3);
+        assert_relative_eq!(dec, 27.128_3, epsilon = 1e-3);
+        // and the galactic center at 17h 45m 37.2s, -28° 56' 10"
+        let center = direction_at(266.405, -28.936);
+        let (l, b) = galactic_coords(&center);
+        assert!(!(0.01..359.99).contains(&l));
+        assert_relative_eq!(b, 0.0, epsilon = 1e-2);
+        assert!(angle_between(&from_galactic(0.0, 0.0), &center).to_degrees() < 1e-2);
+
+        let deneb = direction_at(310.358, 45.280);
+        let (l, b) = galactic_coords(&deneb);
+        assert_relative_eq!(l, 84.28, epsilon = 1e-2);
+        assert_relative_eq!(b, 1.99, epsilon = 1e-2);
+        assert!(angle_between(&from_galactic(l, b), &deneb).to_degrees() < 1e-3);
+
+        let sky = Sky::from(&[CatalogStar::new(deneb, Brightness::new(0.5), "α Cyg")]);
+        let galactic = sky.to_galactic();
+        assert_relative_eq!(
+            galactic[0].position()[2],
+            1.99f32.to_radians().sin(),
+            epsilon = 1e-3
+        );
+        let back = galactic.to_equatorial();
+        assert!(angle_between(back[0].position(), &deneb).to_degrees() < 1e-3);
+    }
+}
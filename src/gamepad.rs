@@ -0,0 +1,139 @@
+use crate::config::GamepadConfig;
+
+/// `value`, an axis from -1 to 1, with the dead zone around the center taken
+/// away: nothing within it, and the rest stretched to still reach 1, so that
+/// a stick at rest does not turn the view.
+pub fn dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let dead_zone = dead_zone.clamp(0.0, 0.99);
+    if value.abs() <= dead_zone {
+        0.0
+    } else {
+        value.signum() * ((value.abs() - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+}
+
+/// How fast a gamepad turns and zooms the view, each from -1 to 1, in steps
+/// for the time of a step of the keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rates {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
+    /// zooming in, or out when negative
+    pub zoom: f32,
+}
+
+impl Rates {
+    /// The rates from the left stick (pitch and yaw), the horizontal axis of
+    /// the right one (roll) and the left and right triggers (zoom out and in),
+    /// outside of the dead zones of `config`.
+    pub fn from_axes(
+        left: (f32, f32),
+        right_x: f32,
+        triggers: (f32, f32),
+        config: &GamepadConfig,
+    ) -> Self {
+        let stick = |value| dead_zone(value, config.stick_dead_zone);
+        let trigger = |value| dead_zone(value, config.trigger_dead_zone);
+        Self {
+            pitch: stick(left.1),
+            yaw: stick(left.0),
+            roll: stick(right_x),
+            zoom: trigger(triggers.1) - trigger(triggers.0),
+        }
+    }
+
+    /// how fast it turns, as the fastest of its axes
+    pub fn turning(&self) -> f32 {
+        self.pitch.abs().max(self.yaw.abs()).max(self.roll.abs())
+    }
+}
+
+/// The gamepads connected, read once in each frame. Without the `gamepad`
+/// feature there are none.
+pub struct Gamepad {
+    #[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+    config: GamepadConfig,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+impl Gamepad {
+    pub fn new(config: &GamepadConfig) -> Self {
+        Self {
+            config: config.clone(),
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
+        }
+    }
+
+    /// the rates of the first gamepad connected, or none
+    #[cfg(feature = "gamepad")]
+    pub fn rates(&mut self) -> Rates {
+        use gilrs::{Axis, Button};
+
+        let Some(gilrs) = &mut self.gilrs else {
+            return Rates::default();
+        };
+        // the state of the gamepads is kept up to date by reading their events
+        while gilrs.next_event().is_some() {}
+        let Some((_, pad)) = gilrs.gamepads().next() else {
+            return Rates::default();
+        };
+        let trigger = |button| pad.button_data(button).map_or(0.0, |data| data.value());
+        Rates::from_axes(
+            (pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY)),
+            pad.value(Axis::RightStickX),
+            (
+                trigger(Button::LeftTrigger2),
+                trigger(Button::RightTrigger2),
+            ),
+            &self.config,
+        )
+    }
+
+    /// the rates of the first gamepad connected, or none
+    #[cfg(not(feature = "gamepad"))]
+    pub fn rates(&mut self) -> Rates {
+        Rates::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{dead_zone, Rates};
+    use crate::config::GamepadConfig;
+
+    #[test]
+    fn test_dead_zone() {
+        assert_eq!(dead_zone(0.1, 0.15), 0.0);
+        assert_eq!(dead_zone(-0.15, 0.15), 0.0);
+        assert_relative_eq!(dead_zone(0.5, 0.2), 0.375);
+        assert_relative_eq!(dead_zone(-1.0, 0.2), -1.0);
+        assert_relative_eq!(dead_zone(0.3, 0.0), 0.3);
+    }
+
+    #[test]
+    fn test_rates() {
+        let config = GamepadConfig {
+            stick_dead_zone: 0.2,
+            trigger_dead_zone: 0.1,
+        };
+        // a stick resting a bit off center does nothing
+        let still = Rates::from_axes((0.1, -0.15), 0.05, (0.05, 0.0), &config);
+        assert_eq!(still, Rates::default());
+        assert_eq!(still.turning(), 0.0);
+
+        let rates = Rates::from_axes((0.6, -1.0), 0.0, (0.0, 1.0), &config);
+        assert_relative_eq!(rates.yaw, 0.5);
+        assert_relative_eq!(rates.pitch, -1.0);
+        assert_eq!(rates.roll, 0.0);
+        assert_relative_eq!(rates.zoom, 1.0);
+        assert_relative_eq!(rates.turning(), 1.0);
+        // both triggers pressed cancel each other
+        let both = Rates::from_axes((0.0, 0.0), 0.0, (1.0, 1.0), &config);
+        assert_eq!(both.zoom, 0.0);
+    }
+}
@@ -1,23 +1,188 @@
-use core::time;
-use std::{cell::RefCell, rc::Rc, thread};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
+use ::rand::{Rng, SeedableRng};
 use macroquad::prelude::*;
 use macroquad::Window;
-use nalgebra::UnitQuaternion;
+use nalgebra::{Unit, UnitQuaternion};
+use rand_chacha::ChaCha8Rng;
 
 use crate::{
-    sky::{quat_coords_str, random_quaternion, FoV, Sky},
-    view::{get_help_lines, Options, Scoring},
+    assist::{PairAssist, PENALTY},
+    attitude::{centering, is_aligned, parse_attitude, AttitudeError},
+    config::{Config, GamepadConfig, InvertConfig, ViewConfig, WindowConfig},
+    deepsky::{bright_objects, DeepSkyKind, DeepSkyObject},
+    difficulty::Difficulty,
+    ephemeris::julian_date,
+    frames::{Camera, Equatorial},
+    galactic::galactic_pole,
+    gamepad::Gamepad,
+    hint::{self, Hint},
+    lod::LevelOfDetail,
+    macros::{Macro, Turn},
+    observer::SIDEREAL_RATE,
+    sky::{
+        equatorial_grid, great_circle, quat_coords_str, random_quaternion, random_quaternion_with,
+        Brightness, CatalogStar, FoV, Occluder, Sky, SkyRotation, Star,
+    },
+    store::CatalogStore,
+    timing::{Stage, Timings, ENABLED},
+    tutorial::{tutorial_sky, tutorial_start, Action, Tutorial},
+    variable,
+    view::{
+        edge_arrow, get_help_lines, preset_step, star_info, toggle_all_sky, GameMetadata, Options,
+        Scoring, Slew,
+    },
 };
 
+/// how much faster than real time variable stars change: an hour every second
+const SIMULATED_SPEED: f64 = 3600.0;
+/// how much faster than the Earth turns the target drifts: 15° per minute
+const DRIFT_SPEED: f32 = 60.0;
+/// how long the turn to the start of a new game lasts
+const ARRIVAL: Duration = Duration::from_millis(800);
+/// how long a held key takes to turn the view by a step, and to count a move
+const STEP_TIME: f32 = 0.05;
+/// how long drawing the sky may take, before fewer stars are drawn
+const DRAW_TIME: Duration = Duration::from_millis(12);
+/// the side of each sprite of the atlas stars are drawn with, in texels
+const SPRITE: u16 = 32;
+/// the most stars in a mesh, within the vertices and indices macroquad draws at once
+const STARS_PER_MESH: usize = 800;
+/// how long a solved game is celebrated, before the next one
+const CELEBRATION: Duration = Duration::from_millis(2500);
+
+/// A part of the window, from `x_min` to `x_max` of its width and from
+/// `y_min` to `y_max` of its height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Viewport {
+    x_min: f32,
+    x_max: f32,
+    y_min: f32,
+    y_max: f32,
+}
+
+impl Viewport {
+    const WHOLE: Self = Self {
+        x_min: 0.0,
+        x_max: 1.0,
+        y_min: 0.0,
+        y_max: 1.0,
+    };
+}
+
+/// Sparks flying out of the center of the window when a game is solved,
+/// with how it was solved.
+struct Celebration {
+    started: Instant,
+    message: String,
+    /// the velocity of each spark, in pixels per second, and its color
+    sparks: Vec<(Vec2, Color)>,
+}
+
+impl Celebration {
+    fn new(message: String) -> Self {
+        // not from the generator of the games, so that a seed gives the
+        // same games however they are solved
+        let mut rng = ::rand::thread_rng();
+        let colors = [GOLD, YELLOW, ORANGE, WHITE, SKYBLUE];
+        let sparks = (0..150)
+            .map(|_| {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let speed = rng.gen_range(60.0..420.0);
+                let color = colors[rng.gen_range(0..colors.len())];
+                (vec2(angle.cos(), angle.sin()) * speed, color)
+            })
+            .collect();
+        Self {
+            started: Instant::now(),
+            message,
+            sparks,
+        }
+    }
+}
+
 pub struct GSkyView {
+    /// the stars of the game, from the brightest to the faintest
     pub sky: Sky,
+    false_sky: Sky,
+    /// the catalogs read, to make new skies from
+    catalogs: CatalogStore,
+    /// how many of the stars there is time to draw
+    lod: RefCell<LevelOfDetail>,
+    /// the part of a move turned with the keys, not counted yet
+    turning: f32,
+    gamepad: Gamepad,
+    /// the ways of turning that go the other way
+    invert: InvertConfig,
+    /// the turns recorded so far, while recording a macro
+    recording: Option<Macro>,
+    /// the macro recorded last
+    recorded: Macro,
+    /// the turns of the macro still to replay
+    replaying: Option<std::vec::IntoIter<Turn>>,
+    /// the line being typed, if any
+    entry: Option<TextEntry>,
+    /// the star of the sky clicked on, to show what it is
+    selected: Option<usize>,
+    /// the pairs of stars matched so far, while matching them
+    assist: Option<PairAssist>,
+    /// the lessons of the tutorial, while it is taken
+    tutorial: Option<Tutorial>,
+    /// when a hint was last asked for, to show it for a while
+    hint_shown: Option<Instant>,
+    /// what could not be read to make the sky, if anything
+    status: Option<String>,
+    /// the scoring of the games that count, kept aside while practicing:
+    /// meanwhile, moves and games go to a scoring of their own
+    practicing: Option<Rc<RefCell<Scoring>>>,
+    /// where two fingers were in the last frame while pinching the sky, and
+    /// whether they turned it since they were put down
+    pinching: Option<([Vec2; 2], bool)>,
+    /// where the mouse was in the last frame while dragging the sky, and
+    /// whether it moved since the button was pressed
+    dragging: Option<((f32, f32), bool)>,
+    /// where the stars are turned to draw them, kept between frames
+    rotation: RefCell<SkyRotation>,
+    /// where they go on the window, and their index in the sky
+    drawn: RefCell<Vec<(u32, u32, u8, usize)>>,
+    /// the sprites of the stars, drawn together
+    batch: RefCell<StarBatch>,
+    /// how long making and drawing the sky takes
+    timings: RefCell<Timings>,
+    deep_sky: Vec<DeepSkyObject>,
+    /// the julian date the sky was made for
+    sky_date: f64,
+    /// the julian date the game started at, for the simulated time
+    start_date: f64,
+    started: Instant,
+    /// when the target started drifting, in this game
+    drift_started: Instant,
+    occluder: Occluder,
+    slew: Option<Slew>,
+    /// the turn to the starting attitude of a new game, and that attitude
+    arrival: Option<(Slew, UnitQuaternion<f32>)>,
+    /// the game just solved, celebrated before the next one
+    celebration: Option<Celebration>,
+    /// when this game started, to tell how long it took
+    game_started: Instant,
+    render_scale: f32,
     fov: FoV,
+    /// the field of view to go back to, while showing the whole sky
+    narrow_fov: Option<FoV>,
     target_q: UnitQuaternion<f32>,
     real_q: UnitQuaternion<f32>,
     step: f32,
+    initial_step: f32,
     scoring: Rc<RefCell<Scoring>>,
     options: Options,
+    /// where targets, attitudes and random skies come from
+    rng: ChaCha8Rng,
 }
 
 impl GSkyView {
@@ -25,121 +190,882 @@ impl GSkyView {
         let catalog = Some("assets/bsc5.csv".to_string());
         let nstars: usize = 1200;
         let target_q = random_quaternion();
-        let sky = Sky::new(&catalog, nstars).with_attitude(target_q);
+        let mut catalogs = CatalogStore::default();
+        let (sky, fallback) = catalogs.sky(&catalog, nstars);
+        let sky = sky.with_attitude(target_q);
+        let sky = sky.brightest(sky.len());
         let options = Options {
-            show_distance: false,
-            show_star_names: true,
             catalog_filename: catalog,
             nstars,
-            show_help: false,
-            only_target: false,
+            ..Options::default()
         };
         let fov = FoV::new(2.0, 1.0);
         let real_q = random_quaternion();
         Self {
             sky,
+            false_sky: Sky::from(&[]),
+            catalogs,
+            lod: RefCell::new(LevelOfDetail::new(DRAW_TIME)),
+            turning: 0.0,
+            gamepad: Gamepad::new(&GamepadConfig::default()),
+            invert: InvertConfig::default(),
+            recording: None,
+            recorded: Macro::default(),
+            replaying: None,
+            entry: None,
+            selected: None,
+            assist: None,
+            tutorial: None,
+            hint_shown: None,
+            status: fallback,
+            practicing: None,
+            dragging: None,
+            pinching: None,
+            rotation: RefCell::default(),
+            drawn: RefCell::default(),
+            batch: RefCell::new(StarBatch::new(sprite_atlas())),
+            timings: RefCell::default(),
+            deep_sky: bright_objects(),
+            sky_date: julian_date(SystemTime::now()),
+            start_date: julian_date(SystemTime::now()),
+            started: Instant::now(),
+            drift_started: Instant::now(),
+            occluder: Occluder::random(),
+            slew: None,
+            arrival: None,
+            celebration: None,
+            game_started: Instant::now(),
+            render_scale: 1.0,
             fov,
+            narrow_fov: None,
             target_q,
             real_q,
             step: 0.5,
+            initial_step: 0.5,
             scoring: Rc::clone(&scoring),
             options,
+            rng: ChaCha8Rng::from_entropy(),
+        }
+    }
+    /// The same view, with its games drawn from a generator seeded with
+    /// `seed`: the same seed gives the same sequence of skies and targets.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.new_game();
+        self
+    }
+    /// the same view, reading the gamepads with the dead zones of `config`
+    pub fn with_gamepad(mut self, config: &GamepadConfig) -> Self {
+        self.gamepad = Gamepad::new(config);
+        self
+    }
+    /// the same view, turning each way the other way if `invert` says so
+    pub fn with_invert(mut self, invert: &InvertConfig) -> Self {
+        self.invert = invert.clone();
+        self
+    }
+    /// The same view, taking the tutorial: its first game is on a tiny sky
+    /// of named stars, a little off the target.
+    pub fn with_tutorial(mut self) -> Self {
+        self.target_q = UnitQuaternion::identity();
+        self.real_q = tutorial_start();
+        self.sky = tutorial_sky();
+        self.false_sky = Sky::from(&[]);
+        self.selected = None;
+        self.options.show_star_names = true;
+        self.options.sidereal_drift = false;
+        self.tutorial = Some(Tutorial::default());
+        self
+    }
+    /// Takes `action` as done in the tutorial, if it is being taken.
+    fn learned(&mut self, action: Action) {
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.did(action);
         }
     }
     fn make_sky(&mut self) {
-        self.sky = Sky::new(&self.options.catalog_filename, self.options.nstars)
-            .with_attitude(self.target_q);
+        self.sky_date = julian_date(SystemTime::now());
+        let started = self.timings.get_mut().start();
+        let (sky, fallback) =
+            self.options
+                .catalog_sky(&mut self.catalogs, self.sky_date, &mut self.rng);
+        let sky = sky.with_attitude(self.target_q);
+        self.sky = sky.brightest(sky.len());
+        self.status = fallback;
+        self.timings.get_mut().stop(Stage::Parse, started);
+        self.selected = None;
+        self.false_sky = Sky::false_stars_with(self.options.false_stars, &mut self.rng);
+    }
+    /// the julian date in the game, where time runs `SIMULATED_SPEED` times faster
+    fn simulated_date(&self) -> f64 {
+        self.start_date + self.started.elapsed().as_secs_f64() * SIMULATED_SPEED / 86_400.0
+    }
+    /// `star`, drawn `b` bright through `fov`, as the window shows it now:
+    /// none if it is behind the planet or varies too faint to be seen.
+    fn as_seen<'a>(
+        &self,
+        star: &'a CatalogStar,
+        b: u8,
+        fov: &FoV,
+    ) -> Option<(Cow<'a, CatalogStar>, u8)> {
+        if self.options.occultation && self.occluder.hides(star.position(), self.real_q) {
+            return None;
+        }
+        let Some(magnitude) = variable::magnitude_at(star, self.simulated_date()) else {
+            return Some((Cow::Borrowed(star), b));
+        };
+        let varied = star.with_brightness(Brightness::from_magnitude(magnitude));
+        if !fov.can_be_seen(&varied.brightness()) {
+            return None;
+        }
+        // as much brighter than the faintest as the star is now
+        let ratio = varied.brightness().value() / star.brightness().value().max(f32::EPSILON);
+        let b = (128.0 + (b as f32 - 128.0) * ratio).min(255.0) as u8;
+        Some((Cow::Owned(varied), b))
     }
     pub fn options(&self) -> &Options {
         &self.options
     }
+    /// the same view, playing at `difficulty` from a new game
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.set_difficulty(difficulty);
+        self.new_game();
+        self
+    }
+    /// The same view, practicing from the start.
+    pub fn with_practice(mut self) -> Self {
+        self.practicing = Some(std::mem::take(&mut self.scoring));
+        self
+    }
+    /// Starts practicing after scoring the game played, or stops and goes
+    /// back to the games that count with a new one.
+    fn toggle_practice(&mut self) {
+        match self.practicing.take() {
+            Some(scoring) => self.scoring = scoring,
+            None => {
+                self.score();
+                self.practicing = Some(std::mem::take(&mut self.scoring));
+            }
+        }
+        let from = self.real_q;
+        self.new_game();
+        self.arrive_from(from);
+    }
+    /// Plays at `difficulty`, with its field of view, from the next game.
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.options.difficulty = difficulty;
+        let sight = difficulty.sight();
+        self.options.set_sight(sight);
+        let scale = sight.fov_scale;
+        self.fov = FoV::new(2.0 * scale, scale);
+        self.narrow_fov = None;
+    }
+    /// the same view with the options, field of view and step of a previous run
+    pub fn with_config(mut self, config: &ViewConfig) -> Self {
+        self.options = config.options.clone();
+        self.fov = FoV::new(config.fov.0, config.fov.1);
+        self.step = config.step;
+        self.initial_step = config.step;
+        self.make_sky();
+        self
+    }
+    pub fn config(&self) -> ViewConfig {
+        ViewConfig {
+            options: self.options.clone(),
+            fov: self.narrow_fov.as_ref().unwrap_or(&self.fov).half_fov(),
+            step: self.step,
+        }
+    }
+    /// Turns the view by `x`, `y` and `z` steps of pitch, yaw and roll.
     fn rotate(&mut self, x: f32, y: f32, z: f32) {
-        self.real_q =
-            UnitQuaternion::from_euler_angles(x * self.step, y * self.step, z * self.step)
-                * self.real_q;
-        (*self.scoring).borrow_mut().add_move();
+        let steps = self.invert.apply([x, y, z]);
+        self.turn(Turn::of_steps(steps, self.step, self.options.body_axes));
+        for (angle, action) in [(x, Action::Pitch), (y, Action::Yaw), (z, Action::Roll)] {
+            if angle != 0.0 {
+                self.learned(action);
+            }
+        }
+    }
+    /// Turns the view by `turn`, recording it while a macro is recorded.
+    fn turn(&mut self, turn: Turn) {
+        self.real_q = turn.apply(self.real_q);
+        if let Some(recording) = &mut self.recording {
+            recording.push(turn);
+        }
+    }
+    /// Starts recording the turns of the view, or stops and keeps them to
+    /// be replayed.
+    fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(recorded) => self.recorded = recorded,
+            None => self.recording = Some(Macro::default()),
+        }
     }
+    /// Turns as the next turn replayed, one in each frame.
+    fn keep_replaying(&mut self) {
+        let Some(turn) = self.replaying.as_mut().and_then(Iterator::next) else {
+            self.replaying = None;
+            return;
+        };
+        self.real_q = turn.apply(self.real_q);
+        self.count_turning(turn.moves);
+    }
+    /// Counts the moves of turning for `steps` of `STEP_TIME`, so that the
+    /// score does not depend on how often frames are drawn.
+    fn count_turning(&mut self, steps: f32) {
+        self.turning += steps;
+        while self.turning >= 1.0 {
+            (*self.scoring).borrow_mut().add_move();
+            self.turning -= 1.0;
+        }
+    }
+    /// Follows the fingers on a touch screen: one drags the sky as the mouse
+    /// does, and two twist it about the center to roll and pinch it to zoom.
+    /// Without fingers on the screen, the mouse drags it.
+    fn touch(&mut self) {
+        let mut fingers: Vec<Touch> = touches()
+            .into_iter()
+            .filter(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .collect();
+        fingers.sort_by_key(|t| t.id);
+        match fingers[..] {
+            [] => {
+                self.pinching = None;
+                self.drag(is_mouse_button_down(MouseButton::Left), mouse_position());
+            }
+            [ref finger] => {
+                let here = (finger.position.x, finger.position.y);
+                // the finger left after a pinch goes on dragging, and is no tap
+                if self.pinching.take().is_some() {
+                    self.dragging = Some((here, true));
+                }
+                self.drag(true, here);
+            }
+            [ref first, ref second, ..] => {
+                self.dragging = None;
+                self.pinch([first.position, second.position]);
+            }
+        }
+    }
+    /// Turns and zooms the view as two fingers at `fingers` twist and pinch
+    /// the sky since the last frame. Each pinch that turns the view is a move.
+    fn pinch(&mut self, fingers: [Vec2; 2]) {
+        let moved = match self.pinching {
+            Some((before, moved)) if before != fingers => {
+                let (width, height) = (screen_width(), screen_height());
+                let fov = self.fov_for(width, height);
+                match pinch_motion(&fov, before, fingers, width, height) {
+                    Some((rotation, scale)) => {
+                        self.turn(Turn::of_rotation(rotation, if moved { 0.0 } else { 1.0 }));
+                        self.fov = self.fov.rescale(scale);
+                        if !moved {
+                            (*self.scoring).borrow_mut().add_move();
+                        }
+                        true
+                    }
+                    None => moved,
+                }
+            }
+            Some((_, moved)) => moved,
+            None => false,
+        };
+        self.pinching = Some((fingers, moved));
+    }
+    /// Turns the view as the mouse, or a finger, drags the sky from where it
+    /// was in the last frame to `here`, so that the star under it follows it.
+    /// Each drag is a move. Pressed and released without moving, it clicks.
+    fn drag(&mut self, down: bool, here: (f32, f32)) {
+        if !down {
+            if let Some((at, false)) = self.dragging.take() {
+                self.click(at);
+            }
+            return;
+        }
+        let moved = match self.dragging {
+            Some((last, moved)) if last != here => {
+                let (width, height) = (screen_width(), screen_height());
+                let rotation = self
+                    .fov_for(width, height)
+                    .drag_rotation(last, here, width, height);
+                self.turn(Turn::of_rotation(rotation, if moved { 0.0 } else { 1.0 }));
+                if !moved {
+                    (*self.scoring).borrow_mut().add_move();
+                }
+                true
+            }
+            Some((_, moved)) => moved,
+            None => false,
+        };
+        self.dragging = Some((here, moved));
+    }
+    /// Turns the view to center the selected star, as one move.
+    fn center_selected(&mut self) {
+        if let Some(i) = self.selected {
+            self.real_q = centering(&(self.real_q * self.sky[i].position())) * self.real_q;
+            (*self.scoring).borrow_mut().add_move();
+        }
+    }
+    /// Where the target is drawn, as the left, top, width and height of the
+    /// part of the window it takes.
+    fn target_rect(&self) -> (f32, f32, f32, f32) {
+        if self.options.only_target {
+            (0.0, 0.0, 1.0, 1.0)
+        } else {
+            (0.0, 0.7, 0.3, 0.3)
+        }
+    }
+    /// The star nearest to the point `at` of a view turned by `quat` in the
+    /// part `rect` of the window, and where the camera of that view sees it.
+    fn star_at(
+        &self,
+        quat: UnitQuaternion<f32>,
+        at: (f32, f32),
+        rect: (f32, f32, f32, f32),
+    ) -> Option<(usize, Star)> {
+        let (left, top) = (rect.0 * screen_width(), rect.1 * screen_height());
+        let (width, height) = (rect.2 * screen_width(), rect.3 * screen_height());
+        let fov = self.fov_for(width, height);
+        let direction = fov.screen_to_direction(at.0 - left, at.1 - top, width, height);
+        let (i, _) = self.sky.nearest_star(&(quat.inverse() * direction), &fov)?;
+        Some((i, quat * self.sky[i].position()))
+    }
+    /// Selects the star clicked on in the window or, matching pairs of
+    /// stars, picks it in the window or the target.
+    fn click(&mut self, at: (f32, f32)) {
+        let rect = self.target_rect();
+        let in_target =
+            at.0 < (rect.0 + rect.2) * screen_width() && at.1 >= rect.1 * screen_height();
+        if self.assist.is_none() {
+            if !in_target {
+                let found = self.star_at(self.real_q, at, (0.0, 0.0, 1.0, 1.0));
+                self.selected = found.map(|(i, _)| i);
+            }
+            return;
+        }
+        let found = if in_target {
+            self.star_at(self.target(), at, rect)
+        } else {
+            self.star_at(self.real_q, at, (0.0, 0.0, 1.0, 1.0))
+        };
+        let Some((_, direction)) = found else {
+            return;
+        };
+        let rotation = self.assist.as_mut().and_then(|assist| {
+            if in_target {
+                assist.pick_target(direction)
+            } else {
+                assist.pick_window(direction);
+                None
+            }
+        });
+        if let Some(rotation) = rotation {
+            self.real_q = rotation * self.real_q;
+            self.assist = None;
+            (*self.scoring).borrow_mut().moves += PENALTY;
+        }
+    }
+    /// the field of view on a viewport of `width` by `height` pixels
+    fn fov_for(&self, width: f32, height: f32) -> FoV {
+        self.fov
+            .with_aspect(width, height)
+            .with_limiting_magnitude(self.options.limiting_magnitude)
+    }
+    /// Draws the stars of `sky` turned to `quat` in `viewport`, with their names in
+    /// `font_size` if they are shown. The `live` stars are those of the
+    /// window, as they vary and hide behind the planet, and not the target.
     fn draw_stars(
         &self,
+        sky: &[CatalogStar],
         quat: UnitQuaternion<f32>,
-        x_min: f32,
-        x_max: f32,
-        y_min: f32,
-        y_max: f32,
+        viewport: Viewport,
         font: Option<&Font>,
         font_size: u16,
+        live: bool,
     ) {
-        let width = (x_max - x_min) * 256.0;
-        let height = (y_max - y_min) * 256.0;
-        for fps in self
-            .fov
-            .project_sky_to_screen(self.sky.with_attitude(quat), width as u8, height as u8)
-            .into_iter()
-            .flatten()
-        {
-            let (px, py, b, n) = fps;
-            let px = (x_min + (px as f32) / 256.0) * screen_width();
-            let py = (y_min + (py as f32) / 256.0) * screen_height();
+        let Viewport {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+        } = viewport;
+        let width = (x_max - x_min) * screen_width();
+        let height = (y_max - y_min) * screen_height();
+        let fov = self.fov_for(width, height);
+        let mut timings = self.timings.borrow_mut();
+        let started = timings.start();
+        let mut rotation = self.rotation.borrow_mut();
+        let positions = rotation.rotate(sky, quat);
+        timings.stop(Stage::Rotate, started);
+        let started = timings.start();
+        let mut drawn = self.drawn.borrow_mut();
+        fov.screen_stars_into(sky, positions, width as u32, height as u32, &mut drawn);
+        timings.stop(Stage::Project, started);
+        let started = timings.start();
+        let at = |px: u32, py: u32| {
+            (
+                x_min * screen_width() + px as f32,
+                y_min * screen_height() + py as f32,
+            )
+        };
+        let seen = |b: u8, i: usize| {
+            if live {
+                self.as_seen(&sky[i], b, &fov)
+            } else {
+                Some((Cow::Borrowed(&sky[i]), b))
+            }
+        };
+        let mut batch = self.batch.borrow_mut();
+        for &(px, py, b, i) in drawn.iter() {
+            let Some((star, b)) = seen(b, i) else {
+                continue;
+            };
+            let (px, py) = at(px, py);
             let b = (b as f32 - 64.0) / 192.0;
-            let color = Color::new(b, b, b, 1.0);
-            draw_circle(px, py, 4.0, color);
-            if self.options.show_star_names {
-                draw_text_ex(
-                    &n,
-                    px + 6.0,
-                    py,
-                    TextParams {
-                        font_size,
-                        font,
-                        ..Default::default()
-                    },
-                );
+            let [r, g, bl] = star.tint();
+            let color = Color::new(b * r, b * g, b * bl, 1.0);
+            let size = star.brightness().glyph_size();
+            let radius = (1.5 + 4.5 * size) * self.render_scale;
+            // a soft halo around the brightest stars, fading outwards
+            if size > 0.5 {
+                let glow = Color::new(color.r, color.g, color.b, 0.48 * (size - 0.5));
+                batch.add(px, py, 3.0 * radius, Sprite::Halo, glow);
+            }
+            batch.add(px, py, radius, Sprite::Disc, color);
+        }
+        batch.flush();
+        if self.options.show_star_names {
+            for &(px, py, b, i) in drawn.iter() {
+                let Some((star, _)) = seen(b, i) else {
+                    continue;
+                };
+                let (px, py) = at(px, py);
+                let name = star.display_name(self.options.name_style);
+                if !name.is_empty() {
+                    draw_text_ex(
+                        &name,
+                        px + 6.0 * self.render_scale,
+                        py,
+                        TextParams {
+                            font_size: self.scaled(font_size),
+                            font,
+                            ..Default::default()
+                        },
+                    );
+                }
             }
         }
+        timings.stop(Stage::Draw, started);
+    }
+    /// Draws over the whole window the great circle 90° away from `pole`:
+    /// the horizon around the zenith, or the galactic equator around its pole.
+    fn draw_great_circle(&self, quat: UnitQuaternion<f32>, pole: Equatorial, color: Color) {
+        self.draw_sky_line(quat, &great_circle(&pole.direction(), 360), 2.0, color);
+    }
+
+    /// Draws over the whole window the closed line through `points`, given
+    /// in catalog coordinates, `thickness` pixels wide.
+    fn draw_sky_line(
+        &self,
+        quat: UnitQuaternion<f32>,
+        points: &[Star],
+        thickness: f32,
+        color: Color,
+    ) {
+        let (width, height) = (screen_width(), screen_height());
+        let fov = self.fov_for(width, height);
+        let attitude = quat * self.target_q;
+        let points: Vec<_> = points
+            .iter()
+            .map(|point| {
+                let point = Equatorial::new(*point).to_body(attitude).to_camera();
+                let fpp = fov.project_camera(&point)?;
+                Some(((fpp[0] + 1.0) / 2.0 * width, (fpp[1] + 1.0) / 2.0 * height))
+            })
+            .collect();
+        for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            if let (Some((x1, y1)), Some((x2, y2))) = (a, b) {
+                draw_line(*x1, *y1, *x2, *y2, thickness * self.render_scale, color);
+            }
+        }
+    }
+
+    /// Draws the galaxies, clusters and nebulae over the whole window, each
+    /// kind with its own glyph, as large as the object looks.
+    fn draw_deep_sky(&self, quat: UnitQuaternion<f32>, font: &Font) {
+        let (width, height) = (screen_width(), screen_height());
+        let fov = self.fov_for(width, height);
+        let (half_fov_x, _) = fov.half_fov();
+        for object in &self.deep_sky {
+            let position = Equatorial::new(*object.position())
+                .to_body(quat * self.target_q)
+                .to_camera();
+            let Some((px, py)) = fov.to_screen(&position, width as u32, height as u32) else {
+                continue;
+            };
+            let (x, y) = (px as f32, py as f32);
+            let r = ((object.angular_size() / 2.0).tan() / half_fov_x * width / 2.0)
+                .max(5.0 * self.render_scale);
+            let thickness = self.render_scale;
+            match object.kind() {
+                DeepSkyKind::Galaxy => {
+                    draw_ellipse_lines(x, y, r, r / 2.5, 30.0, thickness, ORANGE);
+                }
+                DeepSkyKind::OpenCluster => {
+                    draw_poly_lines(x, y, 12, r, 0.0, thickness, YELLOW);
+                }
+                DeepSkyKind::GlobularCluster => {
+                    draw_circle_lines(x, y, r, thickness, YELLOW);
+                    draw_line(x - r, y, x + r, y, thickness, YELLOW);
+                    draw_line(x, y - r, x, y + r, thickness, YELLOW);
+                }
+                DeepSkyKind::Nebula => {
+                    draw_rectangle_lines(x - r, y - r, 2.0 * r, 2.0 * r, thickness, GREEN);
+                }
+                DeepSkyKind::PlanetaryNebula => {
+                    draw_circle_lines(x, y, r, thickness, SKYBLUE);
+                    draw_circle(x, y, r / 3.0, SKYBLUE);
+                }
+            }
+            draw_text_ex(
+                object.designation(),
+                x + r + 2.0 * self.render_scale,
+                y,
+                TextParams {
+                    font_size: self.scaled(12),
+                    font: Some(font),
+                    color: GRAY,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    /// The attitude to reach: `target_q`, turned about the celestial pole
+    /// as the Earth turns when the target drifts.
+    fn target(&self) -> UnitQuaternion<f32> {
+        if !self.options.sidereal_drift {
+            return self.target_q;
+        }
+        let pole = Unit::new_normalize(self.target_q * Star::z_axis().into_inner());
+        let angle = -SIDEREAL_RATE * DRIFT_SPEED * self.drift_started.elapsed().as_secs_f32();
+        self.target_q * UnitQuaternion::from_axis_angle(&pole, angle)
     }
     fn distance(&self) -> f32 {
-        let (roll, pitch, yaw) = (self.target_q / self.real_q).euler_angles();
-        (roll.powi(2) + pitch.powi(2) + yaw.powi(2)).sqrt()
+        self.options.metric.distance(self.target(), self.real_q)
     }
     fn restart(&mut self) {
-        (*self.scoring)
-            .borrow_mut()
-            .score_and_reset(self.distance());
-        self.target_q = random_quaternion();
+        self.score();
+        let from = self.real_q;
+        self.new_game();
+        self.arrive_from(from);
+    }
+    /// turns smoothly from `from` to the starting attitude of the game
+    fn arrive_from(&mut self, from: UnitQuaternion<f32>) {
+        self.arrival = Some((Slew::new(from).lasting(ARRIVAL), self.real_q));
+        self.real_q = from;
+    }
+    fn game_metadata(&self) -> GameMetadata {
+        GameMetadata {
+            false_stars: self.options.false_stars,
+            occultation: self.options.occultation,
+            difficulty: self.options.difficulty,
+            solved: is_aligned(self.target(), self.real_q, self.options.win_tolerance),
+            seconds: self.game_started.elapsed().as_secs_f32(),
+            moves: (*self.scoring).borrow().moves,
+        }
+    }
+    fn score(&mut self) {
+        let metadata = self.game_metadata();
+        (*self.scoring).borrow_mut().score_and_reset(
+            self.distance() * self.options.difficulty.score_weight(),
+            metadata,
+        );
+        self.learned(Action::Score);
+    }
+    /// Scores the game just solved, and celebrates it before the next one.
+    fn celebrate(&mut self) {
+        let metadata = self.game_metadata();
+        self.score();
+        self.replaying = None;
+        self.celebration = Some(Celebration::new(format!(
+            "Solved in {:.1} s and {} moves!",
+            metadata.seconds, metadata.moves
+        )));
+    }
+    fn new_game(&mut self) {
+        self.target_q = random_quaternion_with(&mut self.rng);
+        self.occluder = Occluder::random_with(&mut self.rng);
         self.make_sky();
-        self.real_q = random_quaternion();
-        self.step = 0.5;
+        self.real_q = self
+            .options
+            .difficulty
+            .starting_attitude(self.target_q, &mut self.rng);
+        self.step = self.initial_step;
+        self.drift_started = Instant::now();
+        self.game_started = Instant::now();
+        self.replaying = None;
+        self.hint_shown = None;
+    }
+    /// Shows for a while the turn still needed to reach the target, for
+    /// the moves of a hint.
+    fn show_hint(&mut self) {
+        self.hint_shown = Some(Instant::now());
+        (*self.scoring).borrow_mut().moves += hint::PENALTY;
+    }
+    /// Does what the line typed for `entry` asks, or tells why it cannot.
+    fn submit(&mut self, entry: Entry, text: &str) -> Result<(), String> {
+        match entry {
+            Entry::Attitude => {
+                self.real_q = parse_attitude(text)?;
+                (*self.scoring).borrow_mut().add_move();
+            }
+            Entry::Search => {
+                let found = self.sky.find(text);
+                self.selected =
+                    Some(found.ok_or_else(|| format!("no star called {}", text.trim()))?);
+                self.center_selected();
+            }
+        }
+        Ok(())
+    }
+    /// Reads what is typed in the entry, if it is open, submitting it with enter.
+    /// Returns whether it was open, so that keys do nothing else meanwhile.
+    fn handle_entry(&mut self) -> bool {
+        let Some(entry) = &mut self.entry else {
+            return false;
+        };
+        match entry.read() {
+            Typed::Editing => {}
+            Typed::Cancelled => self.entry = None,
+            Typed::Done(text) => {
+                let kind = entry.entry;
+                match self.submit(kind, &text) {
+                    Ok(()) => self.entry = None,
+                    Err(e) => {
+                        if let Some(entry) = &mut self.entry {
+                            entry.error = Some(e);
+                        }
+                    }
+                }
+            }
+        }
+        true
     }
     fn handle_keys(&mut self) -> bool {
+        if let Some((slew, start)) = &self.arrival {
+            match slew.at(*start) {
+                Some(q) => self.real_q = q,
+                None => {
+                    self.real_q = *start;
+                    self.arrival = None;
+                }
+            }
+            return is_key_pressed(KeyCode::Q);
+        }
+        if let Some(slew) = &self.slew {
+            match slew.at(self.target()) {
+                Some(q) => self.real_q = q,
+                None => {
+                    self.slew = None;
+                    let from = self.real_q;
+                    self.new_game();
+                    self.arrive_from(from);
+                }
+            }
+            return is_key_pressed(KeyCode::Q);
+        }
+        if let Some(celebration) = &self.celebration {
+            if celebration.started.elapsed() >= CELEBRATION {
+                self.celebration = None;
+                let from = self.real_q;
+                self.new_game();
+                self.arrive_from(from);
+            }
+            return is_key_pressed(KeyCode::Q);
+        }
+        if self.handle_entry() {
+            return false;
+        }
+        if is_aligned(self.target(), self.real_q, self.options.win_tolerance) {
+            self.celebrate();
+            return false;
+        }
         let sign = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
-        let sign_step: f32 = if sign { self.step } else { -self.step };
-        if is_key_down(KeyCode::P) {
-            self.rotate(-sign_step, 0.0, 0.0);
+        if sign && is_key_pressed(KeyCode::Semicolon) {
+            self.entry = Some(TextEntry::new(Entry::Attitude));
+            return false;
+        }
+        if !sign && is_key_pressed(KeyCode::Slash) {
+            self.entry = Some(TextEntry::new(Entry::Search));
+            return false;
+        }
+        if sign && is_key_pressed(KeyCode::Slash) {
+            self.show_hint();
+        }
+        // as far as the keys turn in the time this frame took, but no
+        // farther than a few steps after a pause, as when the window is moved
+        let steps = (get_frame_time() / STEP_TIME).min(5.0);
+        let signed_steps = if sign { steps } else { -steps };
+        let letters = [KeyCode::P, KeyCode::Y, KeyCode::R].map(|key| {
+            if is_key_down(key) {
+                signed_steps
+            } else {
+                0.0
+            }
+        });
+        if letters != [0.0; 3] {
+            self.rotate(-letters[0], letters[1], letters[2]);
+        }
+        // the arrow and page keys turn as the letters do, each way without shift
+        let arrows = [
+            (KeyCode::Up, KeyCode::Down),
+            (KeyCode::Right, KeyCode::Left),
+            (KeyCode::PageDown, KeyCode::PageUp),
+        ]
+        .map(|(ahead, back)| (is_key_down(ahead) as i8 - is_key_down(back) as i8) as f32 * steps);
+        if arrows != [0.0; 3] {
+            self.rotate(arrows[0], arrows[1], arrows[2]);
+        }
+        if letters != [0.0; 3] || arrows != [0.0; 3] {
+            self.count_turning(steps);
         }
-        if is_key_down(KeyCode::Y) {
-            self.rotate(0.0, sign_step, 0.0);
+        let rates = self.gamepad.rates();
+        if rates.turning() > 0.0 {
+            self.rotate(-rates.pitch * steps, rates.yaw * steps, rates.roll * steps);
+            self.count_turning(rates.turning() * steps);
         }
-        if is_key_down(KeyCode::R) {
-            self.rotate(0.0, 0.0, sign_step);
+        if rates.zoom != 0.0 {
+            self.fov = self.fov.rescale(1.0905f32.powf(-rates.zoom * steps));
+        }
+        self.touch();
+        if is_key_pressed(KeyCode::J) {
+            self.options.body_axes = !self.options.body_axes;
         }
         if is_key_pressed(KeyCode::S) {
             self.step *= 1.1892f32.powf(if sign { 1.0 } else { -1.0 });
         }
+        let presets = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+            KeyCode::Key8,
+            KeyCode::Key9,
+        ];
+        if let Some(digit) = presets.iter().position(|&key| is_key_pressed(key)) {
+            self.step = preset_step(digit as u32 + 1);
+        }
         if is_key_pressed(KeyCode::Z) {
             let scale = 1.0905f32.powf(if sign { 1.0 } else { -1.0 });
             let fov = self.fov.rescale(scale);
             self.fov = fov;
+            self.learned(Action::Zoom);
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            // zoom in as the wheel rolls forward, about the point under the mouse
+            let scale = 1.0905f32.powf(-wheel.signum());
+            let (width, height) = (screen_width(), screen_height());
+            let turn =
+                self.fov_for(width, height)
+                    .zoom_rotation(scale, mouse_position(), width, height);
+            self.real_q = turn * self.real_q;
+            self.fov = self.fov.rescale(scale);
+            self.learned(Action::Zoom);
         }
         if is_key_pressed(KeyCode::N) {
-            self.options.show_star_names = !self.options.show_star_names;
+            if sign {
+                self.options.name_style = self.options.name_style.next();
+            } else {
+                self.options.show_star_names = !self.options.show_star_names;
+            }
         }
         if is_key_pressed(KeyCode::V) {
             let mult: f32 = if sign { 1.25 } else { 0.8 };
             self.options.nstars = (self.options.nstars as f32 * mult).max(8.0) as usize;
             self.make_sky();
         }
+        if is_key_pressed(KeyCode::F) {
+            self.options.false_stars = if sign {
+                self.options.false_stars + 5
+            } else {
+                self.options.false_stars.saturating_sub(5)
+            };
+            self.false_sky = Sky::false_stars_with(self.options.false_stars, &mut self.rng);
+        }
+        if is_key_pressed(KeyCode::E) {
+            if sign {
+                self.options
+                    .next_optics(&mut self.fov, &mut self.narrow_fov);
+            } else {
+                self.fov = self.fov.with_vignetting(!self.fov.vignetting());
+            }
+        }
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            self.options.change_limiting_magnitude(&self.fov, 0.5);
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            self.options.change_limiting_magnitude(&self.fov, -0.5);
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            toggle_all_sky(&mut self.fov, &mut self.narrow_fov);
+        }
+        if is_key_pressed(KeyCode::O) {
+            self.options.occultation = !self.options.occultation;
+        }
+        if is_key_pressed(KeyCode::B) {
+            self.options.solar_system = !self.options.solar_system;
+            self.make_sky();
+        }
+        if is_key_pressed(KeyCode::G) {
+            if sign {
+                self.center_selected();
+            } else {
+                self.options.sidereal_drift = !self.options.sidereal_drift;
+                self.drift_started = Instant::now();
+            }
+        }
+        if is_key_pressed(KeyCode::U) {
+            self.options.refraction = !self.options.refraction;
+            self.make_sky();
+        }
+        if is_key_pressed(KeyCode::X) {
+            self.options.conditions = self.options.conditions.next();
+            self.make_sky();
+        }
+        if is_key_pressed(KeyCode::I) {
+            if sign {
+                self.options.grid = !self.options.grid;
+            } else {
+                self.options.galactic_equator = !self.options.galactic_equator;
+            }
+        }
+        if is_key_pressed(KeyCode::L) {
+            self.options.horizon = !self.options.horizon;
+            self.make_sky();
+        }
+        if is_key_pressed(KeyCode::M) {
+            if sign {
+                self.assist = match self.assist {
+                    None => Some(PairAssist::default()),
+                    Some(_) => None,
+                };
+            } else {
+                self.options.deep_sky = !self.options.deep_sky;
+            }
+        }
         if is_key_pressed(KeyCode::D) {
-            self.options.show_distance = !self.options.show_distance;
+            if sign {
+                self.score();
+                self.set_difficulty(self.options.difficulty.next());
+                let from = self.real_q;
+                self.new_game();
+                self.arrive_from(from);
+            } else {
+                self.options.show_distance = !self.options.show_distance;
+            }
         }
         if is_key_pressed(KeyCode::H) {
             self.options.show_help = !self.options.show_help;
@@ -147,28 +1073,89 @@ impl GSkyView {
         if is_key_pressed(KeyCode::Space) {
             self.restart();
         }
+        if is_key_pressed(KeyCode::A) {
+            self.score();
+            self.slew = Some(Slew::new(self.real_q));
+        }
         if is_key_pressed(KeyCode::T) {
-            self.options.only_target = !self.options.only_target;
+            if sign {
+                self.toggle_practice();
+            } else {
+                self.options.only_target = !self.options.only_target;
+            }
         }
+        if is_key_pressed(KeyCode::Escape) {
+            self.selected = None;
+            self.replaying = None;
+        }
+        if is_key_pressed(KeyCode::F5) {
+            self.toggle_recording();
+        }
+        if is_key_pressed(KeyCode::F6) {
+            if let Some(recorded) = self.recording.take() {
+                self.recorded = recorded;
+            }
+            self.replaying = Some(self.recorded.replay(if sign { 0.5 } else { 1.0 }));
+        }
+        self.keep_replaying();
 
         if is_key_pressed(KeyCode::Q) {
-            self.restart();
+            self.score();
             return true;
         }
         false
     }
 
+    /// `font_size` grown for the user's render scale
+    fn scaled(&self, font_size: u16) -> u16 {
+        (font_size as f32 * self.render_scale).round() as u16
+    }
+
     fn draw(&self, font: &Font) {
+        let started = Instant::now();
+        let fov = self.fov_for(screen_width(), screen_height());
+        let nstars = self.lod.borrow().count(&self.sky, &fov);
+        let shown = &self.sky.stars[..nstars.min(self.sky.len())];
         clear_background(BLACK);
-        self.draw_stars(self.real_q, 0.0, 1.0, 0.0, 1.0, Some(font), 16);
-        self.draw_target_rectangle(font);
+        if self.options.deep_sky {
+            self.draw_deep_sky(self.real_q, font);
+        }
+        if self.options.horizon {
+            let zenith = self.options.observer.zenith(self.sky_date);
+            self.draw_great_circle(self.real_q, Equatorial::new(zenith), DARKGREEN);
+        }
+        if self.options.galactic_equator {
+            self.draw_great_circle(self.real_q, Equatorial::new(galactic_pole()), DARKPURPLE);
+        }
+        if self.options.grid {
+            let (ra_step, dec_step) = self.options.grid_step;
+            let color = Color::new(0.25, 0.35, 0.6, 0.7);
+            for line in equatorial_grid(ra_step, dec_step, 360) {
+                self.draw_sky_line(self.real_q, &line, 1.0, color);
+            }
+        }
+        for sky in [shown, &self.false_sky.stars] {
+            self.draw_stars(sky, self.real_q, Viewport::WHOLE, Some(font), 16, true);
+        }
+        self.draw_target_rectangle(shown, font);
         self.draw_help();
-        self.show_state(font);
+        self.draw_selection(&fov, font);
+        self.draw_hint(&fov, font);
+        self.show_state();
+        self.draw_tutorial(font);
+        self.draw_entry(font);
+        self.draw_celebration(font);
+        self.lod.borrow_mut().frame_took(started.elapsed(), nstars);
+        self.timings.borrow_mut().end_frame();
     }
 
     fn draw_help(&self) {
         if self.options.show_help {
-            let (reltx, relty, reltw, relth, font_size) = (0.6, 0.1, 0.4, 0.8, 20);
+            let (reltx, relty, reltw, relth) = (0.6, 0.1, 0.4, 0.8);
+            let help_lines = get_help_lines();
+            // as big as it fits in the height of the window
+            let fitting = relth * screen_height() / (help_lines.len() as f32 * 1.12);
+            let font_size = self.scaled(20).min(fitting as u16);
             draw_rectangle(
                 reltx * screen_width(),
                 relty * screen_height(),
@@ -176,11 +1163,11 @@ impl GSkyView {
                 relth * screen_height(),
                 BLACK,
             );
-            for (i, line) in get_help_lines().iter().enumerate() {
+            for (i, line) in help_lines.iter().enumerate() {
                 draw_text(
                     line,
                     reltx * screen_width(),
-                    relty * screen_width() + (font_size * i) as f32 * 1.12,
+                    relty * screen_height() + (font_size as usize * i) as f32 * 1.12,
                     font_size as f32,
                     WHITE,
                 );
@@ -188,17 +1175,15 @@ impl GSkyView {
         }
     }
 
-    fn draw_target_rectangle(&self, font: &Font) {
-        let (reltx, relty, reltw, relth, font_size) = if self.options.only_target {
-            (0.0, 0.0, 1.0, 1.0, 16)
-        } else {
-            (0.0, 0.7, 0.3, 0.3, 12)
-        };
+    /// Draws `sky` as seen from the target attitude, in a corner or over the whole window.
+    fn draw_target_rectangle(&self, sky: &[CatalogStar], font: &Font) {
+        let (reltx, relty, reltw, relth) = self.target_rect();
+        let font_size = if self.options.only_target { 16 } else { 12 };
         let (tx, ty, tw, th) = (
             reltx * screen_width(),
             relty * screen_height(),
             reltw * screen_width(),
-            relth * screen_width(),
+            relth * screen_height(),
         );
 
         draw_rectangle(tx, ty, tw, th, BLACK);
@@ -206,72 +1191,680 @@ impl GSkyView {
             draw_line(tx, ty, tx + tw, ty, 1.0, YELLOW);
             draw_line(tx + tw, ty, tx + tw, ty + th, 1.0, YELLOW);
         }
-        self.draw_stars(
-            self.target_q,
-            reltx,
-            reltx + reltw,
-            relty,
-            relty + relth,
-            Some(font),
-            font_size,
-        );
+        let viewport = Viewport {
+            x_min: reltx,
+            x_max: reltx + reltw,
+            y_min: relty,
+            y_max: relty + relth,
+        };
+        self.draw_stars(sky, self.target(), viewport, Some(font), font_size, false);
     }
 
-    fn show_state(&self, font: &Font) {
+    /// Circles the selected star, and tells what it is in the bottom right corner.
+    fn draw_selection(&self, fov: &FoV, font: &Font) {
+        let Some(star) = self.selected.map(|i| &self.sky[i]) else {
+            return;
+        };
+        let (width, height) = (screen_width(), screen_height());
+        let direction = Equatorial::new(*star.position())
+            .to_body(self.real_q)
+            .to_camera();
+        if let Some((x, y)) = fov.to_screen(&direction, width as u32, height as u32) {
+            let radius = 12.0 * self.render_scale;
+            draw_circle_lines(x as f32, y as f32, radius, 1.5, YELLOW);
+        } else {
+            // out of sight, an arrow on the edge points the way to it
+            let length = 24.0 * self.render_scale;
+            let (x, y, angle) = edge_arrow(&direction, width, height, length);
+            let ahead = vec2(angle.cos(), angle.sin());
+            let across = vec2(-ahead.y, ahead.x) * length / 3.0;
+            let tip = vec2(x, y) + ahead * length / 2.0;
+            let back = vec2(x, y) - ahead * length / 2.0;
+            draw_triangle(tip, back + across, back - across, YELLOW);
+        }
+        let boresight = Camera::boresight()
+            .to_body()
+            .to_equatorial(self.real_q)
+            .direction();
+        let info = star_info(star, self.options.name_style, self.target_q, &boresight);
+        let size = self.scaled(16);
+        let line_height = 1.2 * size as f32;
+        let (box_width, box_height) = (300.0 * self.render_scale, line_height * 4.5);
+        let (left, top) = (width - box_width - 10.0, height - box_height - 10.0);
+        draw_rectangle(
+            left,
+            top,
+            box_width,
+            box_height,
+            Color::new(0.1, 0.1, 0.1, 0.8),
+        );
+        for (i, line) in info.iter().enumerate() {
+            draw_text_ex(
+                line,
+                left + 8.0,
+                top + line_height * (i as f32 + 1.0),
+                TextParams {
+                    font: Some(font),
+                    font_size: size,
+                    color: YELLOW,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    /// While a hint is shown, fading away: an arrow from where the center
+    /// of the target is to the center of the view, the way to drag it, and
+    /// an arc about the center as far as to twist it.
+    fn draw_hint(&self, fov: &FoV, font: &Font) {
+        let Some(elapsed) = self.hint_shown.map(|shown| shown.elapsed()) else {
+            return;
+        };
+        if elapsed >= hint::SHOWN {
+            return;
+        }
+        let fade = 1.0 - elapsed.as_secs_f32() / hint::SHOWN.as_secs_f32();
+        let color = Color { a: fade, ..ORANGE };
+        let thickness = 3.0 * self.render_scale;
+        let length = 24.0 * self.render_scale;
+        let arrow_head = |tip: Vec2, ahead: Vec2| {
+            let across = vec2(-ahead.y, ahead.x) * length / 3.0;
+            let back = tip - ahead * length;
+            draw_triangle(tip, back + across, back - across, color);
+        };
+        let (width, height) = (screen_width(), screen_height());
+        let center = vec2(width / 2.0, height / 2.0);
+        let hint = Hint::between(self.target(), self.real_q);
+        let direction = hint.target_center();
+        let from = match fov.to_screen(&direction, width as u32, height as u32) {
+            Some((x, y)) => vec2(x as f32, y as f32),
+            None => {
+                let (x, y, _) = edge_arrow(&direction, width, height, length);
+                vec2(x, y)
+            }
+        };
+        if from.distance(center) > length {
+            draw_line(from.x, from.y, center.x, center.y, thickness, color);
+            arrow_head(center, (center - from).normalize());
+        }
+        let roll = AttitudeError::between(self.target(), self.real_q).roll;
+        let radius = 0.15 * height;
+        let on_arc = |angle: f32| center + radius * vec2(angle.cos(), angle.sin());
+        // from the top of the circle, clockwise as the roll turns the sky
+        let start = -std::f32::consts::FRAC_PI_2;
+        let segments = (roll.abs().to_degrees() / 5.0).ceil() as usize;
+        for i in 0..segments {
+            let (a, b) = (
+                on_arc(start + roll * i as f32 / segments as f32),
+                on_arc(start + roll * (i + 1) as f32 / segments as f32),
+            );
+            draw_line(a.x, a.y, b.x, b.y, thickness, color);
+        }
+        if segments > 0 {
+            let end = start + roll;
+            arrow_head(on_arc(end), vec2(-end.sin(), end.cos()) * roll.signum());
+        }
+        let text = format!("hint, {} moves: {hint}", hint::PENALTY);
+        let size = self.scaled(18);
+        let text_width = measure_text(&text, Some(font), size, 1.0).width;
+        draw_text_ex(
+            &text,
+            center.x - text_width / 2.0,
+            center.y + radius + 2.0 * size as f32,
+            TextParams {
+                font: Some(font),
+                font_size: size,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+    /// the message of the game just solved, among sparks fading away
+    fn draw_celebration(&self, font: &Font) {
+        let Some(celebration) = &self.celebration else {
+            return;
+        };
+        let t = celebration.started.elapsed().as_secs_f32();
+        let fade = (1.0 - t / CELEBRATION.as_secs_f32()).max(0.0);
+        let center = vec2(screen_width() / 2.0, screen_height() / 2.0);
+        for (velocity, color) in &celebration.sparks {
+            let at = center + *velocity * t * self.render_scale;
+            let color = Color { a: fade, ..*color };
+            draw_circle(at.x, at.y, 2.5 * self.render_scale, color);
+        }
+        let size = self.scaled(36);
+        let width = measure_text(&celebration.message, Some(font), size, 1.0).width;
+        draw_text_ex(
+            &celebration.message,
+            center.x - width / 2.0,
+            center.y - 1.5 * size as f32,
+            TextParams {
+                font: Some(font),
+                font_size: size,
+                color: GOLD,
+                ..Default::default()
+            },
+        );
+    }
+    /// the lesson of the tutorial, in a box over the lower part of the sky
+    fn draw_tutorial(&self, font: &Font) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        let prompt = tutorial.prompt();
+        let size = self.scaled(22);
+        let width = measure_text(&prompt, Some(font), size, 1.0).width;
+        let (left, top) = ((screen_width() - width) / 2.0, 0.75 * screen_height());
+        draw_rectangle(
+            left - size as f32,
+            top - 1.2 * size as f32,
+            width + 2.0 * size as f32,
+            1.8 * size as f32,
+            Color::new(0.0, 0.1, 0.2, 0.8),
+        );
+        draw_text_ex(
+            &prompt,
+            left,
+            top,
+            TextParams {
+                font: Some(font),
+                font_size: size,
+                color: SKYBLUE,
+                ..Default::default()
+            },
+        );
+    }
+    /// the line being typed, at the bottom of the window
+    fn draw_entry(&self, font: &Font) {
+        let Some(entry) = &self.entry else {
+            return;
+        };
+        let size = self.scaled(18);
+        let height = 2.4 * size as f32;
+        let top = screen_height() - height;
+        draw_rectangle(
+            0.0,
+            top,
+            screen_width(),
+            height,
+            Color::new(0.1, 0.1, 0.1, 0.9),
+        );
+        let params = TextParams {
+            font: Some(font),
+            font_size: size,
+            color: WHITE,
+            ..Default::default()
+        };
+        let line = format!("{}: {}_", entry.entry.prompt(), entry.text);
+        draw_text_ex(&line, 10.0, top + size as f32, params.clone());
+        if let Some(error) = &entry.error {
+            let params = TextParams {
+                color: RED,
+                ..params
+            };
+            draw_text_ex(error, 10.0, top + 2.1 * size as f32, params);
+        }
+    }
+    fn show_state(&self) {
         let header_1 = format!(
-            "Stars: {}, catalog: {}. Step: {:.4}, zoom: {:.3}, moves: {}, games: {}, score: {:.6}",
+            "Stars: {}{}, catalog: {}, {}{}. Step: {:.4} ({} axes), zoom: {:.3}{}, limit: {:.1}, moves: {}, games: {}, score: {:.6}",
             self.options.nstars,
+            self.options.false_stars_str(),
             self.options
                 .catalog_filename
                 .clone()
                 .unwrap_or("random".to_string()),
+            self.options.difficulty,
+            if self.practicing.is_some() {
+                ", practice"
+            } else {
+                ""
+            },
             self.step,
+            self.options.axes_str(),
             self.fov.zoom(),
+            self.options
+                .optics
+                .map(|optics| format!(" ({optics})"))
+                .unwrap_or_default(),
+            self.fov
+                .with_limiting_magnitude(self.options.limiting_magnitude)
+                .limiting_magnitude(),
             (*self.scoring).borrow().moves,
             (*self.scoring).borrow().total.len(),
             (*self.scoring).borrow().get_score(),
         );
-        draw_text(&header_1, 10.0, 20.0, 18.0, GRAY);
-        let state_text = format!("State : {}", quat_coords_str(self.real_q));
-        draw_text(&state_text, 10.0, 38.0, 18.0, GRAY);
-        if self.options.show_distance {
+        let size = 18.0 * self.render_scale;
+        draw_text(&header_1, 10.0, 20.0 * self.render_scale, size, GRAY);
+        let boresight = Camera::boresight()
+            .to_body()
+            .to_equatorial(self.real_q)
+            .direction();
+        let state_text = format!(
+            "State : {},    near: {}{}",
+            quat_coords_str(self.real_q),
+            self.sky.nearest_constellation(&boresight).unwrap_or("?"),
+            self.status
+                .as_ref()
+                .map(|status| format!(",    {status}"))
+                .unwrap_or_default()
+        );
+        draw_text(
+            &state_text,
+            10.0,
+            20.0 * self.render_scale + size,
+            size,
+            GRAY,
+        );
+        if self.options.show_distance || self.practicing.is_some() {
             let dist_text = format!(
-                "Target: {},    t/s: {},    distance: {:.6}",
-                quat_coords_str(self.target_q),
-                quat_coords_str(self.target_q / self.real_q),
-                self.distance()
+                "Target: {},    t/s: {},    distance: {:.6},    {}",
+                quat_coords_str(self.target()),
+                quat_coords_str(self.target() / self.real_q),
+                self.distance(),
+                AttitudeError::between(self.target(), self.real_q)
+            );
+            draw_text(
+                &dist_text,
+                10.0,
+                20.0 * self.render_scale + 2.0 * size,
+                size,
+                GRAY,
+            );
+        }
+        let macro_state = match (&self.recording, &self.replaying) {
+            (Some(recording), _) => Some(format!("recording: {} turns", recording.len())),
+            (None, Some(replaying)) => Some(format!("replaying: {} turns left", replaying.len())),
+            (None, None) => None,
+        };
+        if let Some(macro_state) = macro_state {
+            let width = measure_text(&macro_state, None, size as u16, 1.0).width;
+            draw_text(
+                &macro_state,
+                screen_width() - width - 10.0,
+                20.0 * self.render_scale + 3.0 * size,
+                size,
+                ORANGE,
+            );
+        }
+        if let Some(assist) = &self.assist {
+            let prompt = format!("{}, {PENALTY} moves", assist.prompt());
+            draw_text(
+                &prompt,
+                10.0,
+                20.0 * self.render_scale + 3.0 * size,
+                size,
+                YELLOW,
             );
-            draw_text(&dist_text, 10.0, 56.0, 18.0, GRAY);
         }
     }
 }
 
-fn window_conf() -> Conf {
+fn window_conf(window: &WindowConfig) -> Conf {
     Conf {
         window_title: "CuYAt".to_owned(),
-        fullscreen: false,
-        window_width: 1200,
-        window_height: 600,
+        fullscreen: window.fullscreen,
+        window_width: window.width,
+        window_height: window.height,
+        window_resizable: true,
+        high_dpi: true,
+        platform: miniquad::conf::Platform {
+            swap_interval: Some(1),
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
 
-pub fn launch(scoring: Rc<RefCell<Scoring>>) {
-    Window::from_config(window_conf(), main_loop(scoring));
+/// What a line typed in the window is for
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Entry {
+    /// an attitude to jump to
+    Attitude,
+    /// the name of a star to select
+    Search,
 }
 
-pub async fn main_loop(scoring: Rc<RefCell<Scoring>>) {
+impl Entry {
+    fn prompt(self) -> &'static str {
+        match self {
+            Entry::Attitude => "go to (roll pitch yaw in degrees, or w i j k)",
+            Entry::Search => "find a star (name, proper name or HR number)",
+        }
+    }
+}
+
+/// What became of a line being typed, after a frame
+enum Typed {
+    Editing,
+    Done(String),
+    Cancelled,
+}
+
+/// A line typed at the bottom of the window, and why the last one was not taken.
+struct TextEntry {
+    entry: Entry,
+    text: String,
+    error: Option<String>,
+}
+
+impl TextEntry {
+    fn new(entry: Entry) -> Self {
+        // the key that opened it is not part of the line
+        while get_char_pressed().is_some() {}
+        Self {
+            entry,
+            text: String::new(),
+            error: None,
+        }
+    }
+
+    /// takes the keys typed in this frame
+    fn read(&mut self) -> Typed {
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                self.text.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.text.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            Typed::Cancelled
+        } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            Typed::Done(self.text.clone())
+        } else {
+            Typed::Editing
+        }
+    }
+}
+
+/// The sprites of the atlas, side by side.
+#[derive(Clone, Copy)]
+enum Sprite {
+    /// a disc with a soft edge
+    Disc,
+    /// the halo of the brightest stars, fading outwards
+    Halo,
+}
+
+/// How two fingers going from `before` to `fingers` on a viewport of `width`
+/// by `height` pixels turn the screen, twisting it about the center and
+/// zooming it about the point between them, and how much they rescale the
+/// field of view: apart they zoom in. None if they are too close to tell.
+fn pinch_motion(
+    fov: &FoV,
+    before: [Vec2; 2],
+    fingers: [Vec2; 2],
+    width: f32,
+    height: f32,
+) -> Option<(UnitQuaternion<f32>, f32)> {
+    let (from, to) = (before[1] - before[0], fingers[1] - fingers[0]);
+    if from.length() < 1.0 || to.length() < 1.0 {
+        return None;
+    }
+    let twist = fov.twist_rotation(from.perp_dot(to).atan2(from.dot(to)), width, height);
+    let scale = from.length() / to.length();
+    let middle = (fingers[0] + fingers[1]) / 2.0;
+    let zoom = fov.zoom_rotation(scale, (middle.x, middle.y), width, height);
+    Some((zoom * twist, scale))
+}
+
+/// The sprites stars are drawn with, in one texture: white, to be tinted with
+/// the color of each star.
+fn sprite_atlas() -> Texture2D {
+    let side = SPRITE as usize;
+    let half = side as f32 / 2.0;
+    let mut bytes = Vec::with_capacity(side * side * 8);
+    for y in 0..side {
+        for x in 0..2 * side {
+            // from 0 at the center of the sprite to 1 at its edge
+            let d = (x % side) as f32 + 0.5 - half;
+            let d = d.hypot(y as f32 + 0.5 - half) / half;
+            let alpha = if x < side {
+                ((1.0 - d) * half).clamp(0.0, 1.0)
+            } else {
+                (1.0 - d).max(0.0).powi(2)
+            };
+            bytes.extend([255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    let texture = Texture2D::from_rgba8(2 * SPRITE, SPRITE, &bytes);
+    texture.set_filter(FilterMode::Linear);
+    texture
+}
+
+/// Stars to draw as textured squares, in a few meshes a frame instead of a
+/// call for each star. The buffers are kept from frame to frame.
+struct StarBatch {
+    atlas: Texture2D,
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+}
+
+impl StarBatch {
+    fn new(atlas: Texture2D) -> Self {
+        Self {
+            atlas,
+            vertices: Vec::with_capacity(4 * STARS_PER_MESH),
+            indices: Vec::with_capacity(6 * STARS_PER_MESH),
+        }
+    }
+
+    /// Adds `sprite`, tinted with `color`, `radius` pixels around (`x`, `y`).
+    fn add(&mut self, x: f32, y: f32, radius: f32, sprite: Sprite, color: Color) {
+        if self.vertices.len() >= 4 * STARS_PER_MESH {
+            self.flush();
+        }
+        let u = match sprite {
+            Sprite::Disc => 0.0,
+            Sprite::Halo => 0.5,
+        };
+        let first = self.vertices.len() as u16;
+        for (dx, dy, du, v) in [
+            (-1.0, -1.0, 0.0, 0.0),
+            (1.0, -1.0, 0.5, 0.0),
+            (1.0, 1.0, 0.5, 1.0),
+            (-1.0, 1.0, 0.0, 1.0),
+        ] {
+            self.vertices.push(Vertex::new(
+                x + dx * radius,
+                y + dy * radius,
+                0.0,
+                u + du,
+                v,
+                color,
+            ));
+        }
+        self.indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+    }
+
+    /// Draws the stars added so far.
+    fn flush(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let mesh = Mesh {
+            vertices: std::mem::take(&mut self.vertices),
+            indices: std::mem::take(&mut self.indices),
+            texture: Some(self.atlas.clone()),
+        };
+        draw_mesh(&mesh);
+        self.vertices = mesh.vertices;
+        self.indices = mesh.indices;
+        self.vertices.clear();
+        self.indices.clear();
+    }
+}
+
+/// Keeps the main loop from drawing more than a number of frames per second,
+/// sleeping only what is left of each frame so that keys are read right away.
+struct FrameLimiter {
+    frame: Option<Duration>,
+    started: Instant,
+}
+
+impl FrameLimiter {
+    fn new(target_fps: u32) -> Self {
+        Self {
+            frame: (target_fps > 0).then(|| Duration::from_secs_f32(1.0 / target_fps as f32)),
+            started: Instant::now(),
+        }
+    }
+
+    fn wait(&mut self) {
+        if let Some(frame) = self.frame {
+            let elapsed = self.started.elapsed();
+            if elapsed < frame {
+                thread::sleep(frame - elapsed);
+            }
+        }
+        self.started = Instant::now();
+    }
+}
+
+fn draw_debug_hud(render_scale: f32, timings: &Timings) {
+    let hud = format!("fps: {}", get_fps());
+    draw_text(
+        &hud,
+        screen_width() - 90.0 * render_scale,
+        20.0 * render_scale,
+        18.0 * render_scale,
+        GREEN,
+    );
+    if ENABLED {
+        let timings = timings.to_string();
+        let width = measure_text(&timings, None, (18.0 * render_scale) as u16, 1.0).width;
+        draw_text(
+            &timings,
+            screen_width() - width - 10.0 * render_scale,
+            40.0 * render_scale,
+            18.0 * render_scale,
+            GREEN,
+        );
+    }
+}
+
+/// Plays in a window until `q` is pressed, starting from the GUI options and
+/// the window geometry in `config`, and with the games of `seed` if there is one.
+/// With `tutorial`, it starts by taking the tutorial, and with `practice`,
+/// practicing. Returns `config` with the options and geometry the window ended with.
+pub fn launch(
+    scoring: Rc<RefCell<Scoring>>,
+    config: Config,
+    seed: Option<u64>,
+    difficulty: Option<Difficulty>,
+    tutorial: bool,
+    practice: bool,
+) -> Config {
+    let conf = window_conf(&config.window);
+    let config = Rc::new(RefCell::new(config));
+    Window::from_config(
+        conf,
+        main_loop(
+            scoring,
+            Rc::clone(&config),
+            seed,
+            difficulty,
+            tutorial,
+            practice,
+        ),
+    );
+    config.take()
+}
+
+pub async fn main_loop(
+    scoring: Rc<RefCell<Scoring>>,
+    config: Rc<RefCell<Config>>,
+    seed: Option<u64>,
+    difficulty: Option<Difficulty>,
+    tutorial: bool,
+    practice: bool,
+) {
     let font = load_ttf_font("assets/Piazzolla-Medium.ttf").await.unwrap();
-    let mut view = GSkyView::new(Rc::clone(&scoring));
+    // fingers are followed on their own, not as a mouse
+    simulate_mouse_with_touch(false);
+    let mut view = GSkyView::new(Rc::clone(&scoring))
+        .with_gamepad(&config.borrow().gamepad)
+        .with_invert(&config.borrow().invert);
+    if let Some(gui) = &config.borrow().gui {
+        view = view.with_config(gui);
+    }
+    if let Some(difficulty) = difficulty {
+        view = view.with_difficulty(difficulty);
+    }
+    if let Some(seed) = seed {
+        view = view.with_seed(seed);
+    }
+    if tutorial {
+        view = view.with_tutorial();
+    }
+    if practice {
+        view = view.with_practice();
+    }
+    let mut window = config.borrow().window.clone();
+    // macroquad already draws at the pixel density of the screen
+    view.render_scale = window.render_scale;
+    let mut limiter = FrameLimiter::new(window.target_fps);
+    let mut show_hud = false;
 
     loop {
         let must_stop = view.handle_keys();
         if must_stop {
             break;
         }
+        if is_key_pressed(KeyCode::F11) {
+            window.fullscreen = !window.fullscreen;
+            set_fullscreen(window.fullscreen);
+        }
+        if !window.fullscreen {
+            window.width = screen_width() as i32;
+            window.height = screen_height() as i32;
+        }
+        if is_key_pressed(KeyCode::F3) {
+            show_hud = !show_hud;
+        }
         view.draw(&font);
+        if show_hud {
+            draw_debug_hud(view.render_scale, &view.timings.borrow());
+        }
 
-        thread::sleep(time::Duration::from_millis(50));
+        limiter.wait();
         next_frame().await;
     }
+    let mut config = config.borrow_mut();
+    config.gui = Some(view.config());
+    config.window = window;
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use macroquad::math::vec2;
+    use nalgebra::UnitQuaternion;
+
+    use super::pinch_motion;
+    use crate::{
+        macros::{Macro, Turn},
+        sky::FoV,
+    };
+
+    #[test]
+    fn test_pinch_replay() {
+        let (width, height) = (800.0, 600.0);
+        let fov = FoV::new(2.0, 1.0).with_aspect(width, height);
+        let before = [vec2(300.0, 300.0), vec2(500.0, 300.0)];
+        let after = [vec2(280.0, 260.0), vec2(540.0, 340.0)];
+        let (rotation, scale) = pinch_motion(&fov, before, after, width, height).unwrap();
+        assert!(scale < 1.0);
+
+        // the pinch is recorded as the turn it makes, and replays the same
+        let q = UnitQuaternion::from_euler_angles(0.3, -0.2, 1.0);
+        let mut recorded = Macro::default();
+        recorded.push(Turn::of_rotation(rotation, 1.0));
+        assert_relative_eq!(recorded.apply(q, 1.0), rotation * q, epsilon = 1e-5);
+        assert_eq!(recorded.replay(1.0).map(|t| t.moves).sum::<f32>(), 1.0);
+
+        let together = [before[0], before[0]];
+        assert!(pinch_motion(&fov, before, together, width, height).is_none());
+    }
 }
@@ -0,0 +1,99 @@
+use std::{fmt, time::Duration};
+
+use nalgebra::{Unit, UnitQuaternion, Vector3};
+
+use crate::frames::Camera;
+
+/// the moves added to the score each time a hint is asked for
+pub const PENALTY: usize = 5;
+/// how long a hint is shown
+pub const SHOWN: Duration = Duration::from_secs(3);
+
+/// The turn still needed to reach the target from the view, the shorter way
+/// round, about the axes of the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hint {
+    pub axis: Unit<Vector3<f32>>,
+    /// in radians, from 0 to π
+    pub angle: f32,
+}
+
+impl Hint {
+    pub fn between(target_q: UnitQuaternion<f32>, real_q: UnitQuaternion<f32>) -> Self {
+        let (axis, angle) = (target_q / real_q)
+            .axis_angle()
+            .unwrap_or((Vector3::z_axis(), 0.0));
+        Self { axis, angle }
+    }
+
+    /// the turn, that takes the view to the target as `turn * real_q`
+    pub fn turn(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&self.axis, self.angle)
+    }
+
+    /// where the center of the target is in the view, in the frame of the
+    /// camera: what the turn brings to the center
+    pub fn target_center(&self) -> Camera {
+        // the boresight of the view once turned, in the frame of the view
+        Camera::boresight()
+            .to_body()
+            .to_equatorial(self.turn())
+            .to_body(UnitQuaternion::identity())
+            .to_camera()
+    }
+
+    /// pitch, yaw and roll, in radians, that make up the turn as the keys
+    /// turn one after the other
+    pub fn angles(&self) -> [f32; 3] {
+        let (pitch, yaw, roll) = self.turn().euler_angles();
+        [pitch, yaw, roll]
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [pitch, yaw, roll] = self.angles().map(f32::to_degrees);
+        write!(
+            f,
+            "turn {:.1}° about ({:+.2}, {:+.2}, {:+.2}): pitch {pitch:+.1}°, yaw {yaw:+.1}°, roll {roll:+.1}°",
+            self.angle.to_degrees(),
+            self.axis.x,
+            self.axis.y,
+            self.axis.z
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use approx::assert_relative_eq;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    use super::Hint;
+    use crate::attitude::geodesic_distance;
+
+    #[test]
+    fn test_hint() {
+        let real = UnitQuaternion::from_euler_angles(0.7, -0.3, 2.0);
+        let turn = UnitQuaternion::from_euler_angles(0.1, 0.2, -0.3);
+        let hint = Hint::between(turn * real, real);
+        assert_relative_eq!(geodesic_distance(hint.turn() * real, turn * real), 0.0);
+        let [pitch, yaw, roll] = hint.angles();
+        assert_relative_eq!(pitch, 0.1, epsilon = 1e-5);
+        assert_relative_eq!(yaw, 0.2, epsilon = 1e-5);
+        assert_relative_eq!(roll, -0.3, epsilon = 1e-5);
+        assert!(hint
+            .to_string()
+            .ends_with(": pitch +5.7°, yaw +11.5°, roll -17.2°"));
+
+        // 300° one way is 60° the other
+        let far = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 300f32.to_radians());
+        let hint = Hint::between(far * real, real);
+        assert_relative_eq!(hint.angle, PI / 3.0, epsilon = 1e-5);
+        assert_relative_eq!(hint.axis.y, -1.0, epsilon = 1e-5);
+        // the center of the target is as far from that of the view
+        assert_relative_eq!(hint.target_center().direction().z, 0.5, epsilon = 1e-5);
+    }
+}
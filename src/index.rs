@@ -0,0 +1,178 @@
+use std::cmp::Ordering;
+
+use crate::sky::{CatalogStar, Sky, Star};
+
+/// A node of the tree: the star at the median of its subtree along `axis`,
+/// with the stars below it on the left and those above on the right.
+#[derive(Clone, Debug)]
+struct Node {
+    star: usize,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A k-d tree over the directions of the stars of a sky, to find those
+/// near a direction without going through all of them.
+/// Directions are compared by the chord between them on the unit sphere,
+/// which grows with the angle.
+#[derive(Clone, Debug)]
+pub struct SkyIndex<'a> {
+    sky: &'a Sky,
+    positions: Vec<Star>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+/// the length of the chord between two directions `angle` radians apart
+fn chord(angle: f32) -> f32 {
+    2.0 * (angle.clamp(0.0, std::f32::consts::PI) / 2.0).sin()
+}
+
+impl<'a> SkyIndex<'a> {
+    pub fn new(sky: &'a Sky) -> Self {
+        let positions: Vec<Star> = sky.iter().map(|star| star.position().normalize()).collect();
+        let mut index = Self {
+            sky,
+            positions,
+            nodes: Vec::with_capacity(sky.len()),
+            root: None,
+        };
+        let mut stars: Vec<usize> = (0..sky.len()).collect();
+        index.root = index.build(&mut stars, 0);
+        index
+    }
+
+    fn build(&mut self, stars: &mut [usize], depth: usize) -> Option<usize> {
+        if stars.is_empty() {
+            return None;
+        }
+        let axis = depth % 3;
+        let median = stars.len() / 2;
+        let positions = &self.positions;
+        stars.select_nth_unstable_by(median, |&a, &b| {
+            positions[a][axis]
+                .partial_cmp(&positions[b][axis])
+                .unwrap_or(Ordering::Equal)
+        });
+        let star = stars[median];
+        let (below, above) = stars.split_at_mut(median);
+        let left = self.build(below, depth + 1);
+        let right = self.build(&mut above[1..], depth + 1);
+        self.nodes.push(Node {
+            star,
+            axis,
+            left,
+            right,
+        });
+        Some(self.nodes.len() - 1)
+    }
+
+    /// the stars within `radius` radians of `direction`, nearest first
+    pub fn cone_search(&self, direction: &Star, radius: f32) -> Vec<&'a CatalogStar> {
+        let direction = direction.normalize();
+        let max_chord = chord(radius);
+        let mut found = vec![];
+        let mut pending: Vec<usize> = self.root.into_iter().collect();
+        while let Some(n) = pending.pop() {
+            let node = &self.nodes[n];
+            let position = &self.positions[node.star];
+            let distance = (position - direction).norm();
+            if distance <= max_chord {
+                found.push((distance, node.star));
+            }
+            let offset = direction[node.axis] - position[node.axis];
+            let (near, far) = if offset < 0.0 {
+                (node.left, node.right)
+            } else {
+                (node.right, node.left)
+            };
+            pending.extend(near);
+            if offset.abs() <= max_chord {
+                pending.extend(far);
+            }
+        }
+        found.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        found.into_iter().map(|(_, i)| &self.sky[i]).collect()
+    }
+
+    /// the star nearest to `direction`, if there are stars
+    pub fn nearest(&self, direction: &Star) -> Option<&'a CatalogStar> {
+        let direction = direction.normalize();
+        let mut best: Option<(f32, usize)> = None;
+        self.nearest_in(self.root, &direction, &mut best);
+        best.map(|(_, i)| &self.sky[i])
+    }
+
+    fn nearest_in(&self, node: Option<usize>, direction: &Star, best: &mut Option<(f32, usize)>) {
+        let Some(n) = node else {
+            return;
+        };
+        let node = &self.nodes[n];
+        let position = &self.positions[node.star];
+        let distance = (position - direction).norm();
+        if best.is_none_or(|(d, _)| distance < d) {
+            *best = Some((distance, node.star));
+        }
+        let offset = direction[node.axis] - position[node.axis];
+        let (near, far) = if offset < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        self.nearest_in(near, direction, best);
+        if best.is_none_or(|(d, _)| offset.abs() < d) {
+            self.nearest_in(far, direction, best);
+        }
+    }
+}
+
+impl Sky {
+    /// an index of the stars, for `cone_search` and `nearest`
+    pub fn index(&self) -> SkyIndex<'_> {
+        SkyIndex::new(self)
+    }
+
+    /// the stars within `radius` radians of `direction`, nearest first
+    pub fn cone_search(&self, direction: &Star, radius: f32) -> Vec<&CatalogStar> {
+        self.index().cone_search(direction, radius)
+    }
+
+    /// the star nearest to `direction`
+    pub fn nearest(&self, direction: &Star) -> Option<&CatalogStar> {
+        self.index().nearest(direction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::sky::{angle_between, direction_at, Sky};
+
+    #[test]
+    fn test_index() {
+        let sky = Sky::random_with_rng(2000, &mut StdRng::seed_from_u64(1));
+        let index = sky.index();
+        for (ra, dec) in [(0.0, 0.0), (83.0, -5.0), (200.0, 89.0), (310.0, -60.0)] {
+            let direction = direction_at(ra, dec);
+            let radius = 10f32.to_radians();
+            let mut expected: Vec<_> = sky
+                .iter()
+                .filter(|star| angle_between(star.position(), &direction) <= radius)
+                .collect();
+            expected.sort_by(|a, b| {
+                angle_between(a.position(), &direction)
+                    .total_cmp(&angle_between(b.position(), &direction))
+            });
+            assert_eq!(index.cone_search(&direction, radius), expected);
+            assert_eq!(index.nearest(&direction), expected.first().copied());
+        }
+        assert!(sky.cone_search(&direction_at(0.0, 0.0), 0.0).len() <= 1);
+        assert_eq!(sky.cone_search(&direction_at(0.0, 0.0), 4.0).len(), 2000);
+
+        let empty = Sky::from(&[]);
+        assert!(empty.nearest(&direction_at(0.0, 0.0)).is_none());
+        assert!(empty.cone_search(&direction_at(0.0, 0.0), 1.0).is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::{fs, path::Path, time::Duration};
+
+use nalgebra::{Quaternion, UnitQuaternion};
+use serde::Deserialize;
+
+/// The controls a level may allow or forbid.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Control {
+    Roll,
+    Pitch,
+    Yaw,
+    Zoom,
+    Scale,
+}
+
+impl Control {
+    pub const ALL: [Control; 5] = [
+        Control::Roll,
+        Control::Pitch,
+        Control::Yaw,
+        Control::Zoom,
+        Control::Scale,
+    ];
+}
+
+/// What the player is allowed to do while playing a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+    pub controls: Vec<Control>,
+    pub max_moves: Option<usize>,
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            controls: Control::ALL.to_vec(),
+            max_moves: None,
+            time_limit: None,
+        }
+    }
+}
+
+impl Rules {
+    pub fn allows(&self, control: Control) -> bool {
+        self.controls.contains(&control)
+    }
+
+    /// whether one more move can be done after `moves` moves and `elapsed` time
+    pub fn allows_move(&self, moves: usize, elapsed: Duration) -> bool {
+        self.max_moves.is_none_or(|max| moves < max)
+            && self.time_limit.is_none_or(|limit| elapsed < limit)
+    }
+}
+
+/// A level, as written in a `.cuyat` file (TOML). For instance:
+///
+/// ```toml
+/// name = "Orion's belt"
+/// catalog = "assets/bsc5.csv"
+/// nstars = 400
+/// target = [0.5, 0.5, 0.5, 0.5]
+/// controls = ["roll", "pitch", "yaw"]
+/// max_moves = 40
+/// time_limit = 120
+/// par = 2.5
+/// ```
+///
+/// `catalog` can be left out to play with random stars, and `seed` can be
+/// given instead of (or together with) `target`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub catalog: Option<String>,
+    pub nstars: usize,
+    pub seed: Option<u64>,
+    pub target: Option<UnitQuaternion<f32>>,
+    pub rules: Rules,
+    pub par: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelFile {
+    name: String,
+    catalog: Option<String>,
+    nstars: usize,
+    seed: Option<u64>,
+    target: Option<[f32; 4]>,
+    controls: Option<Vec<Control>>,
+    max_moves: Option<usize>,
+    time_limit: Option<u64>,
+    par: Option<f32>,
+}
+
+impl Level {
+    pub fn from_file<P: AsRef<Path>>(fname: P) -> Result<Self, String> {
+        let fname = fname.as_ref();
+        let input = fs::read_to_string(fname).map_err(|e| format!("{}: {e}", fname.display()))?;
+        Self::from_toml(&input).map_err(|e| format!("{}: {e}", fname.display()))
+    }
+
+    pub fn from_toml(input: &str) -> Result<Self, String> {
+        let file: LevelFile = toml::from_str(input).map_err(|e| e.to_string())?;
+        if file.nstars == 0 {
+            return Err(String::from("nstars must be positive"));
+        }
+        let target = match file.target {
+            None => None,
+            Some([w, i, j, k]) => Some(
+                UnitQuaternion::try_new(Quaternion::new(w, i, j, k), f32::EPSILON)
+                    .ok_or(String::from("target must not be a null quaternion"))?,
+            ),
+        };
+        let controls = file.controls.unwrap_or(Control::ALL.to_vec());
+        if !controls
+            .iter()
+            .any(|c| [Control::Roll, Control::Pitch, Control::Yaw].contains(c))
+        {
+            return Err(String::from("at least one rotation must be allowed"));
+        }
+        if file.max_moves == Some(0) || file.time_limit == Some(0) {
+            return Err(String::from("limits must be positive"));
+        }
+        if file.par.is_some_and(|par| par <= 0.0) {
+            return Err(String::from("par must be positive"));
+        }
+        Ok(Self {
+            name: file.name,
+            catalog: file.catalog,
+            nstars: file.nstars,
+            seed: file.seed,
+            target,
+            rules: Rules {
+                controls,
+                max_moves: file.max_moves,
+                time_limit: file.time_limit.map(Duration::from_secs),
+            },
+            par: file.par,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{Control, Level};
+
+    #[test]
+    fn test_from_toml() {
+        let level = Level::from_toml(
+            r#"
+            name = "belt"
+            catalog = "assets/bsc5.csv"
+            nstars = 400
+            target = [2.0, 0.0, 0.0, 0.0]
+            controls = ["roll", "zoom"]
+            max_moves = 3
+            par = 2.5
+            "#,
+        )
+        .unwrap();
+        assert_eq!(level.name, "belt");
+        assert_eq!(level.target.unwrap().w, 1.0);
+        assert!(level.rules.allows(Control::Roll));
+        assert!(!level.rules.allows(Control::Yaw));
+        assert!(level.rules.allows_move(2, Duration::from_secs(1000)));
+        assert!(!level.rules.allows_move(3, Duration::ZERO));
+
+        let level = Level::from_toml("name = \"free\"\nnstars = 100\nseed = 4").unwrap();
+        assert_eq!(level.catalog, None);
+        assert_eq!(level.rules.controls, Control::ALL.to_vec());
+
+        assert!(Level::from_toml("name = \"x\"\nnstars = 0").is_err());
+        assert!(
+            Level::from_toml("name = \"x\"\nnstars = 9\ntarget = [0.0, 0.0, 0.0, 0.0]").is_err()
+        );
+        assert!(Level::from_toml("name = \"x\"\nnstars = 9\ncontrols = [\"zoom\"]").is_err());
+        assert!(Level::from_toml("name = \"x\"\nnstars = 9\nmax_moves = 0").is_err());
+        assert!(Level::from_toml("name = \"x\"\nnstars = 9\nlives = 3").is_err());
+    }
+}
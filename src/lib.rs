@@ -1,3 +1,41 @@
+pub mod assist;
+pub mod attitude;
+pub mod boundaries;
+pub mod cache;
+pub mod campaign;
+#[cfg(feature = "net")]
+pub mod catalog;
+pub mod cluster;
+pub mod command;
+pub mod config;
+pub mod convert;
+pub mod daily;
+pub mod deepsky;
+pub mod difficulty;
+pub mod ephemeris;
+pub mod extinction;
+pub mod format;
+pub mod frames;
+pub mod galactic;
+pub mod gamepad;
 pub mod gview;
+pub mod hint;
+pub mod index;
+pub mod level;
+pub mod lod;
+pub mod macros;
+pub mod names;
+pub mod observer;
+pub mod optics;
+pub mod precise;
+pub mod projection;
+pub mod quiz;
+pub mod render;
+pub mod scenario;
 pub mod sky;
+pub mod spin;
+pub mod store;
+pub mod timing;
+pub mod tutorial;
+pub mod variable;
 pub mod view;
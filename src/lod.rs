@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use crate::sky::{FoV, Sky};
+
+/// the fewest stars drawn, however slow the frames are
+const MIN_BUDGET: usize = 1000;
+
+/// How many stars of a large sky are worth drawing in each frame: the
+/// brightest ones that can be seen through the field of view, and no more
+/// than fit in the time a frame may take. As the view zooms in and fainter
+/// stars can be seen, they fill in.
+#[derive(Clone, Debug)]
+pub struct LevelOfDetail {
+    /// the most stars to draw in a frame
+    budget: usize,
+    /// how long drawing a frame may take
+    frame_time: Duration,
+}
+
+impl LevelOfDetail {
+    pub fn new(frame_time: Duration) -> Self {
+        Self {
+            budget: usize::MAX,
+            frame_time,
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// How many of the stars of `sky`, sorted from the brightest, to draw
+    /// through `fov`: those bright enough to be seen, while they fit the budget.
+    pub fn count(&self, sky: &Sky, fov: &FoV) -> usize {
+        let seen = sky
+            .iter()
+            .as_slice()
+            .partition_point(|star| fov.can_be_seen(&star.brightness()));
+        seen.min(self.budget)
+    }
+
+    /// Adapts the budget to a frame with `drawn` stars that took `elapsed`:
+    /// it shrinks below them when the frame was too slow and grows back when
+    /// it took less than half the time it may take.
+    pub fn frame_took(&mut self, elapsed: Duration, drawn: usize) {
+        if elapsed > self.frame_time {
+            self.budget = (drawn * 4 / 5).max(MIN_BUDGET);
+        } else if elapsed < self.frame_time / 2 && drawn >= self.budget {
+            self.budget = self.budget.saturating_add(self.budget / 4);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{LevelOfDetail, MIN_BUDGET};
+    use crate::sky::{FoV, Sky};
+
+    #[test]
+    fn test_level_of_detail() {
+        let sky = Sky::random_with_rng(20_000, &mut StdRng::seed_from_u64(7));
+        let sky = sky.brightest(sky.len());
+        let mut lod = LevelOfDetail::new(Duration::from_millis(10));
+        let wide = FoV::new(2.0, 2.0);
+        let narrow = FoV::new(0.2, 0.2);
+        let (few, many) = (lod.count(&sky, &wide), lod.count(&sky, &narrow));
+        // zooming in shows fainter stars
+        assert!(few < many);
+        assert!(sky
+            .iter()
+            .take(many)
+            .all(|s| narrow.can_be_seen(&s.brightness())));
+        assert!(!narrow.can_be_seen(&sky[many].brightness()));
+        let deep = narrow.with_limiting_magnitude(Some(30.0));
+        assert_eq!(lod.count(&sky, &deep), sky.len());
+
+        // too slow: fewer stars next time
+        lod.frame_took(Duration::from_millis(30), 2_000);
+        assert_eq!(lod.budget(), 1_600);
+        assert_eq!(lod.count(&sky, &deep), 1_600);
+        // fast enough, but not as many stars as the budget: it stays
+        lod.frame_took(Duration::from_millis(2), 100);
+        assert_eq!(lod.budget(), 1_600);
+        lod.frame_took(Duration::from_millis(2), 1_600);
+        assert_eq!(lod.budget(), 2_000);
+        lod.frame_took(Duration::from_millis(7), 2_000);
+        assert_eq!(lod.budget(), 2_000);
+        lod.frame_took(Duration::from_secs(1), 10);
+        assert_eq!(lod.budget(), MIN_BUDGET);
+    }
+}
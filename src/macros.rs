@@ -0,0 +1,147 @@
+use nalgebra::UnitQuaternion;
+
+/// A turn of the view, as the keys, a gamepad or a drag of the mouse or a
+/// finger make it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Turn {
+    /// pitch, yaw and roll, in radians
+    pub angles: [f32; 3],
+    /// about the axes of the camera itself instead of the screen
+    pub body_axes: bool,
+    /// the moves it counts for
+    pub moves: f32,
+}
+
+impl Turn {
+    /// A turn of `steps` of pitch, yaw and roll, each of `step` radians,
+    /// counting a move for each step of its largest axis.
+    pub fn of_steps(steps: [f32; 3], step: f32, body_axes: bool) -> Self {
+        Self {
+            angles: steps.map(|s| s * step),
+            body_axes,
+            moves: steps.iter().fold(0.0, |max, s| s.abs().max(max)),
+        }
+    }
+
+    /// The turn of the screen by `rotation`, as a drag makes it, counting
+    /// `moves`.
+    pub fn of_rotation(rotation: UnitQuaternion<f32>, moves: f32) -> Self {
+        let (x, y, z) = rotation.euler_angles();
+        Self {
+            angles: [x, y, z],
+            body_axes: false,
+            moves,
+        }
+    }
+
+    /// The same turn `scale` times as large, with as many times the moves.
+    pub fn scaled(self, scale: f32) -> Self {
+        Self {
+            angles: self.angles.map(|a| a * scale),
+            moves: self.moves * scale.abs(),
+            ..self
+        }
+    }
+
+    /// `q` turned: about the axes of the screen, as `delta * q`, or with
+    /// `body_axes` about those of the camera, as `q * delta`
+    pub fn apply(&self, q: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        let [x, y, z] = self.angles;
+        let delta = UnitQuaternion::from_euler_angles(x, y, z);
+        if self.body_axes {
+            q * delta
+        } else {
+            delta * q
+        }
+    }
+}
+
+/// A sequence of turns recorded, to be replayed later: to show how a sky
+/// was solved, or to drive a view the same way every time.
+#[derive(Clone, Debug, Default)]
+pub struct Macro {
+    turns: Vec<Turn>,
+}
+
+impl Macro {
+    pub fn push(&mut self, turn: Turn) {
+        self.turns.push(turn);
+    }
+
+    pub fn len(&self) -> usize {
+        self.turns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+
+    /// The turns to replay one by one, each `scale` times as large.
+    pub fn replay(&self, scale: f32) -> std::vec::IntoIter<Turn> {
+        let turns: Vec<Turn> = self.turns.iter().map(|t| t.scaled(scale)).collect();
+        turns.into_iter()
+    }
+
+    /// `q` turned by all the turns at once, each `scale` times as large
+    pub fn apply(&self, q: UnitQuaternion<f32>, scale: f32) -> UnitQuaternion<f32> {
+        self.replay(scale).fold(q, |q, turn| turn.apply(q))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::UnitQuaternion;
+
+    use super::{Macro, Turn};
+    use crate::attitude::geodesic_distance;
+
+    #[test]
+    fn test_turn() {
+        let turn = Turn::of_steps([1.0, -2.0, 0.5], 0.1, false);
+        assert_eq!(turn.angles, [0.1, -0.2, 0.05]);
+        assert_eq!(turn.moves, 2.0);
+        let half = turn.scaled(-0.5);
+        assert_eq!(half.angles, [-0.05, 0.1, -0.025]);
+        assert_eq!(half.moves, 1.0);
+
+        let q = UnitQuaternion::from_euler_angles(0.3, -0.2, 1.1);
+        let delta = UnitQuaternion::from_euler_angles(0.1, -0.2, 0.05);
+        assert_relative_eq!(geodesic_distance(turn.apply(q), delta * q), 0.0);
+        let body = Turn {
+            body_axes: true,
+            ..turn
+        };
+        assert_relative_eq!(geodesic_distance(body.apply(q), q * delta), 0.0);
+
+        let dragged = Turn::of_rotation(delta, 1.0);
+        assert_relative_eq!(geodesic_distance(dragged.apply(q), delta * q), 0.0);
+        assert_eq!(dragged.moves, 1.0);
+    }
+
+    #[test]
+    fn test_macro() {
+        let mut recorded = Macro::default();
+        assert!(recorded.is_empty());
+        let turns = [
+            Turn::of_steps([1.0, 0.0, 0.0], 0.2, false),
+            Turn::of_steps([0.0, 0.0, -1.0], 0.2, true),
+            Turn::of_steps([0.0, 0.3, 0.0], 0.2, false),
+        ];
+        for turn in turns {
+            recorded.push(turn);
+        }
+        assert_eq!(recorded.len(), 3);
+
+        let q = UnitQuaternion::from_euler_angles(0.5, 0.1, -0.4);
+        let by_hand = turns.iter().fold(q, |q, turn| turn.apply(q));
+        assert_relative_eq!(geodesic_distance(recorded.apply(q, 1.0), by_hand), 0.0);
+        let replayed: Vec<Turn> = recorded.replay(2.0).collect();
+        assert_eq!(replayed[0].angles, [0.4, 0.0, 0.0]);
+        assert_relative_eq!(replayed.iter().map(|t| t.moves).sum::<f32>(), 4.6);
+        // a tenth as large, it turns about a tenth as far
+        let tenth = geodesic_distance(recorded.apply(q, 0.1), q);
+        let whole = geodesic_distance(recorded.apply(q, 1.0), q);
+        assert!(tenth > 0.08 * whole && tenth < 0.12 * whole);
+    }
+}
@@ -1,26 +1,231 @@
 use std::{cell::RefCell, env, rc::Rc};
 
 use cuyat::{
+    campaign,
+    config::Config,
+    convert::Conversion,
+    daily,
+    difficulty::Difficulty,
+    ephemeris::parse_date,
     gview::{self},
-    view::{Scoring, SkyView},
+    observer::Observer,
+    render::Rendering,
+    scenario,
+    sky::Sky,
+    view::{cursive_window, Scoring, SkyView},
 };
 use macroquad::prelude::*;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let seed = match take_seed(&mut args) {
+        Ok(seed) => seed,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let difficulty = match take_difficulty(&mut args) {
+        Ok(difficulty) => difficulty,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let practice = take_flag(&mut args, "--practice");
+    let (observer, date) = match (
+        take_value(&mut args, "--at", str::parse::<Observer>),
+        take_value(&mut args, "--date", parse_date),
+    ) {
+        (Ok(observer), Ok(date)) => (observer, date),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
 
     let scoring = Rc::new(RefCell::new(Scoring::default()));
+    let profile = args.get(2).map_or("default", |p| p.as_str());
     match args[1].as_str() {
         "cli" => {
-            let sky_view = SkyView::new(
+            let Some(mut config) = load_config(profile) else {
+                return;
+            };
+            let mut sky_view = SkyView::new(
                 Some(String::from("assets/bsc5.csv")),
                 400,
                 Rc::clone(&scoring),
-            );
-            cursive_window(sky_view);
+            )
+            .with_invert(&config.invert);
+            if let Some(cli) = &config.cli {
+                sky_view = sky_view.with_config(cli);
+            }
+            if let Some(difficulty) = difficulty {
+                sky_view = sky_view.with_difficulty(difficulty);
+            }
+            if let Some(seed) = seed {
+                sky_view = sky_view.with_seed(seed);
+            }
+            if practice {
+                sky_view = sky_view.with_practice();
+            }
+            config.cli = cursive_window(sky_view);
+            save_config(&config, profile);
         }
         "gui" => {
-            gview::launch(Rc::clone(&scoring));
+            let Some(config) = load_config(profile) else {
+                return;
+            };
+            let config = gview::launch(
+                Rc::clone(&scoring),
+                config,
+                seed,
+                difficulty,
+                false,
+                practice,
+            );
+            save_config(&config, profile);
+        }
+        "tutorial" if args.get(2).is_some_and(|a| a == "gui") => {
+            let Some(config) = load_config("default") else {
+                return;
+            };
+            gview::launch(Rc::clone(&scoring), config, None, None, true, false);
+        }
+        "tutorial" => {
+            let Some(config) = load_config("default") else {
+                return;
+            };
+            cursive_window(
+                SkyView::new(
+                    Some(String::from("assets/bsc5.csv")),
+                    400,
+                    Rc::clone(&scoring),
+                )
+                .with_invert(&config.invert)
+                .with_tutorial(),
+            );
+        }
+        "zen" => {
+            let Some(config) = load_config(profile) else {
+                return;
+            };
+            cursive_window(
+                SkyView::new(
+                    Some(String::from("assets/bsc5.csv")),
+                    3000,
+                    Rc::clone(&scoring),
+                )
+                .with_invert(&config.invert)
+                .with_zen(observer, date),
+            );
+            return;
+        }
+        "cluster" => {
+            let Some(config) = load_config(profile) else {
+                return;
+            };
+            cursive_window(
+                SkyView::new(None, 300, Rc::clone(&scoring))
+                    .with_invert(&config.invert)
+                    .with_cluster(),
+            );
+        }
+        "6dof" => {
+            let Some(config) = load_config(profile) else {
+                return;
+            };
+            cursive_window(
+                SkyView::new(None, 300, Rc::clone(&scoring))
+                    .with_invert(&config.invert)
+                    .with_six_dof(),
+            );
+        }
+        "quiz" => {
+            let Some(config) = load_config(profile) else {
+                return;
+            };
+            cursive_window(
+                SkyView::new(
+                    Some(String::from("assets/bsc5.csv")),
+                    300,
+                    Rc::clone(&scoring),
+                )
+                .with_invert(&config.invert)
+                .with_quiz(),
+            );
+            return;
+        }
+        #[cfg(feature = "net")]
+        "fetch" => {
+            if let Err(e) = fetch(args.get(2)) {
+                eprintln!("{e}");
+                return;
+            }
+        }
+        "convert" => {
+            match Conversion::from_args(&args[2..]).map(|c| (c.run(), c.output)) {
+                Ok((Ok(n), output)) => println!("{n} stars written to {output}"),
+                Ok((Err(e), _)) => eprintln!("{e}"),
+                Err(e) => eprintln!("{e}"),
+            }
+            return;
+        }
+        "render" => {
+            match Rendering::from_args(&args[2..]).map(|r| (r.run(), r.output)) {
+                Ok((Ok(n), output)) => println!("{n} stars drawn in {output}"),
+                Ok((Err(e), _)) => eprintln!("{e}"),
+                Err(e) => eprintln!("{e}"),
+            }
+            return;
+        }
+        "validate" => {
+            let Some(fname) = args.get(2) else {
+                eprintln!("usage: validate <catalog>");
+                return;
+            };
+            let report =
+                Sky::catalog_format(fname).and_then(|format| Sky::validate_catalog(fname, &format));
+            match report {
+                Ok(report) => print!("{report}"),
+                Err(e) => eprintln!("{e}"),
+            }
+            return;
+        }
+        "campaign" => {
+            let stage = match args.get(2).map(|stage| stage.parse()).transpose() {
+                Ok(stage) => stage,
+                Err(_) => {
+                    eprintln!("the stage must be a number");
+                    return;
+                }
+            };
+            if let Err(e) = campaign::run(stage, Rc::clone(&scoring)) {
+                eprintln!("{e}");
+                return;
+            }
+        }
+        "daily" if args.get(2).is_some_and(|arg| arg == "export") => {
+            if let Err(e) = daily::export(args.get(3).map(|path| path.as_str())) {
+                eprintln!("{e}");
+            }
+            return;
+        }
+        "daily" => {
+            if let Err(e) = daily::run(date, Rc::clone(&scoring)) {
+                eprintln!("{e}");
+                return;
+            }
+        }
+        "batch" => {
+            let Some(fname) = args.get(2) else {
+                eprintln!("usage: batch <file>");
+                return;
+            };
+            if let Err(e) = scenario::run_batch(fname, Rc::clone(&scoring)) {
+                eprintln!("{e}");
+                return;
+            }
         }
         _ => {}
     };
@@ -45,9 +250,86 @@ fn main() {
     );
 }
 
-fn cursive_window(sky_view: SkyView) {
-    let mut siv = cursive::default();
-    siv.add_layer(sky_view);
-    siv.add_global_callback('q', |s| s.quit());
-    siv.run();
+/// Downloads the catalog at `url` and prints where it is. Without an url,
+/// the Yale Bright Star Catalog is downloaded and converted to the
+/// `assets/bsc5.csv` the game reads, and the constellation boundaries are
+/// copied to `assets/constbnd.dat`, if they are not there yet.
+#[cfg(feature = "net")]
+fn fetch(url: Option<&String>) -> Result<(), Box<dyn std::error::Error>> {
+    use cuyat::{boundaries::Boundaries, catalog};
+
+    let downloaded = catalog::fetch(url.map_or(catalog::BSC_URL, |u| u.as_str()))?;
+    println!("{}", downloaded.display());
+    let bsc = std::path::Path::new("assets/bsc5.csv");
+    if url.is_none() && !bsc.exists() {
+        std::fs::create_dir_all("assets")?;
+        Sky::convert_catalog_file(&downloaded.to_string_lossy(), "assets/bsc5.csv", 5.5)?;
+        println!("{}", bsc.display());
+    }
+    let boundaries = std::path::Path::new(Boundaries::DEFAULT_FILE);
+    if url.is_none() && !boundaries.exists() {
+        std::fs::create_dir_all("assets")?;
+        std::fs::copy(catalog::fetch(catalog::BOUNDARIES_URL)?, boundaries)?;
+        println!("{}", boundaries.display());
+    }
+    Ok(())
+}
+
+/// Removes `--seed <n>` from `args`, wherever it is, and returns the seed.
+fn take_seed(args: &mut Vec<String>) -> Result<Option<u64>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--seed") else {
+        return Ok(None);
+    };
+    let seed = args
+        .get(i + 1)
+        .and_then(|seed| seed.parse().ok())
+        .ok_or("--seed needs a number")?;
+    args.drain(i..i + 2);
+    Ok(Some(seed))
+}
+
+/// Removes `--difficulty <easy|medium|hard>`, wherever it is, and returns it.
+fn take_difficulty(args: &mut Vec<String>) -> Result<Option<Difficulty>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--difficulty") else {
+        return Ok(None);
+    };
+    let difficulty = args
+        .get(i + 1)
+        .ok_or("--difficulty needs easy, medium or hard")?
+        .parse()?;
+    args.drain(i..i + 2);
+    Ok(Some(difficulty))
+}
+
+/// Removes `flag` and the value after it, wherever they are, and returns
+/// the value as `parse` reads it.
+fn take_value<T>(
+    args: &mut Vec<String>,
+    flag: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    let Some(i) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    let value = parse(args.get(i + 1).ok_or(format!("{flag} needs a value"))?)?;
+    args.drain(i..i + 2);
+    Ok(Some(value))
+}
+
+/// Removes `flag`, wherever it is, and tells whether it was there.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() < before
+}
+
+/// the options saved for `profile`, or none, after telling why, if they cannot be read
+fn load_config(profile: &str) -> Option<Config> {
+    Config::load(profile).map_err(|e| eprintln!("{e}")).ok()
+}
+
+fn save_config(config: &Config, profile: &str) {
+    if let Err(e) = config.save(profile) {
+        eprintln!("could not save the options: {e}");
+    }
 }
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+/// How stars are labeled: by their Bayer or Flamsteed designation (`α Ori`),
+/// by their proper name (`Betelgeuse`) or by their HR number (`HR 2061`).
+/// Stars without a proper name or HR number keep their designation.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum NameStyle {
+    #[default]
+    Bayer,
+    Proper,
+    Hr,
+}
+
+impl NameStyle {
+    /// the next style, to cycle through them
+    pub fn next(self) -> Self {
+        match self {
+            NameStyle::Bayer => NameStyle::Proper,
+            NameStyle::Proper => NameStyle::Hr,
+            NameStyle::Hr => NameStyle::Bayer,
+        }
+    }
+}
+
+/// HR number, designation as in the converted catalog and IAU proper name of
+/// the brightest and best known stars
+const PROPER_NAMES: [(u32, &str, &str); 66] = [
+    (15, "α And", "Alpheratz"),
+    (21, "β Cas", "Caph"),
+    (99, "α Phe", "Ankaa"),
+    (168, "α Cas", "Schedar"),
+    (188, "β Cet", "Diphda"),
+    (337, "β And", "Mirach"),
+    (424, "α UMi", "Polaris"),
+    (472, "α Eri", "Achernar"),
+    (603, "γ1And", "Almach"),
+    (617, "α Ari", "Hamal"),
+    (911, "α Cet", "Menkar"),
+    (936, "β Per", "Algol"),
+    (1017, "α Per", "Mirfak"),
+    (1457, "α Tau", "Aldebaran"),
+    (1708, "α Aur", "Capella"),
+    (1713, "β Ori", "Rigel"),
+    (1790, "γ Ori", "Bellatrix"),
+    (1791, "β Tau", "Elnath"),
+    (1852, "δ Ori", "Mintaka"),
+    (1903, "ε Ori", "Alnilam"),
+    (1948, "ζ Ori", "Alnitak"),
+    (2004, "κ Ori", "Saiph"),
+    (2061, "α Ori", "Betelgeuse"),
+    (2088, "β Aur", "Menkalinan"),
+    (2294, "β CMa", "Mirzam"),
+    (2326, "α Car", "Canopus"),
+    (2421, "γ Gem", "Alhena"),
+    (2491, "α CMa", "Sirius"),
+    (2618, "ε CMa", "Adhara"),
+    (2693, "δ CMa", "Wezen"),
+    (2891, "α Gem", "Castor"),
+    (2943, "α CMi", "Procyon"),
+    (2990, "β Gem", "Pollux"),
+    (3307, "ε Car", "Avior"),
+    (3685, "β Car", "Miaplacidus"),
+    (3748, "α Hya", "Alphard"),
+    (3982, "α Leo", "Regulus"),
+    (4057, "γ1Leo", "Algieba"),
+    (4295, "β UMa", "Merak"),
+    (4301, "α UMa", "Dubhe"),
+    (4534, "β Leo", "Denebola"),
+    (4730, "α1Cru", "Acrux"),
+    (4763, "γ Cru", "Gacrux"),
+    (4853, "β Cru", "Mimosa"),
+    (4905, "ε UMa", "Alioth"),
+    (5054, "ζ UMa", "Mizar"),
+    (5056, "α Vir", "Spica"),
+    (5191, "η UMa", "Alkaid"),
+    (5267, "β Cen", "Hadar"),
+    (5340, "α Boo", "Arcturus"),
+    (5459, "α1Cen", "Rigil Kentaurus"),
+    (5531, "α2Lib", "Zubenelgenubi"),
+    (5563, "β UMi", "Kochab"),
+    (5854, "α Ser", "Unukalhai"),
+    (6134, "α Sco", "Antares"),
+    (6217, "α TrA", "Atria"),
+    (6527, "λ Sco", "Shaula"),
+    (6556, "α Oph", "Rasalhague"),
+    (6705, "γ Dra", "Eltanin"),
+    (7001, "α Lyr", "Vega"),
+    (7121, "σ Sgr", "Nunki"),
+    (7557, "α Aql", "Altair"),
+    (7790, "α Pav", "Peacock"),
+    (7924, "α Cyg", "Deneb"),
+    (8728, "α PsA", "Fomalhaut"),
+    (8781, "α Peg", "Markab"),
+];
+
+/// the proper name of the star with HR number `hr`, or else with designation `name`
+pub fn proper_name(hr: Option<u32>, name: &str) -> Option<&'static str> {
+    PROPER_NAMES
+        .iter()
+        .find(|(h, designation, _)| match hr {
+            Some(hr) => *h == hr,
+            None => *designation == name,
+        })
+        .map(|(_, _, proper)| *proper)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{proper_name, NameStyle};
+
+    #[test]
+    fn test_proper_name() {
+        assert_eq!(proper_name(Some(2061), "58 Ori"), Some("Betelgeuse"));
+        assert_eq!(proper_name(None, "α Ori"), Some("Betelgeuse"));
+        assert_eq!(proper_name(None, "α1Cen"), Some("Rigil Kentaurus"));
+        assert_eq!(proper_name(Some(2062), "α Ori"), None);
+        assert_eq!(proper_name(None, "  Psc"), None);
+
+        assert_eq!(NameStyle::default().next().next().next(), NameStyle::Bayer);
+    }
+}
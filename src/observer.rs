@@ -0,0 +1,220 @@
+use std::str::FromStr;
+
+use nalgebra::{Matrix3, Rotation3};
+use serde::{Deserialize, Serialize};
+
+use crate::sky::{Sky, Star};
+
+/// mean radius of the Earth, in meters
+const EARTH_RADIUS: f32 = 6_371_000.0;
+
+/// Where on the Earth the sky is seen from: latitude and longitude (east
+/// positive) in degrees and elevation above the sea in meters.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Observer {
+    pub lat: f32,
+    pub lon: f32,
+    pub elevation: f32,
+}
+
+impl Default for Observer {
+    /// Buenos Aires
+    fn default() -> Self {
+        Self {
+            lat: -34.6,
+            lon: -58.4,
+            elevation: 25.0,
+        }
+    }
+}
+
+/// how fast the Earth turns, in radians per second
+pub const SIDEREAL_RATE: f32 = std::f32::consts::TAU / 86_164.09;
+
+/// the Greenwich mean sidereal time at julian date `jd`, in degrees
+pub fn sidereal_time(jd: f64) -> f64 {
+    (280.460_618_37 + 360.985_647_366_29 * (jd - 2_451_545.0)).rem_euclid(360.0)
+}
+
+/// How much higher than it is, in degrees, the atmosphere makes an object at
+/// altitude `alt` (degrees) look, at 10 °C and 1010 hPa, as in Sæmundsson
+/// (1986). Objects well below the horizon are not moved.
+pub fn refraction(alt: f32) -> f32 {
+    if alt < -2.0 {
+        return 0.0;
+    }
+    1.02 / (alt + 10.3 / (alt + 5.11)).to_radians().tan() / 60.0
+}
+
+impl FromStr for Observer {
+    type Err = String;
+
+    /// Reads `lat,lon` in degrees, with the elevation in meters after them or
+    /// none, as `-34.6,-58.4`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers: Option<Vec<f32>> = s.split(',').map(|n| n.trim().parse().ok()).collect();
+        let (lat, lon, elevation) = match numbers.as_deref() {
+            Some(&[lat, lon]) => (lat, lon, 0.0),
+            Some(&[lat, lon, elevation]) => (lat, lon, elevation),
+            _ => (f32::NAN, f32::NAN, 0.0),
+        };
+        if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon) {
+            Ok(Self::new(lat, lon, elevation))
+        } else {
+            Err(format!(
+                "no place '{s}': write latitude,longitude[,elevation], as -34.6,-58.4"
+            ))
+        }
+    }
+}
+
+impl Observer {
+    pub fn new(lat: f32, lon: f32, elevation: f32) -> Self {
+        Self {
+            lat,
+            lon,
+            elevation,
+        }
+    }
+
+    /// the local sidereal time at julian date `jd`, in degrees
+    pub fn local_sidereal_time(&self, jd: f64) -> f32 {
+        (sidereal_time(jd) + self.lon as f64).rem_euclid(360.0) as f32
+    }
+
+    /// The rotation from equatorial coordinates to horizontal ones at julian
+    /// date `jd`: x points to the north, y to the west and z to the zenith.
+    /// Precession and nutation are ignored.
+    pub fn horizontal(&self, jd: f64) -> Rotation3<f32> {
+        let (sin_lat, cos_lat) = self.lat.to_radians().sin_cos();
+        let to_meridian =
+            Rotation3::from_axis_angle(&Star::z_axis(), -self.local_sidereal_time(jd).to_radians());
+        // in the frame of `to_meridian`: x on the meridian, y to the east, z to the pole
+        let north = Star::new(-sin_lat, 0.0, cos_lat);
+        let west = Star::new(0.0, -1.0, 0.0);
+        let zenith = Star::new(cos_lat, 0.0, sin_lat);
+        let rows = Matrix3::from_rows(&[north.transpose(), west.transpose(), zenith.transpose()]);
+        Rotation3::from_matrix_unchecked(rows) * to_meridian
+    }
+
+    /// the direction of the zenith at julian date `jd`, in equatorial coordinates
+    pub fn zenith(&self, jd: f64) -> Star {
+        self.horizontal(jd).inverse() * Star::z_axis().into_inner()
+    }
+
+    /// altitude and azimuth (from the north through the east) of `star` at
+    /// julian date `jd`, in degrees
+    pub fn alt_az(&self, star: &Star, jd: f64) -> (f32, f32) {
+        let h = self.horizontal(jd) * star.normalize();
+        let alt = h[2].clamp(-1.0, 1.0).asin().to_degrees();
+        let az = (-h[1]).atan2(h[0]).to_degrees().rem_euclid(360.0);
+        (alt, az)
+    }
+
+    /// where `star` looks at julian date `jd`, raised by the refraction of the atmosphere
+    pub fn refracted(&self, star: &Star, jd: f64) -> Star {
+        let horizontal = self.horizontal(jd);
+        let h = horizontal * star.normalize();
+        let alt = h[2].clamp(-1.0, 1.0).asin();
+        let Some(ground) = Star::new(h[0], h[1], 0.0).try_normalize(1e-6) else {
+            // at the zenith or the nadir
+            return *star;
+        };
+        let (sin_alt, cos_alt) = (alt + refraction(alt.to_degrees()).to_radians()).sin_cos();
+        horizontal.inverse() * (ground * cos_alt + Star::z_axis().into_inner() * sin_alt)
+    }
+
+    /// how far below the horizon, in radians, can be seen from the elevation of the observer
+    pub fn horizon_dip(&self) -> f32 {
+        (EARTH_RADIUS / (EARTH_RADIUS + self.elevation.max(0.0))).acos()
+    }
+
+    /// whether `star` is above the horizon at julian date `jd`
+    pub fn sees(&self, star: &Star, jd: f64) -> bool {
+        (self.horizontal(jd) * star.normalize())[2] > -self.horizon_dip().sin()
+    }
+}
+
+impl Sky {
+    /// the sky in the horizontal coordinates of `observer` at julian date `jd`
+    pub fn to_horizontal(&self, observer: &Observer, jd: f64) -> Self {
+        self.with_attitude(observer.horizontal(jd).into())
+    }
+
+    /// the sky as `observer` sees it at julian date `jd`, raised by refraction
+    pub fn refracted(&self, observer: &Observer, jd: f64) -> Self {
+        self.iter()
+            .map(|star| star.moved_to(observer.refracted(star.position(), jd)))
+            .collect()
+    }
+
+    /// the stars `observer` sees above the horizon at julian date `jd`
+    pub fn above_horizon(&self, observer: &Observer, jd: f64) -> Self {
+        self.iter()
+            .filter(|star| observer.sees(star.position(), jd))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{refraction, sidereal_time, Observer};
+    use crate::sky::{angle_between, direction_at, Brightness, CatalogStar, Sky};
+
+    #[test]
+    fn test_observer() {
+        // 1987 April 10, 0h UT: 13h 10m 46.37s, as in Meeus's example 12.a
+        assert_relative_eq!(sidereal_time(2_446_895.5), 197.693_2, epsilon = 1e-3);
+
+        let jd = 2_451_545.0;
+        let greenwich = Observer::new(51.48, 0.0, 0.0);
+        let lst = greenwich.local_sidereal_time(jd);
+        // a star on the meridian at the latitude is at the zenith
+        let zenith = direction_at(lst, 51.48);
+        assert!(angle_between(&greenwich.zenith(jd), &zenith).to_degrees() < 1e-3);
+        let (alt, _) = greenwich.alt_az(&zenith, jd);
+        assert_relative_eq!(alt, 90.0, epsilon = 1e-2);
+        // the pole is as high as the latitude, to the north
+        let (alt, az) = greenwich.alt_az(&direction_at(0.0, 90.0), jd);
+        assert_relative_eq!(alt, 51.48, epsilon = 1e-3);
+        assert!(!(0.01..359.99).contains(&az));
+        // rising in the east
+        let (alt, az) = greenwich.alt_az(&direction_at(lst + 90.0, 0.0), jd);
+        assert_relative_eq!(alt, 0.0, epsilon = 1e-3);
+        assert_relative_eq!(az, 90.0, epsilon = 1e-3);
+
+        let sky = Sky::from(&[
+            CatalogStar::new(zenith, Brightness::new(0.5), "up"),
+            CatalogStar::new(-zenith, Brightness::new(0.5), "down"),
+        ]);
+        let seen = sky.above_horizon(&greenwich, jd);
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].name(), "up");
+        let horizontal = sky.to_horizontal(&greenwich, jd);
+        assert_relative_eq!(horizontal[0].position()[2], 1.0, epsilon = 1e-5);
+
+        // about half a degree at the horizon, 1' at 45° and nothing at the zenith
+        assert_relative_eq!(refraction(0.0) * 60.0, 29.0, epsilon = 1.0);
+        assert_relative_eq!(refraction(45.0) * 60.0, 1.0, epsilon = 0.1);
+        assert_relative_eq!(refraction(90.0), 0.0, epsilon = 1e-4);
+        assert_eq!(refraction(-10.0), 0.0);
+        let low = direction_at(lst + 90.0, 0.0);
+        let (alt, az) = greenwich.alt_az(&greenwich.refracted(&low, jd), jd);
+        assert_relative_eq!(alt, refraction(0.0), epsilon = 1e-3);
+        assert_relative_eq!(az, 90.0, epsilon = 1e-3);
+        let seen = sky.refracted(&greenwich, jd);
+        assert!(angle_between(seen[0].position(), &zenith).to_degrees() < 1e-3);
+
+        let everest = Observer::new(28.0, 86.9, 8848.0);
+        assert_relative_eq!(everest.horizon_dip().to_degrees(), 3.0, epsilon = 0.1);
+        assert!(Observer::default().horizon_dip() > 0.0);
+
+        assert_eq!("28.0, 86.9,8848".parse(), Ok(everest));
+        assert_eq!("51.48,0".parse(), Ok(Observer::new(51.48, 0.0, 0.0)));
+        assert!("95,0".parse::<Observer>().is_err());
+        assert!("here".parse::<Observer>().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sky::FoV;
+
+/// Instruments to look through, each with its field of view and the
+/// faintest stars it shows.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Optics {
+    #[default]
+    NakedEye,
+    /// 7x50 binoculars
+    Binoculars,
+    /// a 50 mm lens on a full frame camera
+    Dslr50mm,
+    StarTracker,
+}
+
+impl Optics {
+    pub fn next(self) -> Self {
+        match self {
+            Optics::NakedEye => Optics::Binoculars,
+            Optics::Binoculars => Optics::Dslr50mm,
+            Optics::Dslr50mm => Optics::StarTracker,
+            Optics::StarTracker => Optics::NakedEye,
+        }
+    }
+
+    /// the angle across the shorter side of the view, in degrees
+    pub fn field(self) -> f32 {
+        match self {
+            Optics::NakedEye => 60.0,
+            Optics::Binoculars => 7.0,
+            // 24 mm of the sensor over 50 mm
+            Optics::Dslr50mm => 27.0,
+            Optics::StarTracker => 20.0,
+        }
+    }
+
+    /// the magnitude of the faintest stars seen through it
+    pub fn limiting_magnitude(self) -> f32 {
+        match self {
+            Optics::NakedEye => 6.0,
+            Optics::Binoculars => 9.5,
+            Optics::Dslr50mm => 8.0,
+            Optics::StarTracker => 6.5,
+        }
+    }
+
+    /// its field of view, to be fitted to the shape of the window
+    pub fn fov(self) -> FoV {
+        let half_fov = (self.field().to_radians() / 2.0).tan();
+        FoV::new(half_fov, half_fov)
+    }
+}
+
+impl fmt::Display for Optics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Optics::NakedEye => "naked eye",
+            Optics::Binoculars => "binoculars",
+            Optics::Dslr50mm => "50 mm lens",
+            Optics::StarTracker => "star tracker",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::Optics;
+
+    #[test]
+    fn test_optics() {
+        let mut optics = Optics::default();
+        let mut seen = vec![];
+        for _ in 0..4 {
+            seen.push(optics);
+            optics = optics.next();
+        }
+        assert_eq!(optics, Optics::NakedEye);
+        assert_eq!(seen.len(), 4);
+        assert!(!seen[1..].contains(&Optics::NakedEye));
+
+        let fov = Optics::Binoculars.fov();
+        assert_relative_eq!(fov.half_fov().0, 3.5f32.to_radians().tan());
+        assert_eq!(fov.half_fov().0, fov.half_fov().1);
+        assert!(Optics::Binoculars.limiting_magnitude() > Optics::NakedEye.limiting_magnitude());
+        assert_eq!(Optics::Dslr50mm.to_string(), "50 mm lens");
+    }
+}
@@ -0,0 +1,170 @@
+use nalgebra::{UnitQuaternion, Vector2, Vector3};
+
+use crate::sky::{CatalogStar, FoV, Sky};
+
+/// a direction in the sky in double precision, as `Star` is in single
+pub type Direction = Vector3<f64>;
+/// a point of the focal plane in double precision, as `Fpp` is in single
+pub type FocalPoint = Vector2<f64>;
+/// an attitude in double precision
+pub type Attitude = UnitQuaternion<f64>;
+
+/// the direction at right ascension `ra` and declination `dec`, in degrees
+pub fn direction_at(ra: f64, dec: f64) -> Direction {
+    let (ra, dec) = (ra.to_radians(), dec.to_radians());
+    Direction::new(ra.cos() * dec.cos(), ra.sin() * dec.cos(), dec.sin())
+}
+
+/// right ascension and declination of `direction`, in degrees
+pub fn ra_dec(direction: &Direction) -> (f64, f64) {
+    let ra = direction[1]
+        .atan2(direction[0])
+        .to_degrees()
+        .rem_euclid(360.0);
+    let dec = (direction[2] / direction.norm()).asin().to_degrees();
+    (ra, dec)
+}
+
+/// angle, in radians, between two directions, accurate also when they are very close
+pub fn angle_between(a: &Direction, b: &Direction) -> f64 {
+    let (a, b) = (a.normalize(), b.normalize());
+    a.cross(&b).norm().atan2(a.dot(&b))
+}
+
+/// A star in double precision, for simulations of star trackers where the
+/// arcsecond or less matters. Stars loaded in a `Sky` keep only what `f32`
+/// holds, so precise positions are better made with `direction_at`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreciseStar {
+    pub position: Direction,
+    pub magnitude: f64,
+    pub name: String,
+}
+
+impl PreciseStar {
+    pub fn new<S: Into<String>>(position: Direction, magnitude: f64, name: S) -> Self {
+        Self {
+            position: position.normalize(),
+            magnitude,
+            name: name.into(),
+        }
+    }
+}
+
+impl From<&CatalogStar> for PreciseStar {
+    fn from(star: &CatalogStar) -> Self {
+        Self::new(star.position().cast(), star.magnitude() as f64, star.name())
+    }
+}
+
+impl Sky {
+    /// the stars in double precision
+    pub fn to_precise(&self) -> Vec<PreciseStar> {
+        self.iter().map(PreciseStar::from).collect()
+    }
+}
+
+/// The projection of `FoV` in double precision: half the width and height of
+/// the focal plane, over the focal length.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreciseFoV {
+    pub half_fov_x: f64,
+    pub half_fov_y: f64,
+}
+
+impl PreciseFoV {
+    pub fn new(half_fov_x: f64, half_fov_y: f64) -> Self {
+        Self {
+            half_fov_x,
+            half_fov_y,
+        }
+    }
+
+    /// Where `direction`, in the frame of the camera, falls on the focal
+    /// plane: from -1 to 1 across the field of view. Directions behind the
+    /// camera have no place on it.
+    pub fn project(&self, direction: &Direction) -> Option<FocalPoint> {
+        (direction[2] > 0.0).then(|| {
+            FocalPoint::new(
+                direction[0] / direction[2] / self.half_fov_x,
+                direction[1] / direction[2] / self.half_fov_y,
+            )
+        })
+    }
+
+    /// the stars seen with attitude `attitude`, each with where it falls
+    pub fn project_stars<'a>(
+        &self,
+        stars: &'a [PreciseStar],
+        attitude: &Attitude,
+    ) -> Vec<(FocalPoint, &'a PreciseStar)> {
+        stars
+            .iter()
+            .filter_map(|star| {
+                let point = self.project(&(attitude * star.position))?;
+                (point.abs().max() <= 1.0).then_some((point, star))
+            })
+            .collect()
+    }
+}
+
+impl From<&FoV> for PreciseFoV {
+    fn from(fov: &FoV) -> Self {
+        let (x, y) = fov.half_fov();
+        Self::new(x as f64, y as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use nalgebra::Vector3;
+
+    use super::{angle_between, direction_at, ra_dec, Attitude, PreciseFoV, PreciseStar};
+    use crate::{
+        attitude::geodesic_distance,
+        sky::{Brightness, CatalogStar, FoV, Sky, Star},
+    };
+
+    #[test]
+    fn test_precise() {
+        // a milliarcsecond apart, which f32 cannot tell
+        let mas = (1.0 / 3_600_000.0f64).to_radians();
+        let a = direction_at(83.0, -5.0);
+        let b = direction_at(83.0, -5.0 + 1.0 / 3_600_000.0);
+        assert_relative_eq!(angle_between(&a, &b), mas, max_relative = 1e-6);
+        let (ra, dec) = ra_dec(&b);
+        assert_relative_eq!(ra, 83.0, epsilon = 1e-9);
+        assert_relative_eq!(dec, -5.0 + 1.0 / 3_600_000.0, epsilon = 1e-10);
+
+        let q = Attitude::from_axis_angle(&Vector3::x_axis(), 1e-9);
+        assert_relative_eq!(
+            geodesic_distance(q, Attitude::identity()),
+            1e-9,
+            max_relative = 1e-6
+        );
+
+        let fov = PreciseFoV::from(&FoV::new(0.5, 0.25));
+        assert_eq!(fov, PreciseFoV::new(0.5, 0.25));
+        let stars = [
+            PreciseStar::new(direction_at(0.0, 90.0), 1.0, "center"),
+            PreciseStar::new(direction_at(0.0, 60.0), 2.0, "side"),
+            PreciseStar::new(direction_at(0.0, -90.0), 3.0, "behind"),
+        ];
+        let seen = fov.project_stars(&stars, &Attitude::identity());
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].1.name, "center");
+        assert_relative_eq!(seen[0].0.norm(), 0.0, epsilon = 1e-12);
+        assert!(fov.project(&stars[2].position).is_none());
+
+        let sky = Sky::from(&[CatalogStar::new(
+            Star::new(0.0, 0.0, 2.0),
+            Brightness::from_magnitude(1.0),
+            "a",
+        )]);
+        let precise = sky.to_precise();
+        assert_eq!(precise[0].name, "a");
+        assert_relative_eq!(precise[0].position[2], 1.0);
+        assert_relative_eq!(precise[0].magnitude, 1.0, epsilon = 1e-5);
+    }
+}
@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use crate::sky::{Fpp, Lens, Star};
+
+/// How directions in the frame of the camera, with z along the boresight,
+/// are laid on the focal plane. Points of the focal plane are measured in
+/// focal lengths.
+pub trait Projection {
+    /// where `direction` falls on the focal plane, if it is `visible`
+    fn project(&self, direction: &Star) -> Fpp;
+    /// the unit direction that falls at `point`
+    fn unproject(&self, point: &Fpp) -> Star;
+    /// whether `direction` has a place on the focal plane
+    fn visible(&self, direction: &Star) -> bool;
+}
+
+/// A pinhole camera: straight lines stay straight, but only what is ahead
+/// can be seen, and the edges stretch as the field widens.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Gnomonic;
+
+/// Keeps small shapes, so constellations look right even far from the
+/// center. It takes in all but the direction right behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stereographic;
+
+/// The sphere as seen from far away: one hemisphere, squeezed at its rim.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Orthographic;
+
+/// The fisheye: the distance from the center is the angle from the
+/// boresight. It takes in all but the direction right behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Equidistant;
+
+/// whether `direction` is not exactly behind the camera
+fn not_behind(direction: &Star) -> bool {
+    direction[2] > 0.0 || direction[0] != 0.0 || direction[1] != 0.0
+}
+
+impl Projection for Gnomonic {
+    fn project(&self, direction: &Star) -> Fpp {
+        direction.xy() / direction[2]
+    }
+
+    fn unproject(&self, point: &Fpp) -> Star {
+        Star::new(point[0], point[1], 1.0).normalize()
+    }
+
+    fn visible(&self, direction: &Star) -> bool {
+        direction[2] > 0.0
+    }
+}
+
+impl Projection for Stereographic {
+    fn project(&self, direction: &Star) -> Fpp {
+        let direction = direction.normalize();
+        direction.xy() * 2.0 / (1.0 + direction[2])
+    }
+
+    fn unproject(&self, point: &Fpp) -> Star {
+        let r2 = point.norm_squared();
+        Star::new(4.0 * point[0], 4.0 * point[1], 4.0 - r2) / (4.0 + r2)
+    }
+
+    fn visible(&self, direction: &Star) -> bool {
+        not_behind(direction)
+    }
+}
+
+impl Projection for Orthographic {
+    fn project(&self, direction: &Star) -> Fpp {
+        direction.xy() / direction.norm()
+    }
+
+    /// points beyond the unit circle go to the rim of the hemisphere
+    fn unproject(&self, point: &Fpp) -> Star {
+        let r2 = point.norm_squared();
+        if r2 >= 1.0 {
+            Star::new(point[0], point[1], 0.0).normalize()
+        } else {
+            Star::new(point[0], point[1], (1.0 - r2).sqrt())
+        }
+    }
+
+    fn visible(&self, direction: &Star) -> bool {
+        direction[2] > 0.0
+    }
+}
+
+impl Projection for Equidistant {
+    fn project(&self, direction: &Star) -> Fpp {
+        let off_axis = direction.xy().norm();
+        if off_axis == 0.0 {
+            return Fpp::zeros();
+        }
+        direction.xy() * off_axis.atan2(direction[2]) / off_axis
+    }
+
+    fn unproject(&self, point: &Fpp) -> Star {
+        let angle = point.norm();
+        if angle == 0.0 {
+            return Star::z_axis().into_inner();
+        }
+        let across = point * angle.sin() / angle;
+        Star::new(across[0], across[1], angle.cos())
+    }
+
+    fn visible(&self, direction: &Star) -> bool {
+        not_behind(direction)
+    }
+}
+
+/// Brown–Conrady radial distortion: a point `r` focal lengths away from the
+/// center of the focal plane moves to `r·(1 + k1·r² + k2·r⁴ + k3·r⁶)`, as in
+/// the images of a real camera. Barrel distortion has a negative `k1`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Distortion {
+    pub k1: f32,
+    pub k2: f32,
+    pub k3: f32,
+}
+
+impl Distortion {
+    pub fn new(k1: f32, k2: f32, k3: f32) -> Self {
+        Self { k1, k2, k3 }
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// how much a point `r` focal lengths away from the center moves out
+    fn factor(&self, r2: f32) -> f32 {
+        1.0 + r2 * (self.k1 + r2 * (self.k2 + r2 * self.k3))
+    }
+
+    /// where the lens takes `point`, in focal lengths
+    pub fn distort(&self, point: &Fpp) -> Fpp {
+        point * self.factor(point.norm_squared())
+    }
+
+    /// The point that the lens takes to `point`, as needed to solve where a
+    /// camera points from its image. Found with Newton's method, it is exact
+    /// while the distortion keeps growing with the distance to the center.
+    pub fn undistort(&self, point: &Fpp) -> Fpp {
+        let distorted = point.norm();
+        if self.is_none() || distorted == 0.0 {
+            return *point;
+        }
+        let mut r = distorted;
+        for _ in 0..20 {
+            let r2 = r * r;
+            let slope = 1.0 + r2 * (3.0 * self.k1 + r2 * (5.0 * self.k2 + r2 * 7.0 * self.k3));
+            let step = (r * self.factor(r2) - distorted) / slope;
+            r -= step;
+            if step.abs() < 1e-7 {
+                break;
+            }
+        }
+        point * (r / distorted)
+    }
+}
+
+impl Lens {
+    /// the projection this lens makes
+    pub fn projection(self) -> &'static dyn Projection {
+        match self {
+            Lens::Gnomonic => &Gnomonic,
+            Lens::Stereographic => &Stereographic,
+            Lens::Orthographic => &Orthographic,
+            Lens::Fisheye => &Equidistant,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use approx::assert_relative_eq;
+
+    use super::{Distortion, Equidistant, Gnomonic, Orthographic, Projection, Stereographic};
+    use crate::sky::{angle_between, direction_at, Fpp, Lens, Star};
+
+    #[test]
+    fn test_projections() {
+        let projections: [&dyn Projection; 4] =
+            [&Gnomonic, &Stereographic, &Orthographic, &Equidistant];
+        let directions = [
+            Star::new(0.0, 0.0, 1.0),
+            Star::new(0.3, -0.2, 1.0).normalize(),
+            direction_at(10.0, 20.0),
+            direction_at(200.0, 5.0),
+        ];
+        for projection in projections {
+            for direction in directions.iter().filter(|d| projection.visible(d)) {
+                let point = projection.project(direction);
+                let back = projection.unproject(&point);
+                assert!(angle_between(&back, direction) < 1e-3);
+            }
+            assert_eq!(projection.project(&Star::new(0.0, 0.0, 3.0)), Fpp::zeros());
+            assert!(!projection.visible(&Star::new(0.0, 0.0, -1.0)));
+        }
+
+        // 60° off the boresight
+        let direction = Star::new((PI / 3.0).sin(), 0.0, (PI / 3.0).cos());
+        let across = |projection: &dyn Projection| projection.project(&direction)[0];
+        assert_relative_eq!(across(&Gnomonic), 3f32.sqrt(), epsilon = 1e-5);
+        assert_relative_eq!(across(&Stereographic), 2.0 / 3f32.sqrt(), epsilon = 1e-5);
+        assert_relative_eq!(across(&Orthographic), 3f32.sqrt() / 2.0, epsilon = 1e-5);
+        assert_relative_eq!(across(&Equidistant), PI / 3.0, epsilon = 1e-5);
+
+        let behind = Star::new(1.0, 0.0, -1.0);
+        assert!(!Gnomonic.visible(&behind) && !Orthographic.visible(&behind));
+        assert!(Stereographic.visible(&behind) && Equidistant.visible(&behind));
+        assert_relative_eq!(
+            Equidistant.project(&behind)[0],
+            3.0 * PI / 4.0,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(
+            Orthographic.unproject(&Fpp::new(0.0, 2.0)),
+            Star::y_axis().into_inner()
+        );
+        assert_relative_eq!(Lens::Fisheye.projection().project(&direction)[0], PI / 3.0);
+    }
+
+    #[test]
+    fn test_distortion() {
+        let none = Distortion::default();
+        assert!(none.is_none());
+        let point = Fpp::new(0.3, -0.4);
+        assert_eq!(none.distort(&point), point);
+        assert_eq!(none.undistort(&point), point);
+
+        let barrel = Distortion::new(-0.2, 0.05, 0.0);
+        assert!(!barrel.is_none());
+        let distorted = barrel.distort(&point);
+        // r = 0.5: 1 - 0.2·0.25 + 0.05·0.0625
+        assert_relative_eq!(distorted, point * 0.953_125, epsilon = 1e-6);
+        assert_relative_eq!(barrel.undistort(&distorted), point, epsilon = 1e-6);
+        let pincushion = Distortion::new(0.1, 0.01, 0.001);
+        for point in [Fpp::new(1.0, 0.5), Fpp::new(-0.05, 0.0), Fpp::zeros()] {
+            let back = pincushion.undistort(&pincushion.distort(&point));
+            assert_relative_eq!(back, point, epsilon = 1e-5);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{names::NameStyle, sky::Sky};
+
+/// how many names to choose from
+pub const CHOICES: usize = 4;
+/// how soon a right answer earns more than being right
+pub const TIME_BONUS: Duration = Duration::from_secs(10);
+
+/// A star to name, among a few names to choose from.
+#[derive(Clone, Debug)]
+pub struct Question {
+    /// its index in the sky
+    pub star: usize,
+    /// its name and those of other stars, in no order
+    pub choices: Vec<String>,
+    asked: Instant,
+}
+
+/// A quiz on the names of the stars: one is marked and the player names it,
+/// earning more the sooner.
+#[derive(Clone, Debug, Default)]
+pub struct Quiz {
+    question: Option<Question>,
+    pub asked: usize,
+    pub right: usize,
+    pub points: f32,
+}
+
+impl Quiz {
+    /// Asks the name of one of `stars` of `sky`, as written in `style`, at
+    /// random among those with a name, with the names of others to choose
+    /// from. Without a named star, nothing is asked.
+    pub fn ask<R: Rng>(&mut self, sky: &Sky, stars: &[usize], style: NameStyle, rng: &mut R) {
+        let name = |i: usize| sky[i].display_name(style).into_owned();
+        let mut named: Vec<usize> = stars
+            .iter()
+            .copied()
+            .filter(|&i| !sky[i].display_name(style).is_empty())
+            .collect();
+        // one star for each name, that the choices are different
+        named.sort_by_key(|&i| name(i));
+        named.dedup_by_key(|i| name(*i));
+        self.question = named.choose(rng).map(|&star| {
+            let others: Vec<String> = named
+                .iter()
+                .filter(|&&i| i != star)
+                .map(|&i| name(i))
+                .collect();
+            let mut choices: Vec<String> =
+                others.choose_multiple(rng, CHOICES - 1).cloned().collect();
+            choices.push(name(star));
+            choices.shuffle(rng);
+            Question {
+                star,
+                choices,
+                asked: Instant::now(),
+            }
+        });
+    }
+
+    pub fn question(&self) -> Option<&Question> {
+        self.question.as_ref()
+    }
+
+    /// Answers the question asked with `answer`: the number of a choice, from
+    /// 1, or a name of the star as `Sky::find` reads them. Tells whether it
+    /// was right, or nothing if there was no question.
+    pub fn answer(&mut self, sky: &Sky, answer: &str, style: NameStyle) -> Option<bool> {
+        let question = self.question.take()?;
+        let name = sky[question.star].display_name(style);
+        let right = match answer.trim().parse::<usize>() {
+            Ok(n) => n >= 1 && question.choices.get(n - 1).is_some_and(|c| *c == name),
+            Err(_) => sky.find(answer) == Some(question.star),
+        };
+        self.asked += 1;
+        if right {
+            self.right += 1;
+            self.points += points(question.asked.elapsed());
+        }
+        Some(right)
+    }
+
+    /// what the player should do now, and how well it went so far
+    pub fn prompt(&self) -> String {
+        let tally = format!(
+            "{} of {} right, {:.1} points",
+            self.right, self.asked, self.points
+        );
+        let Some(question) = &self.question else {
+            return format!("quiz: {tally}");
+        };
+        let choices: Vec<String> = question
+            .choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| format!("{}) {choice}", i + 1))
+            .collect();
+        format!(
+            "quiz: which is the star marked? {}, or type its name;   {tally}",
+            choices.join("  ")
+        )
+    }
+}
+
+/// what a right answer after `elapsed` is worth: 1, and up to 1 more the sooner
+pub fn points(elapsed: Duration) -> f32 {
+    1.0 + (1.0 - elapsed.as_secs_f32() / TIME_BONUS.as_secs_f32()).max(0.0)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{points, Quiz, CHOICES};
+    use crate::{
+        names::NameStyle,
+        sky::{Brightness, CatalogStar, Sky, Star},
+    };
+
+    #[test]
+    fn test_quiz() {
+        let sky = Sky::from(
+            &["Alpha", "Beta", "Gamma", "Delta", "Epsilon", ""]
+                .map(|name| CatalogStar::new(Star::new(0.0, 0.0, 1.0), Brightness::new(0.5), name)),
+        );
+        let style = NameStyle::Bayer;
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut quiz = Quiz::default();
+        assert_eq!(quiz.answer(&sky, "1", style), None);
+
+        // only named stars are asked about
+        quiz.ask(&sky, &[5], style, &mut rng);
+        assert!(quiz.question().is_none());
+        quiz.ask(&sky, &[0, 1, 2, 3, 4, 5], style, &mut rng);
+        let question = quiz.question().unwrap().clone();
+        assert_ne!(question.star, 5);
+        assert_eq!(question.choices.len(), CHOICES);
+        let name = sky[question.star].name();
+        let right = question.choices.iter().position(|c| c == name).unwrap();
+        assert!(quiz.prompt().contains(&format!("{}) {name}", right + 1)));
+        assert_eq!(
+            quiz.answer(&sky, &(right + 1).to_string(), style),
+            Some(true)
+        );
+
+        // by its name, and wrong with that of another star
+        quiz.ask(&sky, &[2, 3], style, &mut rng);
+        let star = quiz.question().unwrap().star;
+        assert_eq!(
+            quiz.answer(&sky, &sky[star].name().to_lowercase(), style),
+            Some(true)
+        );
+        quiz.ask(&sky, &[2, 3], style, &mut rng);
+        let other = 5 - quiz.question().unwrap().star;
+        assert_eq!(quiz.answer(&sky, sky[other].name(), style), Some(false));
+        assert_eq!((quiz.right, quiz.asked), (2, 3));
+        assert!(quiz.points > 2.0 && quiz.points <= 4.0);
+        assert_eq!(
+            quiz.prompt(),
+            format!("quiz: 2 of 3 right, {:.1} points", quiz.points)
+        );
+    }
+
+    #[test]
+    fn test_points() {
+        assert_relative_eq!(points(Duration::ZERO), 2.0);
+        assert_relative_eq!(points(Duration::from_secs(5)), 1.5);
+        assert_relative_eq!(points(Duration::from_secs(60)), 1.0);
+    }
+}
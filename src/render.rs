@@ -0,0 +1,181 @@
+use image::{ImageResult, RgbImage};
+use nalgebra::UnitQuaternion;
+
+use crate::sky::{attitude_at, FoV, Sky};
+
+/// The stars of `sky` (in catalog coordinates) as a camera with attitude `q`
+/// sees them through `fov`, fitted to an image of `width` by `height` pixels.
+/// Each star is a disc as bright and as large as it looks, tinted by its color.
+pub fn to_image(sky: &Sky, q: UnitQuaternion<f32>, fov: &FoV, width: u32, height: u32) -> RgbImage {
+    let mut image = RgbImage::new(width, height);
+    let fov = fov.with_aspect(width as f32, height as f32);
+    for (px, py, b, star) in fov.screen_stars(sky, q, width, height) {
+        let b = (b as f32 - 64.0) / 192.0;
+        let color = star.tint().map(|c| c * b * 255.0);
+        let radius = 0.8 + 2.2 * star.brightness().glyph_size();
+        draw_disc(&mut image, px as f32, py as f32, radius, color);
+    }
+    image
+}
+
+/// Lights the pixels within `radius` of (`x`, `y`) with `color`, fading
+/// over the last pixel so that small discs do not look square.
+fn draw_disc(image: &mut RgbImage, x: f32, y: f32, radius: f32, color: [f32; 3]) {
+    let reach = radius.ceil() as i64 + 1;
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    for py in (y as i64 - reach).max(0)..(y as i64 + reach + 1).min(height) {
+        for px in (x as i64 - reach).max(0)..(x as i64 + reach + 1).min(width) {
+            let distance = ((px as f32 - x).powi(2) + (py as f32 - y).powi(2)).sqrt();
+            let cover = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if cover > 0.0 {
+                let pixel = image.get_pixel_mut(px as u32, py as u32);
+                for (channel, c) in pixel.0.iter_mut().zip(color) {
+                    *channel = (*channel).max((c * cover) as u8);
+                }
+            }
+        }
+    }
+}
+
+/// An image of the sky, as given to `cuyat render`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rendering {
+    pub output: String,
+    pub catalog: Option<String>,
+    pub nstars: usize,
+    pub attitude: UnitQuaternion<f32>,
+    pub fov: FoV,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rendering {
+    /// Reads `render <output.png> [--catalog <file>|random] [--nstars <n>]
+    /// [--ra <deg>] [--dec <deg>] [--roll <deg>] [--fov <deg>] [--size <w>x<h>]`.
+    /// The field of view is the angle across the shorter side of the image.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let usage = "usage: render <output.png> [--catalog <file>|random] [--nstars <n>] \
+                     [--ra <deg>] [--dec <deg>] [--roll <deg>] [--fov <deg>] [--size <w>x<h>]";
+        let mut output = None;
+        let mut catalog = Some(String::from("assets/bsc5.csv"));
+        let mut nstars = 2000;
+        let (mut ra, mut dec, mut roll, mut fov) = (0.0f32, 0.0f32, 0.0f32, 60.0f32);
+        let (mut width, mut height) = (800, 600);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut number = |name: &str| -> Result<f32, String> {
+                args.next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or(format!("{name} needs a number; {usage}"))
+            };
+            match arg.as_str() {
+                "--catalog" => {
+                    catalog = match args.next().map(String::as_str) {
+                        Some("random") => None,
+                        Some(file) => Some(file.to_string()),
+                        None => return Err(format!("--catalog needs a file; {usage}")),
+                    };
+                }
+                "--nstars" => nstars = number("--nstars")? as usize,
+                "--ra" => ra = number("--ra")?,
+                "--dec" => dec = number("--dec")?,
+                "--roll" => roll = number("--roll")?,
+                "--fov" => fov = number("--fov")?,
+                "--size" => {
+                    (width, height) = args
+                        .next()
+                        .and_then(|size| size.split_once('x'))
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                        .filter(|&(w, h)| w > 0 && h > 0)
+                        .ok_or(format!("--size is <width>x<height>; {usage}"))?;
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {arg}; {usage}")),
+                _ if output.is_none() => output = Some(arg.clone()),
+                _ => return Err(usage.to_string()),
+            }
+        }
+        if !(0.0..180.0).contains(&fov) || fov == 0.0 {
+            return Err(format!("--fov is between 0 and 180 degrees; {usage}"));
+        }
+        let half_fov = (fov.to_radians() / 2.0).tan();
+        Ok(Self {
+            output: output.ok_or(usage.to_string())?,
+            catalog,
+            nstars,
+            attitude: attitude_at(ra, dec, roll),
+            fov: FoV::new(half_fov, half_fov),
+            width,
+            height,
+        })
+    }
+
+    /// Writes the image, returning how many stars are in it.
+    pub fn run(&self) -> ImageResult<usize> {
+        let (sky, fallback) = Sky::new(&self.catalog, self.nstars);
+        if let Some(fallback) = fallback {
+            eprintln!("{fallback}");
+        }
+        let image = to_image(&sky, self.attitude, &self.fov, self.width, self.height);
+        image.save(&self.output)?;
+        let fov = self.fov.with_aspect(self.width as f32, self.height as f32);
+        Ok(fov
+            .screen_stars(&sky, self.attitude, self.width, self.height)
+            .count())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{to_image, Rendering};
+    use crate::sky::{attitude_at, direction_at, Brightness, CatalogStar, FoV, Sky, Star};
+
+    #[test]
+    fn test_to_image() {
+        let sky = Sky::from(&[
+            CatalogStar::new(direction_at(83.0, -5.0), Brightness::new(1.0), "a"),
+            CatalogStar::new(
+                direction_at(85.0, -5.0),
+                Brightness::from_magnitude(3.0),
+                "b",
+            ),
+            CatalogStar::new(direction_at(263.0, 5.0), Brightness::new(1.0), "behind"),
+        ]);
+        let q = attitude_at(83.0, -5.0, 0.0);
+        let image = to_image(&sky, q, &FoV::new(0.2, 0.2), 200, 100);
+        assert_eq!(image.dimensions(), (200, 100));
+        // the brightest star in the middle, white as it has no color index
+        assert_eq!(image.get_pixel(100, 50).0, [253, 253, 253]);
+        assert!(image.pixels().all(|p| p.0[0] <= 253));
+        // the fainter one to the east, on the left, and dimmer
+        let east = image.get_pixel(91, 50);
+        assert!(east.0[0] > 0 && east.0[0] < 200);
+        assert_eq!(image.get_pixel(150, 50).0, [0, 0, 0]);
+        let empty = to_image(&sky, q, &FoV::new(0.2, 0.2), 0, 0);
+        assert_eq!(empty.dimensions(), (0, 0));
+    }
+
+    #[test]
+    fn test_rendering_args() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        let rendering =
+            Rendering::from_args(&args("orion.png --ra 83 --dec -5 --fov 90 --size 640x480"))
+                .unwrap();
+        assert_eq!(rendering.output, "orion.png");
+        assert_eq!((rendering.width, rendering.height), (640, 480));
+        assert_relative_eq!(rendering.fov.half_fov().0, 1.0, epsilon = 1e-6);
+        assert_relative_eq!(
+            rendering.attitude * direction_at(83.0, -5.0),
+            Star::z_axis().into_inner(),
+            epsilon = 1e-5
+        );
+        let random = Rendering::from_args(&args("a.png --catalog random --nstars 50")).unwrap();
+        assert_eq!((random.catalog, random.nstars), (None, 50));
+        assert!(Rendering::from_args(&args("--ra 3")).is_err());
+        assert!(Rendering::from_args(&args("a.png --size 640")).is_err());
+        assert!(Rendering::from_args(&args("a.png --fov 180")).is_err());
+        assert!(Rendering::from_args(&args("a.png --zoom 2")).is_err());
+        assert!(Rendering::from_args(&args("a.png b.png")).is_err());
+    }
+}
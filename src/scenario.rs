@@ -0,0 +1,266 @@
+use std::{cell::RefCell, fmt, fs, rc::Rc};
+
+use nalgebra::{Quaternion, UnitQuaternion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    level::{Level, Rules},
+    sky::{random_quaternion, random_quaternion_with},
+    view::{cursive_window, Scoring, SkyView},
+};
+
+const DEFAULT_CATALOG: &str = "assets/bsc5.csv";
+const DEFAULT_NSTARS: usize = 400;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RunMode {
+    Cli,
+    Headless,
+}
+
+impl fmt::Display for RunMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunMode::Cli => write!(f, "cli"),
+            RunMode::Headless => write!(f, "headless"),
+        }
+    }
+}
+
+/// One game to be played in a batch.
+/// Scenarios are read from a file with one scenario per line, made of
+/// `key=value` pairs separated by spaces. Known keys are
+/// `seed`, `target` (as `w,i,j,k`), `catalog` (a filename or `random`),
+/// `nstars`, `mode` (`cli` or `headless`) and `level` (a `.cuyat` file whose
+/// values are used unless given after it). Empty lines and lines
+/// starting with `#` are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub seed: Option<u64>,
+    pub target: Option<UnitQuaternion<f32>>,
+    pub catalog: Option<String>,
+    pub nstars: usize,
+    pub mode: RunMode,
+    pub rules: Rules,
+    pub par: Option<f32>,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            target: None,
+            catalog: Some(String::from(DEFAULT_CATALOG)),
+            nstars: DEFAULT_NSTARS,
+            mode: RunMode::Cli,
+            rules: Rules::default(),
+            par: None,
+        }
+    }
+}
+
+impl From<Level> for Scenario {
+    fn from(level: Level) -> Self {
+        Self {
+            seed: level.seed,
+            target: level.target,
+            catalog: level.catalog,
+            nstars: level.nstars,
+            rules: level.rules,
+            par: level.par,
+            ..Self::default()
+        }
+    }
+}
+
+impl Scenario {
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        let mut scenario = Self::default();
+        for field in line.split_whitespace() {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or(format!("expected key=value, got '{field}'"))?;
+            match key {
+                "seed" => {
+                    scenario.seed = Some(value.parse().map_err(|_| format!("bad seed '{value}'"))?)
+                }
+                "target" => scenario.target = Some(parse_quaternion(value)?),
+                "catalog" => {
+                    scenario.catalog = match value {
+                        "random" => None,
+                        _ => Some(String::from(value)),
+                    }
+                }
+                "nstars" => {
+                    scenario.nstars = value
+                        .parse()
+                        .map_err(|_| format!("bad number of stars '{value}'"))?
+                }
+                "mode" => {
+                    scenario.mode = match value {
+                        "cli" => RunMode::Cli,
+                        "headless" => RunMode::Headless,
+                        _ => return Err(format!("unknown mode '{value}'")),
+                    }
+                }
+                "level" => {
+                    let mode = scenario.mode;
+                    scenario = Scenario {
+                        mode,
+                        ..Level::from_file(value)?.into()
+                    };
+                }
+                _ => return Err(format!("unknown key '{key}'")),
+            }
+        }
+        Ok(scenario)
+    }
+
+    /// target and starting attitudes. With a seed, they are always the same,
+    /// as the sky is.
+    pub fn attitudes(&self) -> (UnitQuaternion<f32>, UnitQuaternion<f32>) {
+        match self.seed {
+            Some(seed) => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                let target_q = random_quaternion_with(&mut rng);
+                let real_q = random_quaternion_with(&mut rng);
+                (self.target.unwrap_or(target_q), real_q)
+            }
+            None => (
+                self.target.unwrap_or_else(random_quaternion),
+                random_quaternion(),
+            ),
+        }
+    }
+}
+
+fn parse_quaternion(value: &str) -> Result<UnitQuaternion<f32>, String> {
+    let coords: Vec<f32> = value
+        .split(',')
+        .map(|c| c.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("bad quaternion '{value}'"))?;
+    if coords.len() != 4 {
+        return Err(format!("quaternion '{value}' needs 4 coordinates"));
+    }
+    let quat = Quaternion::new(coords[0], coords[1], coords[2], coords[3]);
+    UnitQuaternion::try_new(quat, f32::EPSILON).ok_or(format!("null quaternion '{value}'"))
+}
+
+pub fn read_scenarios(fname: &str) -> Result<Vec<Scenario>, String> {
+    let input = fs::read_to_string(fname).map_err(|e| format!("{fname}: {e}"))?;
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| Scenario::from_line(line).map_err(|e| format!("{fname}:{}: {e}", i + 1)))
+        .collect()
+}
+
+/// The view `scenario` is played in, keeping its score in `scoring`.
+fn view_for(scenario: &Scenario, scoring: Rc<RefCell<Scoring>>) -> SkyView {
+    let (target_q, real_q) = scenario.attitudes();
+    let sky_view = SkyView::with_attitudes(
+        scenario.catalog.clone(),
+        scenario.nstars,
+        scoring,
+        target_q,
+        real_q,
+    )
+    .with_rules(scenario.rules.clone());
+    match scenario.seed {
+        Some(seed) => sky_view.with_sky_seed(seed),
+        None => sky_view,
+    }
+}
+
+/// Plays all scenarios in `fname`, one after the other, and prints how each went.
+pub fn run_batch(fname: &str, scoring: Rc<RefCell<Scoring>>) -> Result<(), String> {
+    let scenarios = read_scenarios(fname)?;
+    let mut report = Vec::new();
+    for (i, scenario) in scenarios.iter().enumerate() {
+        let mut sky_view = view_for(scenario, Rc::clone(&scoring));
+        let par = scenario
+            .par
+            .map(|par| format!(", par: {par:.6}"))
+            .unwrap_or_default();
+        let (games_before, moves_before) = {
+            let score = (*scoring).borrow();
+            (score.games(), score.counted_moves)
+        };
+        match scenario.mode {
+            RunMode::Cli => {
+                cursive_window(sky_view);
+            }
+            RunMode::Headless => sky_view.play_headless(),
+        }
+        let score = (*scoring).borrow();
+        let scores = &score.total[games_before..];
+        let result = format!(
+            "games: {}, moves: {}, score: {:.6}",
+            scores.len(),
+            score.counted_moves - moves_before,
+            scores.iter().sum::<f32>() / scores.len().max(1) as f32
+        );
+        report.push(format!(
+            "scenario {} ({}): {}{}",
+            i + 1,
+            scenario.mode,
+            result,
+            par
+        ));
+    }
+    println!("{}", report.join("\n"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use nalgebra::UnitQuaternion;
+
+    use super::{view_for, RunMode, Scenario};
+    use crate::view::Scoring;
+
+    #[test]
+    fn test_from_line() {
+        let scenario =
+            Scenario::from_line("seed=7 catalog=random nstars=300 mode=headless").unwrap();
+        assert_eq!(scenario.seed, Some(7));
+        assert_eq!(scenario.catalog, None);
+        assert_eq!(scenario.nstars, 300);
+        assert_eq!(scenario.mode, RunMode::Headless);
+        assert_eq!(scenario.attitudes(), scenario.attitudes());
+
+        let scenario = Scenario::from_line("target=2,0,0,0").unwrap();
+        assert_eq!(scenario.target, Some(UnitQuaternion::identity()));
+        assert_eq!(scenario.catalog, Some(String::from("assets/bsc5.csv")));
+        assert_eq!(scenario.mode, RunMode::Cli);
+
+        assert!(Scenario::from_line("target=1,2,3").is_err());
+        assert!(Scenario::from_line("nstars=many").is_err());
+        assert!(Scenario::from_line("mode=vr").is_err());
+        assert!(Scenario::from_line("speed=3").is_err());
+    }
+
+    #[test]
+    fn test_headless() {
+        let scenario =
+            Scenario::from_line("seed=7 catalog=random nstars=50 mode=headless").unwrap();
+        let play = || {
+            let scoring = Rc::new(RefCell::new(Scoring::default()));
+            let mut view = view_for(&scenario, Rc::clone(&scoring));
+            let stars: Vec<_> = view.sky().iter().map(|s| *s.position()).collect();
+            view.play_headless();
+            let scoring = scoring.borrow();
+            (stars, scoring.total.clone(), scoring.metadata[0].solved)
+        };
+        let (stars, total, solved) = play();
+        // the same sky, played the same way to the target
+        assert_eq!(play(), (stars, total.clone(), solved));
+        assert!(solved);
+        assert_eq!(total.len(), 1);
+    }
+}
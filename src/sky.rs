@@ -1,161 +1,938 @@
 use itertools::Itertools;
 use rand::Rng;
-use std::{collections::HashMap, f32::consts::PI, fs};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI},
+    fmt, fs, io,
+    ops::{Index, RangeBounds, RangeInclusive},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use nalgebra::{OVector, SVector, UnitQuaternion, U3};
+use crate::{
+    boundaries::Boundaries,
+    cache, ephemeris,
+    format::{CatalogFormat, LineParser},
+    frames::{Camera, Cameras, Equatorial},
+    names::{proper_name, NameStyle},
+    projection::Distortion,
+    variable,
+};
+
+use nalgebra::{Matrix3xX, OVector, Rotation3, SVector, UnitQuaternion, Vector3, U3};
 use rand_distr::{Distribution, Exp, Uniform};
-use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 pub type Star = SVector<f32, 3>;
 type Position = SVector<f32, 3>;
 pub type Fpp = SVector<f32, 2>; // Focal Plane Point
-pub type FPStars = Vec<(Fpp, Brightness, String)>;
-
-/// Star (position), Brightness, Name
-pub type StBrNm = (Star, Brightness, String);
+pub type FPStars<'a> = Vec<(Fpp, &'a CatalogStar)>;
+/// The positions of the stars of a sky, one in each column, to turn them all at once.
+pub type SkyMat = Matrix3xX<f32>;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
+#[serde(from = "f32", into = "f32")]
 pub struct Brightness {
-    pub brightness: f32, // expected to be between 0 and 1
+    brightness: f32, // always between 0 and 1
+}
+/// as `Brightness::new`, so that a saved one is clamped too
+impl From<f32> for Brightness {
+    fn from(b: f32) -> Self {
+        Self::new(b)
+    }
+}
+impl From<Brightness> for f32 {
+    fn from(b: Brightness) -> Self {
+        b.brightness
+    }
 }
 impl Brightness {
     const MAX_MAG: f32 = -1.46f32;
 
-    fn for_magnitude(m: f32) -> Self {
-        let brightness: f32 = 0.01f32.powf((m - Self::MAX_MAG) / 5.0);
-        Self { brightness }
+    /// brightness of a star of magnitude `m`. Sirius, the brightest star, has 1.
+    pub fn from_magnitude(m: f32) -> Self {
+        Self::new(0.01f32.powf((m - Self::MAX_MAG) / 5.0))
     }
 
+    /// a brightness of `b`, clamped to be between 0 and 1
     pub fn new(b: f32) -> Self {
-        Self { brightness: b }
+        let brightness = if b.is_nan() { 0.0 } else { b.clamp(0.0, 1.0) };
+        Self { brightness }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.brightness
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        Self::MAX_MAG - 2.5 * self.brightness.log10()
+    }
+
+    /// How large a star this bright should be drawn, from 0 for those of
+    /// magnitude 6.5 or fainter to 1 for Sirius: the eye tells magnitudes
+    /// apart by size as much as by light.
+    pub fn glyph_size(&self) -> f32 {
+        ((6.5 - self.magnitude()) / (6.5 - Self::MAX_MAG)).clamp(0.0, 1.0)
     }
 
     /// random brightnesses of `nstars` stars.
     /// This is not accurate but close to.
     pub fn random(nstars: usize) -> Vec<Self> {
+        Self::random_with(nstars, &mut rand::thread_rng())
+    }
+
+    /// same as `random` but drawing from the given generator
+    pub fn random_with<R: Rng>(nstars: usize, rng: &mut R) -> Vec<Self> {
         let alpha = 5.6f32;
         let beta = 1.238f32;
         let lambda = 100f32.ln() * beta / 5.0;
         let max_mag = 5.0 / (2.0 * beta) * (nstars as f32 / alpha).log10();
         let exp = Exp::new(lambda).unwrap();
-        exp.sample_iter(&mut rand::thread_rng())
+        exp.sample_iter(rng)
             .filter(|&n| max_mag - n > Self::MAX_MAG)
             .take(nstars)
             .map(|n: f32| max_mag - n)
-            .map(Brightness::for_magnitude)
+            .map(Brightness::from_magnitude)
             .collect()
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct Sky {
-    pub stars: Vec<StBrNm>,
+/// A star of a catalog: where it is, how bright it is and what it is called,
+/// plus whatever else the catalog tells about it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CatalogStar {
+    position: Star,
+    brightness: Brightness,
+    /// shared by the copies of the star, as turned or seen from elsewhere
+    name: Arc<str>,
+    #[serde(default)]
+    hr: Option<u32>,
+    #[serde(default)]
+    color_index: Option<f32>,
+    #[serde(default)]
+    spectral_class: Option<char>,
+    #[serde(default)]
+    proper_motion: Option<(f32, f32)>,
+    #[serde(default)]
+    constellation: Option<String>,
+    #[serde(default)]
+    variable: bool,
 }
 
-impl Sky {
-    pub fn new(catalog: &Option<String>, nstars: usize) -> Self {
-        match catalog {
-            None => Self::random_with_stars(nstars),
-            Some(ref filename) => Self::from_converted_file(filename.as_str(), nstars),
+impl CatalogStar {
+    pub fn new<S: Into<Arc<str>>>(position: Star, brightness: Brightness, name: S) -> Self {
+        Self {
+            position,
+            brightness,
+            name: name.into(),
+            hr: None,
+            color_index: None,
+            spectral_class: None,
+            proper_motion: None,
+            constellation: None,
+            variable: false,
         }
     }
-    pub fn from(stars: &[StBrNm]) -> Self {
+
+    /// the star with its number in the Harvard Revised (BSC) catalog
+    pub fn with_hr(self, hr: u32) -> Self {
         Self {
-            stars: stars.to_vec(),
+            hr: Some(hr),
+            ..self
         }
     }
 
-    pub fn from_line(line: &str, sbn_re: &Regex) -> StBrNm {
-        let sbn = sbn_re.captures(line).unwrap();
+    /// the star with its B-V color index
+    pub fn with_color_index(self, color_index: f32) -> Self {
+        Self {
+            color_index: Some(color_index),
+            ..self
+        }
+    }
 
-        let name = String::from(sbn.get(1).unwrap().as_str());
+    /// the star with its spectral class, one of `OBAFGKM`
+    pub fn with_spectral_class(self, spectral_class: char) -> Self {
+        Self {
+            spectral_class: Some(spectral_class),
+            ..self
+        }
+    }
 
-        let rahh: u8 = sbn.get(2).unwrap().as_str().parse().unwrap();
-        let ramm: u8 = sbn.get(3).unwrap().as_str().parse().unwrap();
-        let rass: f32 = sbn.get(4).unwrap().as_str().parse().unwrap();
-        let ra: f32 = ((rahh as f32) * 15.0 + (ramm as f32) / 4.0 + rass / 240.0).to_radians();
+    /// the star with its yearly proper motion, in arcseconds along the right
+    /// ascension (that is, already multiplied by cos(dec)) and the declination
+    pub fn with_proper_motion(self, ra: f32, dec: f32) -> Self {
+        Self {
+            proper_motion: Some((ra, dec)),
+            ..self
+        }
+    }
 
-        let sgn: f32 = match sbn.get(5).unwrap().as_str() {
-            "+" => 1.0,
-            _ => -1.0,
-        };
-        let dedeg: u8 = sbn.get(6).unwrap().as_str().parse().unwrap();
-        let demin: u8 = sbn.get(7).unwrap().as_str().parse().unwrap();
-        let desec: u8 = sbn.get(8).unwrap().as_str().parse().unwrap();
-        let dec: f32 =
-            sgn * ((dedeg as f32) + (demin as f32) / 60.0 + (desec as f32) / 3600.0).to_radians();
+    /// the star flagged as variable by its catalog
+    pub fn with_variable(self) -> Self {
+        Self {
+            variable: true,
+            ..self
+        }
+    }
+
+    /// the star tagged with the abbreviation of its constellation
+    pub fn with_constellation<S: Into<String>>(self, constellation: S) -> Self {
+        Self {
+            constellation: Some(constellation.into()),
+            ..self
+        }
+    }
+
+    /// the same star, as bright as `brightness`
+    pub fn with_brightness(&self, brightness: Brightness) -> Self {
+        Self {
+            brightness,
+            ..self.clone()
+        }
+    }
+
+    /// the same star called `name`
+    pub fn renamed<S: Into<Arc<str>>>(&self, name: S) -> Self {
+        Self {
+            name: name.into(),
+            ..self.clone()
+        }
+    }
+
+    /// the same star seen at `position`
+    pub fn moved_to(&self, position: Star) -> Self {
+        Self {
+            position,
+            ..self.clone()
+        }
+    }
+
+    pub fn position(&self) -> &Star {
+        &self.position
+    }
+
+    pub fn brightness(&self) -> Brightness {
+        self.brightness
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.brightness.magnitude()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn hr(&self) -> Option<u32> {
+        self.hr
+    }
+
+    pub fn color_index(&self) -> Option<f32> {
+        self.color_index
+    }
 
-        let star_pos = Star::new(ra.cos() * dec.cos(), ra.sin() * dec.cos(), dec.sin());
+    pub fn spectral_class(&self) -> Option<char> {
+        self.spectral_class
+    }
+
+    pub fn proper_motion(&self) -> Option<(f32, f32)> {
+        self.proper_motion
+    }
+
+    /// whether its catalog flags it as variable
+    pub fn is_variable(&self) -> bool {
+        self.variable
+    }
+
+    /// its IAU proper name, like `Betelgeuse`, if it has one
+    pub fn proper_name(&self) -> Option<&'static str> {
+        proper_name(self.hr, &self.name)
+    }
 
-        let sgn: f32 = match sbn.get(9).unwrap().as_str() {
-            "-" => -1.0,
-            _ => 1.0,
+    /// its name as labeled with `style`, or its catalog name if it has none of that kind
+    pub fn display_name(&self, style: NameStyle) -> Cow<'_, str> {
+        match (style, self.proper_name(), self.hr) {
+            (NameStyle::Proper, Some(proper), _) => Cow::Borrowed(proper),
+            (NameStyle::Hr, _, Some(hr)) => Cow::Owned(format!("HR {hr}")),
+            _ => Cow::Borrowed(&self.name),
+        }
+    }
+
+    /// the constellation it was tagged with or, if none, the one in its name
+    pub fn constellation(&self) -> Option<&str> {
+        self.constellation
+            .as_deref()
+            .or_else(|| constellation(&self.name))
+    }
+
+    /// the star `years` years later, moved by its proper motion
+    pub fn moved_by(&self, years: f32) -> Self {
+        let Some((pm_ra, pm_dec)) = self.proper_motion else {
+            return self.clone();
         };
-        let mag: f32 = sbn.get(10).unwrap().as_str().trim().parse().unwrap();
-        let brightness = Brightness::for_magnitude(sgn * mag);
-        (star_pos, brightness, name)
+        let (ra, dec) = ra_dec(&self.position);
+        let (ra, dec) = (ra.to_radians(), dec.to_radians());
+        let east = Star::new(-ra.sin(), ra.cos(), 0.0);
+        let north = Star::new(-dec.sin() * ra.cos(), -dec.sin() * ra.sin(), dec.cos());
+        let arcsec = (1.0f32 / 3600.0).to_radians();
+        let shift = (east * pm_ra + north * pm_dec) * arcsec * years;
+        self.moved_to((self.position + shift).normalize())
     }
 
-    pub fn from_catalog_file(fname: &str) -> Self {
-        let sbn_re = Regex::new("^.{7}(.{7}).{61}(\\d\\d)(\\d\\d)(\\d\\d\\.\\d)([+-])(\\d\\d)(\\d\\d)(\\d\\d).{12}([+ -])([0-9. ]{4})").unwrap();
-        let input: String = fs::read_to_string(fname).unwrap();
-        let input: Vec<&str> = input.trim_end().split('\n').collect();
-        let stars: Vec<StBrNm> = input
+    /// the RGB tint of the star by its spectral class: blue for O and B, yellow
+    /// for G, red for M. White if the class is not known.
+    pub fn class_tint(&self) -> [f32; 3] {
+        match self.spectral_class {
+            Some('O' | 'B') => [0.6, 0.7, 1.0],
+            Some('A') => [0.85, 0.9, 1.0],
+            Some('F') => [1.0, 1.0, 0.85],
+            Some('G') => [1.0, 0.95, 0.55],
+            Some('K') => [1.0, 0.75, 0.45],
+            Some('M') => [1.0, 0.5, 0.4],
+            _ => [1.0, 1.0, 1.0],
+        }
+    }
+
+    /// the RGB tint of the star, from blue-white for hot stars to orange-red
+    /// for cool ones. White if the color index is not known.
+    pub fn tint(&self) -> [f32; 3] {
+        // B-V and the color of a black body of that temperature
+        const TINTS: [(f32, [f32; 3]); 7] = [
+            (-0.4, [0.61, 0.71, 1.0]),
+            (0.0, [0.80, 0.86, 1.0]),
+            (0.4, [1.0, 0.98, 0.95]),
+            (0.8, [1.0, 0.91, 0.78]),
+            (1.2, [1.0, 0.82, 0.62]),
+            (1.6, [1.0, 0.72, 0.45]),
+            (2.0, [1.0, 0.62, 0.33]),
+        ];
+        let Some(bv) = self.color_index else {
+            return [1.0, 1.0, 1.0];
+        };
+        let bv = bv.clamp(TINTS[0].0, TINTS[TINTS.len() - 1].0);
+        let ((bv0, c0), (bv1, c1)) = TINTS
             .iter()
-            .map(|&line| Self::from_line(line, &sbn_re))
-            .filter(|sbn| sbn.1.brightness > 0.01)
+            .tuple_windows()
+            .find(|(_, (bv1, _))| bv <= *bv1)
+            .unwrap();
+        let t = (bv - bv0) / (bv1 - bv0);
+        [0, 1, 2].map(|k| c0[k] + t * (c1[k] - c0[k]))
+    }
+}
+
+/// Why a catalog could not be read, or some of its lines were skipped.
+#[derive(Debug)]
+pub enum SkyError {
+    Io(io::Error),
+    /// a line that does not hold a star; `number` starts at 1
+    BadLine {
+        number: Option<usize>,
+        line: String,
+    },
+    /// the catalog has no star at all
+    Empty,
+    /// a column the catalog header should have
+    MissingColumn(String),
+    /// a catalog format descriptor that cannot be used
+    BadFormat(String),
+}
+
+impl SkyError {
+    pub(crate) fn bad_line(line: &str) -> Self {
+        Self::BadLine {
+            number: None,
+            line: line.to_string(),
+        }
+    }
+    pub(crate) fn at_line(self, n: usize) -> Self {
+        match self {
+            Self::BadLine { line, .. } => Self::BadLine {
+                number: Some(n),
+                line,
+            },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for SkyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot read the catalog: {e}"),
+            Self::BadLine {
+                number: Some(n),
+                line,
+            } => write!(f, "line {n}: no star in {line:?}"),
+            Self::BadLine { number: None, line } => write!(f, "no star in {line:?}"),
+            Self::Empty => write!(f, "the catalog has no stars"),
+            Self::MissingColumn(c) => write!(f, "the catalog has no {c} column"),
+            Self::BadFormat(e) => write!(f, "bad catalog format: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SkyError {}
+
+impl From<io::Error> for SkyError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// What `Sky::validate_catalog` finds in a catalog: how many stars it has,
+/// the lines without a star, the lines with a magnitude out of
+/// `MAGNITUDE_RANGE` and the names given to more than one star.
+#[derive(Debug, Default)]
+pub struct CatalogReport {
+    pub stars: usize,
+    pub skipped: Vec<SkyError>,
+    pub out_of_range: Vec<(usize, f32)>,
+    pub duplicates: Vec<String>,
+}
+
+impl CatalogReport {
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.out_of_range.is_empty() && self.duplicates.is_empty()
+    }
+}
+
+impl fmt::Display for CatalogReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} stars", self.stars)?;
+        for e in &self.skipped {
+            writeln!(f, "{e}")?;
+        }
+        for (n, m) in &self.out_of_range {
+            writeln!(f, "line {n}: magnitude {m} out of range")?;
+        }
+        for name in &self.duplicates {
+            writeln!(f, "more than one star called {name:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// the magnitudes a catalog star can sensibly have
+pub const MAGNITUDE_RANGE: RangeInclusive<f32> = -2.0..=21.0;
+
+/// A loaded sky along with the lines of the catalog that were skipped.
+pub type Loaded = (Sky, Vec<SkyError>);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Sky {
+    pub stars: Vec<CatalogStar>,
+}
+
+impl Sky {
+    /// The `nstars` brightest stars of `catalog`, or a random sky if there is
+    /// no catalog. If it cannot be read, those of the embedded catalog are
+    /// used, or a random sky without the `embedded` feature, and why is
+    /// returned along with the sky.
+    pub fn new(catalog: &Option<String>, nstars: usize) -> (Self, Option<String>) {
+        let Some(filename) = catalog else {
+            return (Self::random_with_stars(nstars), None);
+        };
+        match Self::all_from(filename) {
+            (Some(sky), fallback) => (sky.brightest_of(nstars).tagged(), fallback),
+            (None, fallback) => (Self::random_with_stars(nstars), fallback),
+        }
+    }
+
+    /// All the stars `new` picks the brightest from: those of `filename` or,
+    /// if it cannot be read, those of the embedded catalog. Without it there
+    /// are none, and `new` makes a random sky. When `filename` is not used,
+    /// the reason comes along, to be shown to the player.
+    pub(crate) fn all_from(filename: &str) -> (Option<Self>, Option<String>) {
+        match Self::load_cached_all(filename) {
+            Ok((sky, _)) => (Some(sky), None),
+            #[cfg(feature = "embedded")]
+            Err(e) => (
+                Some(Self::embedded_all()),
+                Some(format!("{e}; using the embedded catalog")),
+            ),
+            #[cfg(not(feature = "embedded"))]
+            Err(e) => (None, Some(format!("{e}; using a random sky"))),
+        }
+    }
+
+    /// the `nstars` brightest stars of the bright star catalog built into the binary
+    #[cfg(feature = "embedded")]
+    pub fn embedded(nstars: usize) -> Self {
+        Self::embedded_all().brightest_of(nstars).tagged()
+    }
+
+    #[cfg(feature = "embedded")]
+    fn embedded_all() -> Self {
+        const CATALOG: &str = include_str!("../assets/bsc5.csv");
+        let (sky, _) = Self::from_str_with_format(CATALOG, &CatalogFormat::converted())
+            .expect("the embedded catalog has stars");
+        sky
+    }
+
+    /// Loads `catalog`, in the format described by a `.toml` file with the same
+    /// name if there is one. Otherwise it can be a Gaia CSV file or a file
+    /// written by `convert_catalog_file`.
+    pub fn load(catalog: &Option<String>, nstars: usize) -> Result<Loaded, SkyError> {
+        let Some(filename) = catalog else {
+            return Ok((Self::random_with_stars(nstars), vec![]));
+        };
+        let (sky, skipped) = Self::load_all(filename)?;
+        Ok((sky.brightest_of(nstars).tagged(), skipped))
+    }
+
+    /// Like `load`, but reads the stars from a binary cache next to the
+    /// catalog when it was made from the catalog and its format descriptor as
+    /// they are now, and writes it otherwise.
+    /// Skipped lines are only reported when the catalog is parsed.
+    pub fn load_cached(catalog: &Option<String>, nstars: usize) -> Result<Loaded, SkyError> {
+        let Some(filename) = catalog else {
+            return Ok((Self::random_with_stars(nstars), vec![]));
+        };
+        let (sky, skipped) = Self::load_cached_all(filename)?;
+        Ok((sky.brightest_of(nstars).tagged(), skipped))
+    }
+
+    /// all the stars of `filename`, read as `load_cached` reads them
+    fn load_cached_all(filename: &str) -> Result<Loaded, SkyError> {
+        let sources = cache::Sources::of(Path::new(filename))?;
+        let cache_file = Path::new(filename).with_extension("cache");
+        if let Some(sky) = cache::read(&cache_file, &sources) {
+            return Ok((sky, vec![]));
+        }
+        let (sky, skipped) = Self::load_all(filename)?;
+        // without a cache it is only slower
+        let _ = cache::write(&cache_file, &sources, &sky);
+        Ok((sky, skipped))
+    }
+
+    /// The sky with its stars tagged by the constellation boundaries in
+    /// `Boundaries::DEFAULT_FILE`, if it can be read. Otherwise the stars
+    /// keep the constellation in their names.
+    pub(crate) fn tagged(self) -> Self {
+        match Boundaries::shared() {
+            Some(boundaries) => self.with_constellations(boundaries),
+            None => self,
+        }
+    }
+
+    /// the sky with each star tagged with the constellation it is in
+    pub fn with_constellations(&self, boundaries: &Boundaries) -> Self {
+        self.iter()
+            .map(|star| match boundaries.constellation_at(star.position()) {
+                Some(c) => star.clone().with_constellation(c),
+                None => star.clone(),
+            })
+            .collect()
+    }
+
+    fn load_all(filename: &str) -> Result<Loaded, SkyError> {
+        let input: String = read_catalog(filename)?;
+        Self::from_str_with_format(&input, &Self::format_for(filename, &input)?)
+    }
+
+    /// the format `load` reads `catalog` with
+    pub fn catalog_format(catalog: &str) -> Result<CatalogFormat, SkyError> {
+        Self::format_for(catalog, &read_catalog(catalog)?)
+    }
+
+    /// where the `.toml` file describing the format of `catalog` would be
+    pub(crate) fn format_descriptor(catalog: &Path) -> PathBuf {
+        catalog.with_extension("toml")
+    }
+
+    fn format_for(filename: &str, input: &str) -> Result<CatalogFormat, SkyError> {
+        let descriptor = Self::format_descriptor(Path::new(filename));
+        if descriptor.exists() {
+            return CatalogFormat::from_file(descriptor);
+        }
+        Ok(match input.lines().next() {
+            Some(header) if CatalogFormat::is_gaia_header(header) => CatalogFormat::gaia(),
+            _ => CatalogFormat::converted(),
+        })
+    }
+    pub fn from(stars: &[CatalogStar]) -> Self {
+        Self {
+            stars: stars.to_vec(),
+        }
+    }
+
+    /// Parses a catalog line without header. Records with no position or no
+    /// magnitude, as some novae and non-stellar objects in the BSC, are errors.
+    pub fn from_line(line: &str, format: &CatalogFormat) -> Result<CatalogStar, SkyError> {
+        format.parser(None)?.star(line)
+    }
+
+    /// Parses every line of `input` but those starting with `#`, keeping the
+    /// lines that could not be parsed apart. `first` is the number of the
+    /// first line of `input` in its file.
+    fn parse_lines<F>(input: &str, first: usize, parse: F) -> (Vec<CatalogStar>, Vec<SkyError>)
+    where
+        F: Fn(&str) -> Result<CatalogStar, SkyError>,
+    {
+        let (stars, skipped): (Vec<_>, Vec<_>) = input
+            .trim_end()
+            .split('\n')
+            .enumerate()
+            .filter(|(_, line)| !line.starts_with('#'))
+            .map(|(i, line)| parse(line).map_err(|e| e.at_line(i + first)))
+            .partition(Result::is_ok);
+        (
+            stars.into_iter().map(Result::unwrap).collect(),
+            skipped.into_iter().map(Result::unwrap_err).collect(),
+        )
+    }
+
+    /// Reads all the stars of `fname`, laid out as `format` says.
+    pub fn from_file_with_format(fname: &str, format: &CatalogFormat) -> Result<Loaded, SkyError> {
+        Self::from_str_with_format(&read_catalog(fname)?, format)
+    }
+
+    /// the parser for the lines of `input`, the lines after the header and
+    /// the number of the first of them
+    fn split_header<'a>(
+        input: &'a str,
+        format: &CatalogFormat,
+    ) -> Result<(LineParser, &'a str, usize), SkyError> {
+        Ok(if format.has_header() {
+            let (header, body) = input.split_once('\n').unwrap_or((input, ""));
+            (format.parser(Some(header))?, body, 2)
+        } else {
+            (format.parser(None)?, input, 1)
+        })
+    }
+    fn from_str_with_format(input: &str, format: &CatalogFormat) -> Result<Loaded, SkyError> {
+        let (parser, body, first) = Self::split_header(input, format)?;
+        let (stars, skipped) = Self::parse_lines(body, first, |line| parser.star(line));
+        if stars.is_empty() {
+            return Err(SkyError::Empty);
+        }
+        Ok((Self::from(&stars), skipped))
+    }
+
+    /// Reads `fname` as `load` would and reports what is wrong with it,
+    /// for those who prepare their own catalogs.
+    pub fn validate_catalog(
+        fname: &str,
+        format: &CatalogFormat,
+    ) -> Result<CatalogReport, SkyError> {
+        let input = read_catalog(fname)?;
+        let (parser, body, first) = Self::split_header(&input, format)?;
+        let mut report = CatalogReport::default();
+        let mut names = vec![];
+        for (i, line) in body.trim_end().split('\n').enumerate() {
+            if line.starts_with('#') {
+                continue;
+            }
+            match parser.star(line) {
+                Ok(star) => {
+                    report.stars += 1;
+                    match parser.magnitude(line) {
+                        Some(m) if MAGNITUDE_RANGE.contains(&m) => {}
+                        m => report.out_of_range.push((i + first, m.unwrap_or(f32::NAN))),
+                    }
+                    names.push(star.name);
+                }
+                Err(e) => report.skipped.push(e.at_line(i + first)),
+            }
+        }
+        // names like `  Ori` only tell the constellation
+        report.duplicates = names
+            .into_iter()
+            .filter(|name| {
+                constellation(name).map_or(!name.trim().is_empty(), |c| c != name.trim())
+            })
+            .duplicates()
+            .map(|name| name.to_string())
             .collect();
-        Self::from(&stars)
+        Ok(report)
+    }
+
+    /// Reads the BSC file `fname`. Records that are not stars, as HR 92, are
+    /// given back as skipped.
+    pub fn from_catalog_file(fname: &str) -> Result<Loaded, SkyError> {
+        let (mut sky, skipped) = Self::from_file_with_format(fname, &CatalogFormat::bsc())?;
+        sky.retain(|star| star.brightness().value() > 0.01);
+        if sky.is_empty() {
+            return Err(SkyError::Empty);
+        }
+        Ok((sky, skipped))
+    }
+
+    /// Reads the `nstars` brightest stars of `fname`, a file written by
+    /// `convert_catalog_file`.
+    pub fn from_converted_file(fname: &str, nstars: usize) -> Result<Loaded, SkyError> {
+        let (sky, skipped) = Self::from_file_with_format(fname, &CatalogFormat::converted())?;
+        Ok((sky.brightest_of(nstars), skipped))
     }
 
-    pub fn from_converted_file(fname: &str, nstars: usize) -> Self {
-        let sbn_re = Regex::new("^(.{5}),(\\d\\d)(\\d\\d)(\\d\\d\\.\\d),([+-])(\\d\\d)(\\d\\d)(\\d\\d),(-?)([0-9. ]{4})").unwrap();
-        let input: String = fs::read_to_string(fname).unwrap();
-        let input: Vec<&str> = input.trim_end().split('\n').collect();
-        let mut stars: Vec<StBrNm> = input
+    /// Reads the `nstars` brightest stars of a CSV file exported from the Gaia
+    /// archive. The header must have `ra`, `dec` (in degrees) and
+    /// `phot_g_mean_mag` columns, in any order. Gaia stars have no names.
+    pub fn from_gaia_file(fname: &str, nstars: usize) -> Result<Loaded, SkyError> {
+        let (sky, skipped) = Self::from_file_with_format(fname, &CatalogFormat::gaia())?;
+        Ok((sky.brightest_of(nstars), skipped))
+    }
+
+    /// the `nstars` brightest stars, the brightest last
+    fn brightest_of(&self, nstars: usize) -> Self {
+        let mut sky = self.brightest(nstars);
+        sky.stars.reverse();
+        sky
+    }
+    /// Writes the sky as `convert_catalog_file` does. Magnitudes brighter
+    /// than that of Sirius are written as that of Sirius.
+    pub fn write_converted(&self, outfile: &str) -> io::Result<()> {
+        self.write_converted_at(outfile, Epoch::J2000.year())
+    }
+
+    /// Writes the sky as `write_converted`, with its coordinates taken as
+    /// those of the year `epoch`.
+    fn write_converted_at(&self, outfile: &str, epoch: f32) -> io::Result<()> {
+        let lines = self.iter().map(|star| {
+            let optional = |value: Option<String>| value.unwrap_or_default();
+            let (pm_ra, pm_dec) = match star.proper_motion() {
+                Some((ra, dec)) => (format!("{ra:.3}"), format!("{dec:.3}")),
+                None => (String::new(), String::new()),
+            };
+            format!(
+                "{},{},{},{:.2},{},{},{pm_ra},{pm_dec}",
+                star.name(),
+                ra_str(*star.position()),
+                dec_str(*star.position()),
+                star.magnitude(),
+                optional(star.color_index().map(|bv| format!("{bv:.2}"))),
+                optional(star.spectral_class().map(String::from)),
+            )
+        });
+        let header = format!("# epoch {epoch:.1}");
+        fs::write(outfile, std::iter::once(header).chain(lines).join("\n"))
+    }
+
+    /// Reads the BSC file `fname` taking the coordinates of `columns`, with
+    /// the Bayer letters of the names written in Greek, as `α Ori`.
+    pub fn from_bsc_file(fname: &str, columns: Epoch) -> Result<Loaded, SkyError> {
+        let (sky, skipped) = Self::from_file_with_format(fname, &CatalogFormat::bsc_at(columns))?;
+        let sky = sky
             .iter()
-            .map(|&line| Self::from_line(line, &sbn_re))
+            .map(|star| star.renamed(greek_name(star.name())))
             .collect();
-        stars.sort_by(|sbn1, sbn2| sbn1.1.brightness.total_cmp(&sbn2.1.brightness));
-        let eff_nstars = stars.len().min(nstars);
-        Self::from(stars.get(stars.len() - eff_nstars..).unwrap())
+        Ok((sky, skipped))
     }
+
+    /// Converts the BSC file `infile` to `outfile`, keeping the stars up to
+    /// `max_magnitude`, and returns how many were written.
     pub fn convert_catalog_file(
         infile: &str,
         outfile: &str,
         max_magnitude: f32,
-    ) -> Result<u8, std::io::Error> {
-        let sbn_re = Regex::new("^.{7}(.{7}).{61}(\\d\\d\\d\\d\\d\\d\\.\\d)([+-]\\d\\d\\d\\d\\d\\d).{12}([+ -][0-9. ]{4})").unwrap();
-        let conversion_map = greek_names_map();
-        let input: String = fs::read_to_string(infile).unwrap();
-        let input: Vec<&str> = input.trim_end().split('\n').collect();
-        let outb: Vec<String> = input
-            .iter()
-            .filter_map(|line| {
-                let sbn = sbn_re.captures(line).unwrap();
-                let name = String::from(sbn.get(1).unwrap().as_str());
-                let name = format!(
-                    "{}{}",
-                    conversion_map[name.get(0..3).unwrap()],
-                    name.get(3..).unwrap()
-                );
-                let ra = String::from(sbn.get(2).unwrap().as_str());
-                let dec = String::from(sbn.get(3).unwrap().as_str());
-                let mag: f32 = sbn.get(4).unwrap().as_str().trim().parse().unwrap();
-                if mag <= max_magnitude {
-                    Some(format!("{name},{ra},{dec},{mag:.2}"))
+    ) -> Result<usize, SkyError> {
+        Self::convert_catalog_file_to_epoch(
+            infile,
+            outfile,
+            max_magnitude,
+            Epoch::J2000,
+            Epoch::J2000.year(),
+        )
+    }
+
+    /// Converts the BSC file `infile` taking the coordinates of `columns`
+    /// and precessing them to the year `to_epoch`. The B-V color index, the
+    /// spectral class and the proper motions in RA and Dec are kept in the
+    /// fifth to eighth columns, empty when the BSC does not have them.
+    /// The epoch is written in the first line of `outfile`.
+    pub fn convert_catalog_file_to_epoch(
+        infile: &str,
+        outfile: &str,
+        max_magnitude: f32,
+        columns: Epoch,
+        to_epoch: f32,
+    ) -> Result<usize, SkyError> {
+        let (sky, _) = Self::from_bsc_file(infile, columns)?;
+        let mut sky = sky.magnitude_cut(max_magnitude);
+        if to_epoch != columns.year() {
+            sky = sky.precessed(columns.year(), to_epoch);
+        }
+        sky.write_converted_at(outfile, to_epoch)?;
+        Ok(sky.len())
+    }
+
+    pub fn len(&self) -> usize {
+        self.stars.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, CatalogStar> {
+        self.stars.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&CatalogStar> {
+        self.stars.get(index)
+    }
+
+    pub fn push(&mut self, star: CatalogStar) {
+        self.stars.push(star);
+    }
+
+    /// removes the first star called `name`, returning it if there was one
+    pub fn remove_by_name(&mut self, name: &str) -> Option<CatalogStar> {
+        let index = self.stars.iter().position(|star| star.name() == name)?;
+        Some(self.stars.remove(index))
+    }
+
+    /// keeps only the stars for which `keep` is true
+    pub fn retain<F: FnMut(&CatalogStar) -> bool>(&mut self, keep: F) {
+        self.stars.retain(keep);
+    }
+
+    /// Joins several skies in one. A star closer than `DUPLICATE_DISTANCE` to
+    /// one of an earlier sky is taken to be the same star and left out, but
+    /// close stars of the same sky are kept: they are doubles.
+    pub fn merge(skies: &[Sky]) -> Self {
+        let min_cos = DUPLICATE_DISTANCE.cos();
+        let mut merged: Vec<CatalogStar> = vec![];
+        for sky in skies {
+            let earlier = merged.len();
+            for star in sky {
+                let duplicate = merged[..earlier]
+                    .iter()
+                    .any(|other| other.position().dot(star.position()) > min_cos);
+                if !duplicate {
+                    merged.push(star.clone());
+                }
+            }
+        }
+        Self { stars: merged }
+    }
+
+    /// The sky with the stars closer than `separation` radians to a brighter
+    /// one merged into it: the brightest keeps its name and the others add
+    /// their brightness and move it to their weighted mean position. Stars
+    /// come out brightest last, as the loaders leave them.
+    pub fn with_doubles_merged(&self, separation: f32) -> Self {
+        let min_cos = separation.cos();
+        let mut stars = self.stars.clone();
+        stars.sort_by(|s1, s2| s2.brightness().value().total_cmp(&s1.brightness().value()));
+        // each primary with the weighted sum of the positions and the total brightness
+        let mut groups: Vec<(CatalogStar, Star, f32)> = vec![];
+        for star in stars {
+            let b = star.brightness().value();
+            match groups
+                .iter_mut()
+                .find(|(primary, _, _)| primary.position().dot(star.position()) > min_cos)
+            {
+                Some((_, position, total)) => {
+                    *position += star.position() * b;
+                    *total += b;
+                }
+                None => groups.push((star.clone(), star.position() * b, b)),
+            }
+        }
+        let mut merged: Vec<CatalogStar> = groups
+            .into_iter()
+            .map(|(primary, position, total)| {
+                let position = if total > 0.0 {
+                    position.normalize()
                 } else {
-                    None
+                    *primary.position()
+                };
+                CatalogStar {
+                    brightness: Brightness::new(total),
+                    ..primary.moved_to(position)
                 }
             })
             .collect();
+        merged.reverse();
+        Self { stars: merged }
+    }
 
-        fs::write(outfile, outb.join("\n"))?;
-        Ok(0)
+    /// the sky with the known variable stars as bright as they are at julian date `jd`
+    pub fn with_variability(&self, jd: f64) -> Self {
+        self.iter()
+            .map(|star| variable::at_date(star, jd))
+            .collect()
     }
 
-    pub fn len(&self) -> usize {
-        self.stars.len()
+    /// the stars in the constellation with abbreviation `abbrev`, like `Ori`
+    pub fn in_constellation(&self, abbrev: &str) -> Self {
+        self.iter()
+            .filter(|star| {
+                star.constellation()
+                    .is_some_and(|c| c.eq_ignore_ascii_case(abbrev))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// the constellation of the star with a known constellation closest to `direction`
+    pub fn nearest_constellation(&self, direction: &Star) -> Option<&str> {
+        self.iter()
+            .filter_map(|star| {
+                star.constellation()
+                    .map(|c| (angle_between(star.position(), direction), c))
+            })
+            .min_by(|(a1, _), (a2, _)| a1.total_cmp(a2))
+            .map(|(_, c)| c)
+    }
+
+    /// The index of the star closest to `direction` among those bright enough
+    /// to be seen through `fov`, as when one is clicked, and how far it is, in radians.
+    pub fn nearest_star(&self, direction: &Star, fov: &FoV) -> Option<(usize, f32)> {
+        self.iter()
+            .enumerate()
+            .filter(|(_, star)| fov.can_be_seen(&star.brightness()))
+            .map(|(i, star)| (i, angle_between(star.position(), direction)))
+            .min_by(|(_, a1), (_, a2)| a1.total_cmp(a2))
+    }
+
+    /// The index of the star called `name`, whatever the case: by its catalog
+    /// name, its proper name or its HR number, as `HR 2061`. Without any,
+    /// the brightest one with a name that starts so.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        let names = |star: &CatalogStar| {
+            let hr = star.hr().map(|hr| format!("hr {hr}"));
+            [star.name().to_lowercase()]
+                .into_iter()
+                .chain(star.proper_name().map(str::to_lowercase))
+                .chain(hr)
+                .collect::<Vec<_>>()
+        };
+        let named = |matches: &dyn Fn(&str) -> bool| {
+            self.iter()
+                .enumerate()
+                .filter(|(_, star)| names(star).iter().any(|n| matches(n)))
+                .max_by(|(_, s1), (_, s2)| {
+                    s1.brightness().value().total_cmp(&s2.brightness().value())
+                })
+                .map(|(i, _)| i)
+        };
+        named(&|n| n == name).or_else(|| named(&|n| n.starts_with(&name)))
+    }
+
+    /// the `n` brightest stars, brightest first
+    pub fn brightest(&self, n: usize) -> Self {
+        let mut stars = self.stars.clone();
+        stars.sort_by(|s1, s2| s2.brightness().value().total_cmp(&s1.brightness().value()));
+        stars.truncate(n);
+        Self { stars }
+    }
+
+    /// the stars for which `keep` is true, in the same order
+    pub fn filter<F: FnMut(&CatalogStar) -> bool>(&self, mut keep: F) -> Self {
+        self.iter().filter(|star| keep(star)).cloned().collect()
+    }
+
+    /// the stars whose magnitude is in `range`
+    pub fn filter_by_magnitude<R: RangeBounds<f32>>(&self, range: R) -> Self {
+        self.filter(|star| range.contains(&star.magnitude()))
+    }
+
+    /// the stars as bright as magnitude `m` or brighter
+    pub fn magnitude_cut(&self, m: f32) -> Self {
+        self.filter_by_magnitude(..=m)
     }
 
     #[must_use]
@@ -168,26 +945,61 @@ impl Sky {
             stars: self
                 .stars
                 .iter()
-                .map(|(s, b, n)| (*s - pos, *b, n.clone()))
+                .map(|star| star.moved_to(star.position() - pos))
                 .collect(),
         }
     }
 
+    /// the sky at julian date `jd`, with each star moved by its proper motion
+    /// since J2000. Coordinates are still those of J2000: see `precessed`.
+    pub fn at_epoch(&self, jd: f64) -> Self {
+        let years = julian_year(jd) - Epoch::J2000.year();
+        self.iter().map(|star| star.moved_by(years)).collect()
+    }
+
+    /// the sky in the coordinates of year `to` instead of those of year `from`
+    pub fn precessed(&self, from: f32, to: f32) -> Self {
+        let rotation = precession_from_j2000(to) * precession_from_j2000(from).inverse();
+        self.iter()
+            .map(|star| star.moved_to(rotation * star.position()))
+            .collect()
+    }
+
+    /// the sky with the Sun, the Moon and the planets at julian date `jd`
+    pub fn with_solar_system(&self, jd: f64) -> Self {
+        self.iter()
+            .cloned()
+            .chain(ephemeris::solar_system(jd))
+            .collect()
+    }
+
     pub fn with_attitude(&self, q: UnitQuaternion<f32>) -> Self {
-        Self {
-            stars: self
-                .stars
-                .iter()
-                .map(|(s, b, n)| (q * *s, *b, n.clone()))
-                .collect(),
-        }
+        #[cfg(feature = "parallel")]
+        let stars = {
+            use rayon::prelude::*;
+            self.stars
+                .par_iter()
+                .map(|star| star.moved_to(q * star.position()))
+                .collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let stars = self
+            .stars
+            .iter()
+            .map(|star| star.moved_to(q * star.position()))
+            .collect();
+        Self { stars }
     }
 
     pub fn random_with_stars(nstars: usize) -> Self {
-        let unifd = Uniform::new(-1.0, 1.0);
-        let rng = rand::thread_rng();
+        Self::random_with_rng(nstars, &mut rand::thread_rng())
+    }
 
-        let stars_positions: Vec<Star> = rng
+    /// same as `random_with_stars` but drawing from the given generator,
+    /// so that a seeded one gives always the same sky
+    pub fn random_with_rng<R: Rng>(nstars: usize, rng: &mut R) -> Self {
+        let unifd = Uniform::new(-1.0, 1.0);
+        let stars_positions: Vec<Star> = (&mut *rng)
             .sample_iter(unifd)
             .tuples::<(f32, f32, f32)>()
             .filter_map(|(x, y, z)| {
@@ -201,29 +1013,205 @@ impl Sky {
             .take(nstars)
             .collect();
 
-        let brightnesses = Brightness::random(nstars);
-        let prefs: Vec<&str> = greek_names_map().values().copied().collect();
+        let brightnesses = Brightness::random_with(nstars, rng);
+        // sorted, since the order of a map changes from run to run
+        let prefs: Vec<&str> = greek_names_map().values().copied().sorted().collect();
         let consts: Vec<char> = ('a'..='z').chain('A'..='Z').chain('😀'..'🙂').collect();
         let names = consts
             .iter()
             .cartesian_product(prefs.iter())
             .map(|(c, p)| format!("{p}{c}"));
 
-        let stars: Vec<StBrNm> = stars_positions
+        let stars: Vec<CatalogStar> = stars_positions
             .iter()
             .copied()
             .zip(brightnesses.iter())
             .zip(names)
-            .map(|((s, &b), n)| (s, b, n))
+            .map(|((s, &b), n)| CatalogStar::new(s, b, n))
             .collect();
         Self { stars }
     }
 
+    /// random stars without names, to be mixed among the real ones
+    pub fn false_stars(nstars: usize) -> Self {
+        Self::false_stars_with(nstars, &mut rand::thread_rng())
+    }
+
+    /// same as `false_stars` but drawing from the given generator
+    pub fn false_stars_with<R: Rng>(nstars: usize, rng: &mut R) -> Self {
+        Self {
+            stars: Self::random_with_rng(nstars, rng)
+                .stars
+                .into_iter()
+                .map(|star| CatalogStar::new(*star.position(), star.brightness(), ""))
+                .collect(),
+        }
+    }
+
     pub fn with_random_quaternion(&self) -> Sky {
         self.with_attitude(random_quaternion())
     }
 }
 
+impl Index<usize> for Sky {
+    type Output = CatalogStar;
+
+    fn index(&self, index: usize) -> &CatalogStar {
+        &self.stars[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a Sky {
+    type Item = &'a CatalogStar;
+    type IntoIter = std::slice::Iter<'a, CatalogStar>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Turns the stars of a sky with a single product of matrices, keeping its
+/// buffers from frame to frame: unlike `Sky::with_attitude`, once they are
+/// large enough nothing is copied but the positions.
+#[derive(Clone, Debug)]
+pub struct SkyRotation {
+    positions: SkyMat,
+    rotated: SkyMat,
+}
+
+impl Default for SkyRotation {
+    fn default() -> Self {
+        Self {
+            positions: SkyMat::zeros(0),
+            rotated: SkyMat::zeros(0),
+        }
+    }
+}
+
+impl SkyRotation {
+    /// the positions of the stars of `sky` as a camera with attitude `q`
+    /// sees them, in the order of the sky
+    pub fn rotate(&mut self, sky: &[CatalogStar], q: UnitQuaternion<f32>) -> Cameras<'_> {
+        let n = sky.len();
+        if self.positions.ncols() < n {
+            self.positions = SkyMat::zeros(n);
+            self.rotated = SkyMat::zeros(n);
+        }
+        for (mut column, star) in self.positions.column_iter_mut().zip(sky) {
+            column.copy_from(star.position());
+        }
+        Equatorial::all_to_camera(self.positions.columns(0, n), q, &mut self.rotated)
+    }
+}
+
+impl IntoIterator for Sky {
+    type Item = CatalogStar;
+    type IntoIter = std::vec::IntoIter<CatalogStar>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stars.into_iter()
+    }
+}
+
+impl Extend<CatalogStar> for Sky {
+    fn extend<I: IntoIterator<Item = CatalogStar>>(&mut self, iter: I) {
+        self.stars.extend(iter);
+    }
+}
+
+impl FromIterator<CatalogStar> for Sky {
+    fn from_iter<I: IntoIterator<Item = CatalogStar>>(iter: I) -> Self {
+        Self {
+            stars: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Epochs of the coordinates given in the BSC catalog
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Epoch {
+    B1900,
+    J2000,
+}
+
+impl Epoch {
+    pub fn year(&self) -> f32 {
+        match self {
+            Epoch::B1900 => 1900.0,
+            Epoch::J2000 => 2000.0,
+        }
+    }
+
+    /// where the coordinates of this epoch start in a BSC line
+    pub(crate) fn bsc_column(&self) -> usize {
+        match self {
+            Epoch::B1900 => 60,
+            Epoch::J2000 => 75,
+        }
+    }
+}
+
+/// Rotation from J2000 mean equatorial coordinates to those of year `epoch`,
+/// with the IAU 1976 precession angles.
+/// Good enough to move B1900 positions too, though it ignores the FK4 to FK5 corrections.
+pub fn precession_from_j2000(epoch: f32) -> Rotation3<f32> {
+    let t = (epoch as f64 - 2000.0) / 100.0;
+    let arcsec = |a: f64| (a / 3600.0).to_radians() as f32;
+    let zeta = arcsec(2306.2181 * t + 0.30188 * t.powi(2) + 0.017998 * t.powi(3));
+    let z = arcsec(2306.2181 * t + 1.09468 * t.powi(2) + 0.018203 * t.powi(3));
+    let theta = arcsec(2004.3109 * t - 0.42665 * t.powi(2) - 0.041833 * t.powi(3));
+    Rotation3::from_axis_angle(&Vector3::z_axis(), z)
+        * Rotation3::from_axis_angle(&Vector3::y_axis(), -theta)
+        * Rotation3::from_axis_angle(&Vector3::z_axis(), zeta)
+}
+
+/// the year, with its fraction, of julian date `jd`
+pub fn julian_year(jd: f64) -> f32 {
+    (2000.0 + (jd - 2_451_545.0) / 365.25) as f32
+}
+
+/// direction of `star` at year `from` moved to year `to`
+pub fn precess(star: &Star, from: f32, to: f32) -> Star {
+    precession_from_j2000(to) * precession_from_j2000(from).inverse() * star
+}
+
+fn ra_str(star: Star) -> String {
+    let ra = star[1].atan2(star[0]).to_degrees().rem_euclid(360.0);
+    let tenths = (ra * 2400.0).round() as u32 % 864000;
+    format!(
+        "{:02}{:02}{:02}.{}",
+        tenths / 36000,
+        tenths / 600 % 60,
+        tenths / 10 % 60,
+        tenths % 10
+    )
+}
+
+fn dec_str(star: Star) -> String {
+    let dec = (star[2] / star.norm()).asin().to_degrees();
+    let secs = (dec.abs() * 3600.0).round() as u32;
+    let sgn = if dec < 0.0 { '-' } else { '+' };
+    format!(
+        "{sgn}{:02}{:02}{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// `name`, as the BSC writes it, with its Bayer letter in Greek: `Alp Ori`
+/// is `α Ori`. Names that are not a Bayer letter, as in novae, keep only
+/// the constellation.
+fn greek_name(name: &str) -> String {
+    let name = format!("{name:>7}");
+    match (name.get(..3), name.get(3..)) {
+        (Some(letter), Some(rest)) => {
+            format!("{}{rest}", greek_names_map().get(letter).unwrap_or(&" "))
+        }
+        _ => name,
+    }
+}
+
 fn greek_names_map<'a>() -> HashMap<&'a str, &'a str> {
     HashMap::from([
         ("   ", " "),
@@ -259,14 +1247,225 @@ pub fn random_quaternion() -> nalgebra::Unit<nalgebra::Quaternion<f32>> {
     UnitQuaternion::from_euler_angles(rpy[0], rpy[1], rpy[2])
 }
 
+/// The attitude that points the boresight at right ascension `ra` and
+/// declination `dec` (in degrees), with the north `roll` degrees clockwise
+/// from up, as seen through the window. The east is to the left, as in the sky.
+pub fn attitude_at(ra: f32, dec: f32, roll: f32) -> UnitQuaternion<f32> {
+    let boresight = direction_at(ra, dec);
+    // down is to the south, or along some meridian at the poles
+    let down = (boresight * boresight[2] - Star::z_axis().into_inner())
+        .try_normalize(1e-6)
+        .unwrap_or_else(|| direction_at(ra, 0.0));
+    let right = down.cross(&boresight);
+    let rotation = Rotation3::from_matrix_unchecked(nalgebra::Matrix3::from_rows(&[
+        right.transpose(),
+        down.transpose(),
+        boresight.transpose(),
+    ]));
+    UnitQuaternion::from_axis_angle(&Vector3::z_axis(), roll.to_radians())
+        * UnitQuaternion::from_rotation_matrix(&rotation)
+}
+
+/// same as `random_quaternion` but drawing from the given generator,
+/// so that a seeded one gives always the same attitude.
+pub fn random_quaternion_with<R: Rng>(rng: &mut R) -> nalgebra::Unit<nalgebra::Quaternion<f32>> {
+    let rpy: OVector<f32, U3> = OVector::<f32, U3>::from_fn(|_, _| rng.gen::<f32>()) * 2.0 * PI;
+    UnitQuaternion::from_euler_angles(rpy[0], rpy[1], rpy[2])
+}
+
+/// distance between two attitudes, as shown to the player
+pub fn attitude_distance(target_q: UnitQuaternion<f32>, real_q: UnitQuaternion<f32>) -> f32 {
+    let (roll, pitch, yaw) = (target_q / real_q).euler_angles();
+    (roll.powi(2) + pitch.powi(2) + yaw.powi(2)).sqrt()
+}
+
+/// angle, in radians, between the directions of two stars
+pub fn angle_between(a: &Star, b: &Star) -> f32 {
+    (a.normalize().dot(&b.normalize())).clamp(-1.0, 1.0).acos()
+}
+
+/// `steps` points evenly spaced on the great circle 90° away from `pole`
+pub fn great_circle(pole: &Star, steps: usize) -> Vec<Star> {
+    small_circle(pole, FRAC_PI_2, steps)
+}
+
+/// `steps` points evenly spaced on the circle `radius` radians away from `pole`
+pub fn small_circle(pole: &Star, radius: f32, steps: usize) -> Vec<Star> {
+    let pole = pole.normalize();
+    // two directions at right angles to the pole, and to each other
+    let u = pole
+        .cross(&Star::x_axis())
+        .try_normalize(1e-3)
+        .unwrap_or_else(|| pole.cross(&Star::y_axis()).normalize());
+    let v = pole.cross(&u);
+    (0..steps)
+        .map(|i| {
+            let t = i as f32 / steps as f32 * std::f32::consts::TAU;
+            pole * radius.cos() + (u * t.cos() + v * t.sin()) * radius.sin()
+        })
+        .collect()
+}
+
+/// The lines of an equatorial grid, each as `steps` points around it: the
+/// meridians every `ra_step` degrees of right ascension and the parallels
+/// every `dec_step` degrees of declination, the equator among them.
+pub fn equatorial_grid(ra_step: f32, dec_step: f32, steps: usize) -> Vec<Vec<Star>> {
+    let north = Star::z_axis().into_inner();
+    // each meridian is half of a great circle, the other half is 180° away
+    let meridians = (0..)
+        .map(|i| i as f32 * ra_step)
+        .take_while(|&ra| ra_step > 0.0 && ra < 180.0)
+        .map(|ra| great_circle(&direction_at(ra + 90.0, 0.0), steps));
+    let parallels = (1..)
+        .map(|i| i as f32 * dec_step - 90.0)
+        .take_while(|&dec| dec_step > 0.0 && dec < 90.0)
+        .map(|dec| small_circle(&north, (90.0 - dec).to_radians(), steps));
+    meridians.chain(parallels).collect()
+}
+
+/// right ascension and declination of `direction`, in degrees
+pub fn ra_dec(direction: &Star) -> (f32, f32) {
+    let ra = direction[1]
+        .atan2(direction[0])
+        .to_degrees()
+        .rem_euclid(360.0);
+    let dec = (direction[2] / direction.norm()).asin().to_degrees();
+    (ra, dec)
+}
+
+/// direction at right ascension `ra` and declination `dec`, in degrees
+pub fn direction_at(ra: f32, dec: f32) -> Star {
+    let (ra, dec) = (ra.to_radians(), dec.to_radians());
+    Star::new(ra.cos() * dec.cos(), ra.sin() * dec.cos(), dec.sin())
+}
+
+/// the spectral class in a spectral type like `M1-2Ia-Iab` or `gK0`
+pub fn spectral_class(sp_type: &str) -> Option<char> {
+    sp_type.chars().find(|c| "OBAFGKM".contains(*c))
+}
+
+/// how close, in radians, two stars of different catalogs must be to be the same star
+pub const DUPLICATE_DISTANCE: f32 = 1.0 / 60.0 * PI / 180.0;
+
+/// The text of a catalog file, uncompressed if its name ends in `.gz`.
+/// Compressed catalogs need the `gzip` feature.
+fn read_catalog(fname: &str) -> io::Result<String> {
+    if !fname.ends_with(".gz") {
+        return fs::read_to_string(fname);
+    }
+    #[cfg(feature = "gzip")]
+    {
+        let mut input = String::new();
+        io::Read::read_to_string(
+            &mut flate2::read::MultiGzDecoder::new(fs::File::open(fname)?),
+            &mut input,
+        )?;
+        Ok(input)
+    }
+    #[cfg(not(feature = "gzip"))]
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{fname}: reading compressed catalogs needs the gzip feature"),
+    ))
+}
+
+/// the IAU abbreviations of the 88 constellations
+pub const CONSTELLATIONS: [&str; 88] = [
+    "And", "Ant", "Aps", "Aqr", "Aql", "Ara", "Ari", "Aur", "Boo", "Cae", "Cam", "Cnc", "CVn",
+    "CMa", "CMi", "Cap", "Car", "Cas", "Cen", "Cep", "Cet", "Cha", "Cir", "Col", "Com", "CrA",
+    "CrB", "Crv", "Crt", "Cru", "Cyg", "Del", "Dor", "Dra", "Equ", "Eri", "For", "Gem", "Gru",
+    "Her", "Hor", "Hya", "Hyi", "Ind", "Lac", "Leo", "LMi", "Lep", "Lib", "Lup", "Lyn", "Lyr",
+    "Men", "Mic", "Mon", "Mus", "Nor", "Oct", "Oph", "Ori", "Pav", "Peg", "Per", "Phe", "Pic",
+    "Psc", "PsA", "Pup", "Pyx", "Ret", "Sge", "Sgr", "Sco", "Scl", "Sct", "Ser", "Sex", "Tau",
+    "Tel", "Tri", "TrA", "Tuc", "UMa", "UMi", "Vel", "Vir", "Vol", "Vul",
+];
+
+/// the constellation abbreviation in a catalog name like `α Ori`
+pub fn constellation(name: &str) -> Option<&str> {
+    let abbrev = name.get(name.len().checked_sub(3)?..)?;
+    CONSTELLATIONS.contains(&abbrev).then_some(abbrev)
+}
+
 pub fn quat_coords_str(quat: UnitQuaternion<f32>) -> String {
     format!("_ + {:.5} i + {:.5} j + {:.5} k", quat[0], quat[1], quat[2])
 }
 
-#[derive(Clone)]
+/// A disk (the Earth seen from a low orbit) that hides the stars behind it.
+/// It is fixed to the camera, so it moves over the sky as the camera turns.
+#[derive(Clone, Debug)]
+pub struct Occluder {
+    /// the center of the disk, in the frame of the camera
+    pub direction: Star,
+    pub angular_radius: f32,
+}
+
+impl Occluder {
+    pub fn new(direction: Star, angular_radius: f32) -> Self {
+        Self {
+            direction: direction.normalize(),
+            angular_radius,
+        }
+    }
+
+    /// An occluder between 15 and 60 degrees wide below the boresight, its
+    /// limb up to 20 degrees below the center of the view.
+    pub fn random() -> Self {
+        Self::random_with(&mut rand::thread_rng())
+    }
+
+    /// same as `random` but drawing from the given generator
+    pub fn random_with<R: Rng>(rng: &mut R) -> Self {
+        let angular_radius = rng.gen_range(15f32..60f32).to_radians();
+        let below = angular_radius + rng.gen_range(0f32..20f32).to_radians();
+        Self::new(Star::new(0.0, below.sin(), below.cos()), angular_radius)
+    }
+
+    /// whether `star` is behind the occluder, seen by a camera at `attitude`
+    pub fn hides(&self, star: &Star, attitude: UnitQuaternion<f32>) -> bool {
+        (attitude * star.normalize()).dot(&self.direction) > self.angular_radius.cos()
+    }
+
+    /// the stars of `sky` that are not behind the occluder, seen at `attitude`
+    pub fn occult(&self, sky: &Sky, attitude: UnitQuaternion<f32>) -> Sky {
+        Sky {
+            stars: sky
+                .stars
+                .iter()
+                .filter(|star| !self.hides(star.position(), attitude))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// How directions are laid on the focal plane: each lens makes one of the
+/// projections in `projection`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Lens {
+    /// straight lines stay straight, for narrow fields
+    #[default]
+    Gnomonic,
+    /// small shapes are kept, even far from the center
+    Stereographic,
+    /// one hemisphere, as seen from far away
+    Orthographic,
+    /// equidistant fisheye: the distance from the center grows as the angle
+    /// from the boresight, so that a whole hemisphere fits
+    Fisheye,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct FoV {
     half_fov_x: f32,
     half_fov_y: f32,
+    vignetting: bool,
+    #[serde(default)]
+    lens: Lens,
+    #[serde(default)]
+    distortion: Distortion,
+    /// the faintest stars shown; without it, the wider the field the brighter they must be
+    #[serde(default)]
+    limiting_magnitude: Option<f32>,
 }
 
 impl FoV {
@@ -274,41 +1473,192 @@ impl FoV {
         Self {
             half_fov_x,
             half_fov_y,
+            vignetting: false,
+            lens: Lens::Gnomonic,
+            distortion: Distortion::default(),
+            limiting_magnitude: None,
         }
     }
-    pub fn rescale(&self, scale: f32) -> Self {
+    pub fn rescale(&self, scale: f32) -> Self {
+        Self {
+            half_fov_x: self.half_fov_x * scale,
+            half_fov_y: self.half_fov_y * scale,
+            ..self.clone()
+        }
+    }
+    /// The same field of view on a viewport `width` by `height`: the shorter
+    /// side keeps its angle and the longer one widens, so that circles in the
+    /// sky stay round on the screen.
+    pub fn with_aspect(&self, width: f32, height: f32) -> Self {
+        if width <= 0.0 || height <= 0.0 {
+            return self.clone();
+        }
+        let half_fov = self.half_fov_x.min(self.half_fov_y);
+        let (half_fov_x, half_fov_y) = if width >= height {
+            (half_fov * width / height, half_fov)
+        } else {
+            (half_fov, half_fov * height / width)
+        };
+        Self {
+            half_fov_x,
+            half_fov_y,
+            ..self.clone()
+        }
+    }
+    /// a fisheye view with the same shape, whose shorter side spans a hemisphere
+    pub fn all_sky(&self) -> Self {
+        let scale = FRAC_PI_2 / self.half_fov_x.min(self.half_fov_y);
+        self.rescale(scale).with_lens(Lens::Fisheye)
+    }
+    pub fn with_lens(&self, lens: Lens) -> Self {
+        Self {
+            lens,
+            ..self.clone()
+        }
+    }
+    pub fn lens(&self) -> Lens {
+        self.lens
+    }
+    /// the same field of view through a lens that bends it radially
+    pub fn with_distortion(&self, distortion: Distortion) -> Self {
+        Self {
+            distortion,
+            ..self.clone()
+        }
+    }
+    pub fn distortion(&self) -> Distortion {
+        self.distortion
+    }
+    /// dim stars toward the edge of the field, as a camera lens does
+    pub fn with_vignetting(&self, vignetting: bool) -> Self {
         Self {
-            half_fov_x: self.half_fov_x * scale,
-            half_fov_y: self.half_fov_y * scale,
+            vignetting,
+            ..self.clone()
         }
     }
+    pub fn vignetting(&self) -> bool {
+        self.vignetting
+    }
     pub fn zoom(&self) -> f32 {
         self.half_fov_x
     }
-    fn can_be_seen(&self, b: &Brightness) -> bool {
-        b.brightness / self.half_fov_x > 0.01f32.powf(0.8)
+    pub fn half_fov(&self) -> (f32, f32) {
+        (self.half_fov_x, self.half_fov_y)
     }
-    pub fn project(&self, star: &Star) -> Fpp {
-        Fpp::new(
-            star[0] / star[2] / self.half_fov_x,
-            star[1] / star[2] / self.half_fov_y,
-        )
+    /// the same field of view showing stars down to magnitude `limit`, or
+    /// as faint as the zoom allows if it is `None`
+    pub fn with_limiting_magnitude(&self, limit: Option<f32>) -> Self {
+        Self {
+            limiting_magnitude: limit,
+            ..self.clone()
+        }
+    }
+    /// the magnitude of the faintest stars shown
+    pub fn limiting_magnitude(&self) -> f32 {
+        self.limiting_magnitude
+            .unwrap_or_else(|| Brightness::new(0.01f32.powf(0.8) * self.half_fov_x).magnitude())
+    }
+    /// whether a star as bright as `b` is bright enough to be seen
+    pub fn can_be_seen(&self, b: &Brightness) -> bool {
+        match self.limiting_magnitude {
+            Some(limit) => b.magnitude() <= limit,
+            None => b.value() / self.half_fov_x > 0.01f32.powf(0.8),
+        }
+    }
+    /// brightness of `star` once dimmed by the cos^4 falloff of the lens, if any
+    fn apparent_brightness(&self, star: &Star, b: &Brightness) -> Brightness {
+        if self.vignetting {
+            let cos_off_axis = (star[2] / star.norm()).max(0.0);
+            Brightness::new(b.value() * cos_off_axis.powi(4))
+        } else {
+            *b
+        }
+    }
+    /// whether the lens can place `star` on the focal plane: a gnomonic one
+    /// only what is ahead, a fisheye all but what is right behind
+    pub fn faces(&self, star: &Camera) -> bool {
+        self.lens.projection().visible(&star.direction())
+    }
+    /// where `star` falls on the focal plane, from -1 to 1 across the field
+    pub fn project(&self, star: &Camera) -> Fpp {
+        let fpp = self.lens.projection().project(&star.direction());
+        let fpp = self.distortion.distort(&fpp);
+        Fpp::new(fpp[0] / self.half_fov_x, fpp[1] / self.half_fov_y)
+    }
+    /// the unit direction, in the frame of the camera, that `project` takes to `fpp`
+    pub fn unproject(&self, fpp: &Fpp) -> Star {
+        let fpp = Fpp::new(fpp[0] * self.half_fov_x, fpp[1] * self.half_fov_y);
+        let fpp = self.distortion.undistort(&fpp);
+        self.lens.projection().unproject(&fpp)
     }
-    pub fn project_sky(&self, sky: &Sky) -> FPStars {
+    /// The unit direction, in the frame of the camera, seen at (`x`, `y`) on a
+    /// screen `width` by `height`, with (0, 0) at the top left corner, as
+    /// when a star is clicked.
+    pub fn screen_to_direction(&self, x: f32, y: f32, width: f32, height: f32) -> Star {
+        self.unproject(&Fpp::new(2.0 * x / width - 1.0, 2.0 * y / height - 1.0))
+    }
+    /// The turn, in the frame of the camera, that takes what is seen at `from`
+    /// on a screen `width` by `height` to `to`, as when the sky is dragged
+    /// with the mouse: about the axis perpendicular to the drag.
+    pub fn drag_rotation(
+        &self,
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        height: f32,
+    ) -> UnitQuaternion<f32> {
+        let from = self.screen_to_direction(from.0, from.1, width, height);
+        let to = self.screen_to_direction(to.0, to.1, width, height);
+        UnitQuaternion::rotation_between(&from, &to).unwrap_or_else(UnitQuaternion::identity)
+    }
+    /// The turn, in the frame of the camera, that keeps what is seen at `at`
+    /// on a screen `width` by `height` in its place when the field of view is
+    /// rescaled by `scale`, as when zooming about the mouse.
+    pub fn zoom_rotation(
+        &self,
+        scale: f32,
+        at: (f32, f32),
+        width: f32,
+        height: f32,
+    ) -> UnitQuaternion<f32> {
+        let before = self.screen_to_direction(at.0, at.1, width, height);
+        let after = self
+            .rescale(scale)
+            .screen_to_direction(at.0, at.1, width, height);
+        UnitQuaternion::rotation_between(&before, &after).unwrap_or_else(UnitQuaternion::identity)
+    }
+    /// The turn, in the frame of the camera, that follows a twist by `angle`
+    /// of a screen `width` by `height` about its center, as when two fingers
+    /// twist the sky: about the boresight, clockwise as the screen is drawn.
+    pub fn twist_rotation(&self, angle: f32, width: f32, height: f32) -> UnitQuaternion<f32> {
+        let (x, y, r) = (width / 2.0, height / 2.0, width.min(height) / 4.0);
+        let from = self.screen_to_direction(x + r, y, width, height);
+        let to = self.screen_to_direction(x + r * angle.cos(), y + r * angle.sin(), width, height);
+        let boresight = Star::z_axis();
+        // how far they are apart about the boresight
+        let turned = boresight
+            .dot(&from.cross(&to))
+            .atan2(from.dot(&to) - from.dot(&boresight) * to.dot(&boresight));
+        UnitQuaternion::from_axis_angle(&boresight, turned)
+    }
+    /// where the stars of `sky` fall on the focal plane of a camera with
+    /// attitude `q`
+    pub fn project_sky<'a>(&self, sky: &'a Sky, q: UnitQuaternion<f32>) -> FPStars<'a> {
         sky.stars
             .iter()
-            .map(|(s, b, n)| (self.project(s), *b, n.clone()))
+            .map(|star| (self.project(&star_camera(star, q)), star))
             .collect()
     }
-    fn in_box(x: f32, y: f32, maxx: u8, maxy: u8) -> Option<(u8, u8)> {
+    fn in_box(x: f32, y: f32, maxx: u32, maxy: u32) -> Option<(u32, u32)> {
         if x < 0.0 || x >= maxx as f32 || y < 0.0 || y >= maxy as f32 {
             None
         } else {
-            Some((x as u8, y as u8))
+            Some((x as u32, y as u32))
         }
     }
-    pub fn to_screen(&self, star: &Star, maxx: u8, maxy: u8) -> Option<(u8, u8)> {
-        if star[2] <= 0.0 {
+    /// where `star` falls on a screen of `maxx` by `maxy` cells or pixels, if it does
+    pub fn to_screen(&self, star: &Camera, maxx: u32, maxy: u32) -> Option<(u32, u32)> {
+        if !self.faces(star) {
             return None;
         }
         let fpp = self.project(star);
@@ -316,23 +1666,139 @@ impl FoV {
         let y = ((fpp[1] + 1.0) / 2.0 * (maxy as f32)).round();
         Self::in_box(x, y, maxx, maxy)
     }
-    pub fn project_sky_to_screen(
+    /// The cosine of the angle from the boresight to a little beyond the
+    /// corners of the field: stars farther from it cannot be seen, so they
+    /// need not be projected.
+    fn cos_field_radius(&self) -> f32 {
+        let corner = Fpp::new(self.half_fov_x, self.half_fov_y) * 1.05;
+        let covers_all = match self.lens {
+            Lens::Gnomonic => false,
+            Lens::Stereographic => corner.norm() >= 1e3,
+            Lens::Orthographic => corner.norm() >= 1.0,
+            Lens::Fisheye => corner.norm() >= PI,
+        };
+        if covers_all || !self.distortion.is_none() {
+            return -1.0;
+        }
+        self.lens.projection().unproject(&corner)[2]
+    }
+    /// where `star` falls on a screen of `maxx` by `maxy` and how bright it
+    /// looks there, from 128 to 255, if it can be seen
+    fn star_to_screen(
         &self,
-        sky: Sky,
-        maxx: u8,
-        maxy: u8,
-    ) -> Vec<Option<(u8, u8, u8, String)>> {
-        sky.stars
-            .iter()
-            .map(|(s, b, n)| {
-                let sp = self.to_screen(s, maxx, maxy);
-                if sp.is_none() || !self.can_be_seen(b) {
-                    None
-                } else {
-                    let sp = sp.unwrap();
-                    let bu = 128 + (b.brightness * 127.0).floor() as u8;
-                    Some((sp.0, sp.1, bu, String::from(n)))
-                }
+        star: &Camera,
+        brightness: &Brightness,
+        cos_radius: f32,
+        maxx: u32,
+        maxy: u32,
+    ) -> Option<(u32, u32, u8)> {
+        let position = star.direction();
+        // cheaper than projecting, for the many stars behind or aside
+        if position[2] < cos_radius * position.norm() {
+            return None;
+        }
+        let (x, y) = self.to_screen(star, maxx, maxy)?;
+        let b = self.apparent_brightness(&position, brightness);
+        self.can_be_seen(&b)
+            .then(|| (x, y, 128 + (b.value() * 127.0).floor() as u8))
+    }
+
+    /// The stars of `sky` that a camera with attitude `q` can see on a screen
+    /// of `maxx` by `maxy`, with where they fall and how bright they look, as
+    /// in `project_sky_to_screen`. Nothing is copied, so it is the one to draw with.
+    pub fn screen_stars<'a>(
+        &'a self,
+        sky: &'a Sky,
+        q: UnitQuaternion<f32>,
+        maxx: u32,
+        maxy: u32,
+    ) -> impl Iterator<Item = (u32, u32, u8, &'a CatalogStar)> + 'a {
+        let cos_radius = self.cos_field_radius();
+        sky.iter().filter_map(move |star| {
+            let (x, y, b) = self.star_to_screen(
+                &star_camera(star, q),
+                &star.brightness(),
+                cos_radius,
+                maxx,
+                maxy,
+            )?;
+            Some((x, y, b, star))
+        })
+    }
+
+    /// The same as `screen_stars`, for the stars of `sky` at `positions`
+    /// instead, as `SkyRotation` turns them. Each star comes as its index in
+    /// `sky`, so that where they are drawn can be kept while nothing moves.
+    pub fn screen_stars_at<'a>(
+        &'a self,
+        sky: &'a [CatalogStar],
+        positions: Cameras<'a>,
+        maxx: u32,
+        maxy: u32,
+    ) -> impl Iterator<Item = (u32, u32, u8, usize)> + 'a {
+        let cos_radius = self.cos_field_radius();
+        sky.iter().enumerate().filter_map(move |(i, star)| {
+            let (x, y, b) = self.star_to_screen(
+                &positions.get(i),
+                &star.brightness(),
+                cos_radius,
+                maxx,
+                maxy,
+            )?;
+            Some((x, y, b, i))
+        })
+    }
+
+    /// Leaves in `drawn` what `screen_stars_at` gives, projecting the stars
+    /// on every core with the `parallel` feature.
+    pub fn screen_stars_into(
+        &self,
+        sky: &[CatalogStar],
+        positions: Cameras<'_>,
+        maxx: u32,
+        maxy: u32,
+        drawn: &mut Vec<(u32, u32, u8, usize)>,
+    ) {
+        drawn.clear();
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            let cos_radius = self.cos_field_radius();
+            drawn.par_extend(sky.par_iter().enumerate().filter_map(|(i, star)| {
+                let (x, y, b) = self.star_to_screen(
+                    &positions.get(i),
+                    &star.brightness(),
+                    cos_radius,
+                    maxx,
+                    maxy,
+                )?;
+                Some((x, y, b, i))
+            }));
+        }
+        #[cfg(not(feature = "parallel"))]
+        drawn.extend(self.screen_stars_at(sky, positions, maxx, maxy));
+    }
+
+    /// where each star of `sky` falls on a screen of `maxx` by `maxy` for a
+    /// camera with attitude `q`, and how bright it looks, if it can be seen
+    pub fn project_sky_to_screen<'a>(
+        &self,
+        sky: &'a Sky,
+        q: UnitQuaternion<f32>,
+        maxx: u32,
+        maxy: u32,
+    ) -> Vec<Option<(u32, u32, u8, &'a CatalogStar)>> {
+        let cos_radius = self.cos_field_radius();
+        sky.iter()
+            .map(|star| {
+                let (x, y, b) = self.star_to_screen(
+                    &star_camera(star, q),
+                    &star.brightness(),
+                    cos_radius,
+                    maxx,
+                    maxy,
+                )?;
+                Some((x, y, b, star))
             })
             .collect()
     }
@@ -341,32 +1807,68 @@ impl FoV {
         Self {
             half_fov_x: x_rad.tan() / 2.0,
             half_fov_y: y_rad.tan() / 2.0,
+            vignetting: false,
+            lens: Lens::Gnomonic,
+            distortion: Distortion::default(),
+            limiting_magnitude: None,
         }
     }
 }
 
+/// `star` as a camera with attitude `q` sees it
+fn star_camera(star: &CatalogStar, q: UnitQuaternion<f32>) -> Camera {
+    Equatorial::new(*star.position()).to_body(q).to_camera()
+}
+
 #[cfg(test)]
 mod test {
     use approx::assert_relative_eq;
-    use regex::Regex;
-    use std::f32::consts::PI;
+    use std::{
+        borrow::Cow,
+        f32::consts::{FRAC_PI_2, PI},
+        sync::Arc,
+    };
 
     use nalgebra::UnitQuaternion;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        boundaries::Boundaries,
+        format::CatalogFormat,
+        frames::{Camera, Equatorial},
+        names::NameStyle,
+        projection::Distortion,
+    };
+
+    use super::{
+        angle_between, attitude_at, constellation, dec_str, direction_at, equatorial_grid,
+        great_circle, julian_year, precess, ra_dec, ra_str, random_quaternion_with, small_circle,
+        spectral_class, Brightness, CatalogStar, FoV, Fpp, Lens, Occluder, Position, Sky, SkyError,
+        SkyRotation, Star,
+    };
+
+    /// `direction` as a camera looking along the axes of the catalog sees it
+    fn camera(direction: Star) -> Camera {
+        Equatorial::new(direction)
+            .to_body(UnitQuaternion::identity())
+            .to_camera()
+    }
 
-    use super::{Brightness, FoV, Fpp, Position, Sky, StBrNm, Star};
+    /// star at right ascension `HHMMSS.S` and declination `+DDMMSS`, as written in the catalogs
+    fn star_at(ra: &str, dec: &str) -> Star {
+        let num = |s: &str| s.parse::<f32>().unwrap();
+        let ra =
+            (num(&ra[0..2]) * 15.0 + num(&ra[2..4]) / 4.0 + num(&ra[4..]) / 240.0).to_radians();
+        let sgn = if dec.starts_with('-') { -1.0 } else { 1.0 };
+        let dec = sgn
+            * (num(&dec[1..3]) + num(&dec[3..5]) / 60.0 + num(&dec[5..7]) / 3600.0).to_radians();
+        Star::new(ra.cos() * dec.cos(), ra.sin() * dec.cos(), dec.sin())
+    }
 
-    fn stars() -> Vec<StBrNm> {
+    fn stars() -> Vec<CatalogStar> {
         vec![
-            (
-                Star::new(0.0, 1.0, 2.0),
-                Brightness::new(0.5),
-                String::from("a"),
-            ),
-            (
-                Star::new(3.0, 4.0, 5.0),
-                Brightness::new(0.25),
-                String::from("b"),
-            ),
+            CatalogStar::new(Star::new(0.0, 1.0, 2.0), Brightness::new(0.5), "a"),
+            CatalogStar::new(Star::new(3.0, 4.0, 5.0), Brightness::new(0.25), "b"),
         ]
     }
     #[test]
@@ -379,29 +1881,260 @@ mod test {
         assert_eq!(
             from_pos.stars,
             vec![
-                (
-                    Star::new(1.0, 3.0, 5.0),
-                    Brightness::new(0.5),
-                    String::from("a")
-                ),
-                (
-                    Star::new(4.0, 6.0, 8.0),
-                    Brightness::new(0.25),
-                    String::from("b")
-                )
+                CatalogStar::new(Star::new(1.0, 3.0, 5.0), Brightness::new(0.5), "a"),
+                CatalogStar::new(Star::new(4.0, 6.0, 8.0), Brightness::new(0.25), "b")
             ]
         );
         let q = UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0);
         let rotated = from_pos.with_attitude(q);
         assert_eq!(rotated.len(), 2);
-        assert!((rotated.stars[0].0 - Star::new(-3.0, 1.0, 5.0)).norm() < 1e-5);
-        assert!((rotated.stars[1].0 - Star::new(-6.0, 4.0, 8.0)).norm() < 1e-5);
+        assert!((rotated.stars[0].position() - Star::new(-3.0, 1.0, 5.0)).norm() < 1e-5);
+        assert!((rotated.stars[1].position() - Star::new(-6.0, 4.0, 8.0)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_catalog_star() {
+        let star = CatalogStar::new(Star::new(0.0, 0.0, 1.0), Brightness::new(1.0), "α CMa");
+        assert_eq!(star.hr(), None);
+        assert_eq!(star.color_index(), None);
+        let star = star.with_hr(2491).with_color_index(0.0);
+        assert_eq!(star.hr(), Some(2491));
+        assert_eq!(star.color_index(), Some(0.0));
+        assert_relative_eq!(star.magnitude(), -1.46);
+        let moved = star.moved_to(Star::new(1.0, 0.0, 0.0));
+        assert_eq!(moved.position(), &Star::new(1.0, 0.0, 0.0));
+        assert_eq!(moved.hr(), Some(2491));
+        assert_eq!(moved.name(), "α CMa");
+        // the name is shared, not copied
+        assert!(Arc::ptr_eq(&moved.name, &star.name));
+        assert!(matches!(
+            moved.display_name(NameStyle::Bayer),
+            Cow::Borrowed("α CMa")
+        ));
+
+        assert_eq!(star.tint(), [0.80, 0.86, 1.0]);
+        let red = star.clone().with_color_index(1.85);
+        assert!(red.tint()[2] < 0.5 && red.tint()[0] == 1.0);
+        let white = CatalogStar::new(Star::new(0.0, 0.0, 1.0), Brightness::new(1.0), "");
+        assert_eq!(white.tint(), [1.0, 1.0, 1.0]);
+        assert_eq!(star.with_color_index(-3.0).tint(), [0.61, 0.71, 1.0]);
+    }
+
+    #[test]
+    fn test_brightness() {
+        assert_eq!(Brightness::from_magnitude(-1.46).value(), 1.0);
+        assert_relative_eq!(Brightness::from_magnitude(3.54).value(), 0.01);
+        assert_relative_eq!(
+            Brightness::from_magnitude(2.0).magnitude(),
+            2.0,
+            epsilon = 1e-5
+        );
+        assert_eq!(Brightness::new(1.5).value(), 1.0);
+        assert_eq!(Brightness::new(-0.5).value(), 0.0);
+        assert_eq!(Brightness::new(f32::NAN).value(), 0.0);
+        // saved, and clamped when read back
+        assert_eq!(serde_json::to_string(&Brightness::new(0.5)).unwrap(), "0.5");
+        let read = |saved| serde_json::from_str::<Brightness>(saved).unwrap().value();
+        assert_eq!(read("0.5"), 0.5);
+        assert_eq!(read("1e9"), 1.0);
+        assert_eq!(read("-3"), 0.0);
+        assert!(Brightness::from_magnitude(1.0) > Brightness::from_magnitude(2.0));
+        assert_eq!(Brightness::new(1.0).glyph_size(), 1.0);
+        assert_eq!(Brightness::from_magnitude(8.0).glyph_size(), 0.0);
+        assert_eq!(Brightness::new(0.0).glyph_size(), 0.0);
+        assert_relative_eq!(
+            Brightness::from_magnitude(2.52).glyph_size(),
+            0.5,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let sky = Sky::from(&stars());
+        assert_eq!(sky[1].name(), "b");
+        assert_eq!(sky.get(0).unwrap().name(), "a");
+        assert!(sky.get(2).is_none());
+        let names: Vec<&str> = sky.iter().map(|star| star.name()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!((&sky).into_iter().count(), 2);
+        let mut doubled = sky.clone();
+        doubled.extend(sky.clone());
+        let names: Vec<String> = doubled
+            .into_iter()
+            .map(|star| star.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "a", "b"]);
+
+        let brightest = Sky::from(&[stars()[1].clone(), stars()[0].clone()]).brightest(1);
+        assert_eq!(brightest.len(), 1);
+        assert_eq!(brightest[0].name(), "a");
+        assert_eq!(sky.brightest(5).len(), 2);
+
+        // "a" has magnitude ~ -0.71 and "b" ~ 0.04
+        assert_eq!(sky.filter_by_magnitude(..0.0).len(), 1);
+        assert_eq!(sky.filter_by_magnitude(0.0..)[0].name(), "b");
+        assert_eq!(sky.filter_by_magnitude(-2.0..=1.0).len(), 2);
+        assert_eq!(sky.magnitude_cut(0.0)[0].name(), "a");
+        assert_eq!(sky.magnitude_cut(-0.5).len(), 1);
+        assert_eq!(sky.filter(|star| star.name() != "a")[0].name(), "b");
+        let faintest_first = Sky::from(&stars()).brightest_of(5);
+        assert_eq!(faintest_first[1].name(), "a");
+    }
+
+    #[test]
+    fn test_mutations() {
+        let mut sky = Sky::from(&stars());
+        sky.push(CatalogStar::new(
+            Star::new(0.0, 0.0, 1.0),
+            Brightness::new(0.75),
+            "c",
+        ));
+        assert_eq!(sky.len(), 3);
+        assert_eq!(sky[2].name(), "c");
+
+        let removed = sky.remove_by_name("a").unwrap();
+        assert_eq!(removed.brightness(), Brightness::new(0.5));
+        assert_eq!(sky.len(), 2);
+        assert!(sky.remove_by_name("a").is_none());
+
+        sky.retain(|star| star.brightness().value() > 0.5);
+        assert_eq!(sky.len(), 1);
+        assert_eq!(sky[0].name(), "c");
+    }
+
+    #[test]
+    fn test_pointing() {
+        let (ra, dec) = ra_dec(&direction_at(83.5, -5.0));
+        assert_relative_eq!(ra, 83.5, epsilon = 1e-4);
+        assert_relative_eq!(dec, -5.0, epsilon = 1e-4);
+
+        assert_eq!(constellation("α Ori"), Some("Ori"));
+        assert_eq!(constellation("  Psc"), Some("Psc"));
+        assert_eq!(constellation("     "), None);
+        assert_eq!(constellation("αa"), None);
+        assert_eq!(constellation("Jupiter"), None);
+
+        let sky = Sky::from(&[
+            CatalogStar::new(direction_at(88.8, 7.4), Brightness::new(0.5), "α Ori"),
+            CatalogStar::new(direction_at(101.3, -16.7), Brightness::new(1.0), "α CMa"),
+            CatalogStar::new(direction_at(90.0, 0.0), Brightness::new(0.1), "     "),
+        ]);
+        assert_eq!(
+            sky.nearest_constellation(&direction_at(90.0, 0.0)),
+            Some("Ori")
+        );
+        assert_eq!(
+            sky.nearest_constellation(&direction_at(100.0, -20.0)),
+            Some("CMa")
+        );
+        assert_eq!(
+            Sky::from(&[]).nearest_constellation(&direction_at(0.0, 0.0)),
+            None
+        );
+
+        let boundaries =
+            Boundaries::parse("5.0 -10.0 ORI\n6.5 -10.0 ORI\n6.5 20.0 ORI\n5.0 20.0 ORI").unwrap();
+        let tagged = sky.with_constellations(&boundaries);
+        assert_eq!(tagged[2].constellation(), Some("Ori"));
+        assert_eq!(tagged[1].constellation(), Some("CMa"));
+        assert_eq!(tagged.in_constellation("ori").len(), 2);
+        assert_eq!(sky.in_constellation("Ori").len(), 1);
+        assert!(sky.in_constellation("UMa").is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        let bsc = Sky::from(&[
+            CatalogStar::new(direction_at(219.9, -60.835), Brightness::new(0.9), "α1Cen"),
+            CatalogStar::new(
+                direction_at(219.896, -60.837),
+                Brightness::new(0.5),
+                "α2Cen",
+            ),
+        ]);
+        let supplement = Sky::from(&[
+            CatalogStar::new(
+                direction_at(219.9002, -60.8351),
+                Brightness::new(0.9),
+                "Rigil",
+            ),
+            CatalogStar::new(direction_at(10.0, 10.0), Brightness::new(0.3), "Krikkit"),
+        ]);
+        let merged = Sky::merge(&[bsc.clone(), supplement]);
+        let names: Vec<&str> = merged.iter().map(|star| star.name()).collect();
+        assert_eq!(names, ["α1Cen", "α2Cen", "Krikkit"]);
+        assert_eq!(Sky::merge(std::slice::from_ref(&bsc)).stars, bsc.stars);
+        assert!(Sky::merge(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_doubles_merged() {
+        let sky = Sky::from(&[
+            CatalogStar::new(direction_at(219.9, -60.835), Brightness::new(0.4), "α1Cen"),
+            CatalogStar::new(
+                direction_at(219.896, -60.837),
+                Brightness::new(0.1),
+                "α2Cen",
+            ),
+            CatalogStar::new(direction_at(201.3, -11.2), Brightness::new(0.3), "α Vir"),
+            CatalogStar::new(direction_at(201.3, -11.0), Brightness::new(0.01), "close"),
+        ]);
+        let merged = sky.with_doubles_merged((1.0f32 / 60.0).to_radians());
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[2].name(), "α1Cen");
+        assert_relative_eq!(merged[2].brightness().value(), 0.5);
+        let expected =
+            (direction_at(219.9, -60.835) * 0.4 + direction_at(219.896, -60.837) * 0.1).normalize();
+        assert_relative_eq!(*merged[2].position(), expected, epsilon = 1e-6);
+        assert_eq!(merged[0].name(), "close");
+        assert_eq!(sky.with_doubles_merged(0.0).len(), 4);
+    }
+
+    #[test]
+    fn test_occluder() {
+        let occluder = Occluder::new(Star::new(0.0, 0.0, 3.0), PI / 6.0);
+        let identity = UnitQuaternion::identity();
+        assert!(occluder.hides(&Star::new(0.1, 0.0, 1.0), identity));
+        assert!(!occluder.hides(&Star::new(1.0, 0.0, 1.0), identity));
+        assert!(!occluder.hides(&Star::new(0.0, 0.0, -1.0), identity));
+        let visible = occluder.occult(&Sky::from(&stars()), identity);
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible.stars[0].name(), "b");
+
+        // turned, the camera has other stars behind the occluder
+        let turned = UnitQuaternion::from_euler_angles(0.0, FRAC_PI_2, 0.0);
+        let ahead = turned.inverse() * Star::new(0.0, 0.0, 1.0);
+        assert!(occluder.hides(&ahead, turned));
+        assert!(!occluder.hides(&Star::new(0.1, 0.0, 1.0), turned));
+        assert!(!occluder.hides(&ahead, identity));
+    }
+
+    #[test]
+    fn test_seeded() {
+        let sky = |seed| Sky::random_with_rng(50, &mut StdRng::seed_from_u64(seed)).stars;
+        assert_eq!(sky(3), sky(3));
+        assert_ne!(sky(3), sky(4));
+        let mut a = StdRng::seed_from_u64(5);
+        let mut b = StdRng::seed_from_u64(5);
+        assert_eq!(
+            Sky::false_stars_with(10, &mut a).stars,
+            Sky::false_stars_with(10, &mut b).stars
+        );
+        let occluder = Occluder::random_with(&mut a);
+        let identity = UnitQuaternion::identity();
+        assert!(occluder.hides(&Occluder::random_with(&mut b).direction, identity));
+        assert_eq!(
+            random_quaternion_with(&mut a),
+            random_quaternion_with(&mut b)
+        );
     }
 
     #[test]
     fn test_fov() {
         let fov = FoV::new(1.0, 2.5);
-        let proj_stars = fov.project_sky(&Sky::from(&stars()));
+        let sky = Sky::from(&stars());
+        let proj_stars = fov.project_sky(&sky, UnitQuaternion::identity());
         assert!((proj_stars[0].0 - Fpp::new(0.0, 0.2)).norm() < 1e-5);
         assert!((proj_stars[1].0 - Fpp::new(0.6, 0.32)).norm() < 1e-5);
     }
@@ -411,17 +2144,17 @@ mod test {
         let sky = Sky::from(&stars());
         let fov = FoV::new(1.0, 1.0);
         let p: Vec<_> = fov
-            .project_sky_to_screen(sky.clone(), 60, 60)
+            .project_sky_to_screen(&sky, UnitQuaternion::identity(), 60, 60)
             .into_iter()
             .flatten()
             .collect();
         assert_eq!(p.len(), 2);
-        let (a, b) = (p.get(0).unwrap(), p.get(1).unwrap());
+        let (a, b) = (&p[0], &p[1]);
         assert_eq!((a.0, a.1), (30, 45));
         assert_eq!((b.0, b.1), (48, 54));
 
         let p: Vec<_> = FoV::new(0.5, 0.51)
-            .project_sky_to_screen(sky.clone(), 60, 60)
+            .project_sky_to_screen(&sky, UnitQuaternion::identity(), 60, 60)
             .into_iter()
             .flatten()
             .collect();
@@ -430,30 +2163,590 @@ mod test {
         assert_eq!((a.0, a.1), (30, 59));
 
         let p: Vec<_> = FoV::new(0.5, 0.5)
-            .project_sky_to_screen(sky.clone(), 60, 60)
+            .project_sky_to_screen(&sky, UnitQuaternion::identity(), 60, 60)
             .into_iter()
             .flatten()
             .collect();
         assert_eq!(p.len(), 0);
 
+        let turned = UnitQuaternion::from_euler_angles(0.0, 0.0, PI);
         let p: Vec<_> = FoV::new(0.5, 0.5)
-            .project_sky_to_screen(
-                sky.with_attitude(UnitQuaternion::from_euler_angles(0.0, 0.0, PI)),
-                60,
-                60,
-            )
+            .project_sky_to_screen(&sky, turned, 60, 60)
             .into_iter()
             .flatten()
             .collect();
         assert_eq!(p.len(), 1);
         let a = p.first().unwrap();
         assert_eq!((a.0, a.1), (30, 0));
+
+        // screens larger than 255 cells or pixels
+        let p: Vec<_> = FoV::new(1.0, 1.0)
+            .project_sky_to_screen(&sky, UnitQuaternion::identity(), 3840, 2160)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!((p[0].0, p[0].1), (1920, 1620));
+        assert_eq!((p[1].0, p[1].1), (3072, 1944));
+    }
+
+    #[test]
+    fn test_vignetting() {
+        let sky = Sky::from(&stars());
+        let fov = FoV::new(1.0, 1.0);
+        let plain: Vec<_> = fov
+            .project_sky_to_screen(&sky, UnitQuaternion::identity(), 60, 60)
+            .into_iter()
+            .flatten()
+            .collect();
+        let dimmed: Vec<_> = fov
+            .with_vignetting(true)
+            .project_sky_to_screen(&sky, UnitQuaternion::identity(), 60, 60)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(dimmed.len(), 2);
+        assert!(dimmed[0].2 < plain[0].2);
+        assert!(dimmed[1].2 < plain[1].2);
+
+        let on_axis = Sky::from(&[CatalogStar::new(
+            Star::new(0.0, 0.0, 1.0),
+            Brightness::new(0.5),
+            "c",
+        )]);
+        assert_eq!(
+            fov.with_vignetting(true).project_sky_to_screen(
+                &on_axis,
+                UnitQuaternion::identity(),
+                60,
+                60
+            ),
+            fov.project_sky_to_screen(&on_axis, UnitQuaternion::identity(), 60, 60)
+        );
+        assert!(fov.with_vignetting(true).rescale(0.5).vignetting());
+    }
+
+    #[test]
+    fn test_unproject() {
+        let distorted = FoV::new(1.0, 1.0).with_distortion(Distortion::new(-0.1, 0.01, 0.0));
+        for fov in [FoV::new(1.0, 2.5), FoV::new(0.5, 0.25).all_sky(), distorted] {
+            for star in stars() {
+                let direction = star.position().normalize();
+                let back = fov.unproject(&fov.project(&camera(direction)));
+                assert_relative_eq!(back, direction, epsilon = 1e-5);
+            }
+        }
+        let fov = FoV::new(1.0, 0.5);
+        let center = fov.screen_to_direction(400.0, 300.0, 800.0, 600.0);
+        assert_relative_eq!(center, Star::new(0.0, 0.0, 1.0));
+        // the corners are at the edges of the field of view
+        let corner = fov.screen_to_direction(800.0, 0.0, 800.0, 600.0);
+        assert_relative_eq!(corner, Star::new(1.0, -0.5, 1.0).normalize());
+        let clicked = fov.screen_to_direction(100.0, 30.0, 255.0, 255.0);
+        assert_eq!(fov.to_screen(&camera(clicked), 255, 255), Some((100, 30)));
+    }
+
+    #[test]
+    fn test_drag_rotation() {
+        let fov = FoV::new(1.0, 0.5);
+        let q = UnitQuaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let star = q.inverse() * fov.screen_to_direction(200.0, 100.0, 800.0, 600.0);
+        // the star under the mouse stays under it
+        let drag = fov.drag_rotation((200.0, 100.0), (500.0, 420.0), 800.0, 600.0);
+        assert_eq!(
+            fov.to_screen(&camera(drag * q * star), 800, 600),
+            Some((500, 420))
+        );
+        let still = fov.drag_rotation((200.0, 100.0), (200.0, 100.0), 800.0, 600.0);
+        assert_relative_eq!(still.angle(), 0.0);
+    }
+
+    #[test]
+    fn test_zoom_rotation() {
+        let fov = FoV::new(1.0, 0.5);
+        let q = UnitQuaternion::from_euler_angles(-0.7, 0.4, 1.1);
+        let star = q.inverse() * fov.screen_to_direction(640.0, 150.0, 800.0, 600.0);
+        // zooming in or out, the star under the mouse stays under it
+        for scale in [0.8, 1.25] {
+            let turn = fov.zoom_rotation(scale, (640.0, 150.0), 800.0, 600.0);
+            let zoomed = fov.rescale(scale);
+            assert_eq!(
+                zoomed.to_screen(&camera(turn * q * star), 800, 600),
+                Some((640, 150))
+            );
+        }
+        // about the center it is the plain zoom
+        let center = fov.zoom_rotation(0.8, (400.0, 300.0), 800.0, 600.0);
+        assert_relative_eq!(center.angle(), 0.0);
+    }
+
+    #[test]
+    fn test_twist_rotation() {
+        let fov = FoV::new(1.0, 1.0);
+        let star = fov.screen_to_direction(400.0, 300.0, 600.0, 600.0);
+        // what was under the finger stays under it as it twists about the center
+        for angle in [0.5f32, -1.2] {
+            let turn = fov.twist_rotation(angle, 600.0, 600.0);
+            let (x, y) = fov.to_screen(&camera(turn * star), 600, 600).unwrap();
+            assert!((x as f32 - (300.0 + 100.0 * angle.cos())).abs() <= 1.0);
+            assert!((y as f32 - (300.0 + 100.0 * angle.sin())).abs() <= 1.0);
+            assert_relative_eq!(turn.angle(), angle.abs(), epsilon = 1e-5);
+        }
+        assert_relative_eq!(fov.twist_rotation(0.0, 800.0, 600.0).angle(), 0.0);
+    }
+
+    #[test]
+    fn test_limiting_magnitude() {
+        let sky = Sky::from(&[
+            CatalogStar::new(
+                Star::new(0.0, 0.0, 1.0),
+                Brightness::from_magnitude(1.0),
+                "a",
+            ),
+            CatalogStar::new(
+                Star::new(0.1, 0.0, 1.0),
+                Brightness::from_magnitude(4.0),
+                "b",
+            ),
+        ]);
+        let seen = |fov: &FoV| {
+            fov.project_sky_to_screen(&sky, UnitQuaternion::identity(), 60, 60)
+                .into_iter()
+                .flatten()
+                .count()
+        };
+        // as deep as the zoom allows
+        let fov = FoV::new(0.5, 0.5);
+        assert_relative_eq!(fov.limiting_magnitude(), 3.29, epsilon = 1e-2);
+        assert_eq!(seen(&fov), 1);
+        assert_eq!(seen(&fov.rescale(0.5)), 2);
+        // or as deep as asked, whatever the zoom
+        let deep = fov.with_limiting_magnitude(Some(4.5));
+        assert_eq!(deep.limiting_magnitude(), 4.5);
+        assert_eq!(seen(&deep), 2);
+        assert_eq!(seen(&deep.rescale(10.0)), 2);
+        assert_eq!(
+            seen(&fov.rescale(0.5).with_limiting_magnitude(Some(0.5))),
+            0
+        );
+    }
+
+    #[test]
+    fn test_nearest_star() {
+        let sky = Sky::from(&[
+            CatalogStar::new(
+                Star::new(0.0, 0.0, 1.0),
+                Brightness::from_magnitude(1.0),
+                "a",
+            ),
+            CatalogStar::new(
+                Star::new(0.1, 0.0, 1.0).normalize(),
+                Brightness::from_magnitude(4.0),
+                "b",
+            ),
+        ]);
+        let fov = FoV::new(0.5, 0.5).with_limiting_magnitude(Some(4.5));
+        let clicked = fov.screen_to_direction(70.0, 50.0, 100.0, 100.0);
+        let (nearest, angle) = sky.nearest_star(&clicked, &fov).unwrap();
+        assert_eq!(sky[nearest].name(), "b");
+        assert_relative_eq!(angle, angle_between(&clicked, sky[1].position()));
+        // faint stars that cannot be seen are not picked
+        let shallow = fov.with_limiting_magnitude(Some(2.0));
+        assert_eq!(sky.nearest_star(&clicked, &shallow).unwrap().0, 0);
+        assert!(Sky::from(&[]).nearest_star(&clicked, &fov).is_none());
+    }
+
+    #[test]
+    fn test_find() {
+        let sky = Sky::from(&[
+            CatalogStar::new(
+                Star::z_axis().into_inner(),
+                Brightness::from_magnitude(0.5),
+                "α Ori",
+            ),
+            CatalogStar::new(
+                Star::x_axis().into_inner(),
+                Brightness::from_magnitude(1.6),
+                "γ Ori",
+            ),
+            CatalogStar::new(
+                Star::y_axis().into_inner(),
+                Brightness::from_magnitude(2.2),
+                "δ Ori",
+            ),
+        ]);
+        assert_eq!(sky.find("betelgeuse"), Some(0));
+        assert_eq!(sky.find(" δ ORI "), Some(2));
+        // the brightest of those starting so
+        assert_eq!(sky.find("γ"), Some(1));
+        assert_eq!(sky.find("Bell"), Some(1));
+        assert_eq!(sky.find("Rigel"), None);
+        assert_eq!(sky.find(""), None);
+    }
+
+    #[test]
+    fn test_circles() {
+        let pole = direction_at(30.0, 40.0);
+        let circle = great_circle(&pole, 36);
+        assert_eq!(circle.len(), 36);
+        for point in &circle {
+            assert_relative_eq!(angle_between(point, &pole), PI / 2.0, epsilon = 1e-5);
+        }
+        let circle = small_circle(&pole, 0.1, 12);
+        for point in &circle {
+            assert_relative_eq!(point.norm(), 1.0, epsilon = 1e-6);
+            assert_relative_eq!(angle_between(point, &pole), 0.1, epsilon = 1e-3);
+        }
+
+        let grid = equatorial_grid(15.0, 10.0, 72);
+        // 12 meridians and 17 parallels
+        assert_eq!(grid.len(), 29);
+        assert!(grid[3]
+            .iter()
+            .all(|p| p.xy().norm() < 1e-6 || (ra_dec(p).0 - 45.0).abs() % 180.0 < 1e-2));
+        let (_, dec) = ra_dec(&grid[12][5]);
+        assert_relative_eq!(dec, -80.0, epsilon = 1e-3);
+        let (_, dec) = ra_dec(&grid[20][0]);
+        assert_relative_eq!(dec, 0.0, epsilon = 1e-3);
+        assert_eq!(equatorial_grid(0.0, 0.0, 10).len(), 0);
+    }
+
+    #[test]
+    fn test_attitude_at() {
+        for (ra, dec) in [(0.0, 0.0), (83.0, -5.0), (200.0, 60.0), (10.0, 90.0)] {
+            let q = attitude_at(ra, dec, 0.0);
+            let center = q * direction_at(ra, dec);
+            assert_relative_eq!(center, Star::z_axis().into_inner(), epsilon = 1e-5);
+            if dec < 90.0 {
+                let north = q * direction_at(ra, dec + 1.0);
+                assert!(north[1] < 0.0 && north[0].abs() < 1e-5);
+                let east = q * direction_at(ra + 1.0, dec);
+                assert!(east[0] < 0.0 && east[1].abs() < 1e-3);
+                let north = attitude_at(ra, dec, 90.0) * direction_at(ra, dec + 1.0);
+                assert!(north[0] > 0.0 && north[1].abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_culling() {
+        let sky = Sky::random_with_rng(20_000, &mut StdRng::seed_from_u64(3));
+        let fovs = [
+            FoV::new(0.2, 0.1),
+            FoV::new(1.0, 1.0).with_aspect(1600.0, 900.0),
+            FoV::new(0.5, 0.5).all_sky(),
+            FoV::new(0.5, 0.5).with_lens(Lens::Orthographic),
+            FoV::new(0.5, 0.5).with_lens(Lens::Stereographic),
+            FoV::new(0.5, 0.5).with_distortion(Distortion::new(-0.2, 0.0, 0.0)),
+        ];
+        for fov in fovs {
+            let seen = fov.project_sky_to_screen(&sky, UnitQuaternion::identity(), 320, 200);
+            // the same as projecting every star
+            for (star, seen) in sky.iter().zip(seen) {
+                let b = fov.apparent_brightness(star.position(), &star.brightness());
+                let expected = fov
+                    .to_screen(&camera(*star.position()), 320, 200)
+                    .filter(|_| fov.can_be_seen(&b));
+                assert_eq!(seen.map(|(x, y, _, _)| (x, y)), expected);
+            }
+        }
+        let whole_sky = FoV::new(3.0, 3.0).with_lens(Lens::Fisheye);
+        assert_eq!(whole_sky.cos_field_radius(), -1.0);
+        assert!(FoV::new(0.1, 0.1).cos_field_radius() > 0.98);
+    }
+
+    #[test]
+    fn test_screen_stars() {
+        let sky = Sky::random_with_rng(2000, &mut StdRng::seed_from_u64(4));
+        let fov = FoV::new(0.3, 0.2).with_limiting_magnitude(Some(12.0));
+        let seen: Vec<_> = fov.project_sky_to_screen(&sky, UnitQuaternion::identity(), 320, 200);
+        let drawn: Vec<_> = fov
+            .screen_stars(&sky, UnitQuaternion::identity(), 320, 200)
+            .collect();
+        assert!(!drawn.is_empty());
+        assert_eq!(drawn.len(), seen.iter().flatten().count());
+        for (drawn, seen) in drawn.into_iter().zip(seen.into_iter().flatten()) {
+            assert_eq!(drawn, seen);
+        }
+    }
+
+    #[test]
+    fn test_sky_rotation() {
+        let q = UnitQuaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let big = Sky::random_with_rng(300, &mut StdRng::seed_from_u64(5));
+        let small = Sky::random_with_rng(100, &mut StdRng::seed_from_u64(6));
+        let mut rotation = SkyRotation::default();
+        // the buffers only grow, so a smaller sky uses part of them
+        for sky in [&small, &big, &small] {
+            let rotated = rotation.rotate(&sky.stars, q);
+            assert_eq!(rotated.len(), sky.len());
+            for (i, star) in sky.with_attitude(q).iter().enumerate() {
+                assert_relative_eq!(rotated.get(i).direction(), *star.position(), epsilon = 1e-6);
+            }
+        }
+        assert!(rotation.rotate(&[], q).is_empty());
+
+        let fov = FoV::new(0.5, 0.4).with_limiting_magnitude(Some(12.0));
+        let positions = rotation.rotate(&big.stars, q);
+        let at: Vec<_> = fov
+            .screen_stars_at(&big.stars, positions, 320, 200)
+            .collect();
+        let turned: Vec<_> = fov.screen_stars(&big, q, 320, 200).collect();
+        assert!(!at.is_empty());
+        assert_eq!(at.len(), turned.len());
+        for ((x, y, b, i), (tx, ty, tb, turned)) in at.iter().zip(turned) {
+            assert_eq!((*x, *y, *b, big[*i].name()), (tx, ty, tb, turned.name()));
+        }
+        // the same, in parallel or not, and leaving nothing from before
+        let mut drawn = vec![(0, 0, 0, 0); 3];
+        let positions = rotation.rotate(&big.stars, q);
+        fov.screen_stars_into(&big.stars, positions, 320, 200, &mut drawn);
+        assert_eq!(drawn, at);
+    }
+
+    #[test]
+    fn test_aspect() {
+        let fov = FoV::new(2.0, 1.0).with_vignetting(true);
+        assert_eq!(fov.with_aspect(1600.0, 900.0).half_fov(), (16.0 / 9.0, 1.0));
+        assert_eq!(fov.with_aspect(900.0, 1800.0).half_fov(), (1.0, 2.0));
+        assert_eq!(fov.with_aspect(0.0, 900.0).half_fov(), (2.0, 1.0));
+        assert!(fov.with_aspect(1600.0, 900.0).vignetting());
+        // a circle around the boresight is as wide as it is tall
+        let wide = fov.with_aspect(1600.0, 900.0);
+        let (x, _) = wide
+            .to_screen(&camera(direction_at(0.0, 60.0)), 1600, 900)
+            .unwrap();
+        let (_, y) = wide
+            .to_screen(&camera(direction_at(90.0, 60.0)), 1600, 900)
+            .unwrap();
+        assert_eq!((x - 800, y - 450), (260, 260));
+    }
+
+    #[test]
+    fn test_fisheye() {
+        let fov = FoV::new(0.5, 0.25).all_sky();
+        assert_eq!(fov.lens(), Lens::Fisheye);
+        assert_relative_eq!(fov.half_fov().1, PI / 2.0);
+        assert_relative_eq!(fov.half_fov().0, PI);
+        // the distance from the center is the angle from the boresight
+        let horizon = Star::new(0.0, 1.0, 0.0);
+        assert_relative_eq!(fov.project(&camera(horizon)), Fpp::new(0.0, 1.0));
+        let tilted = direction_at(0.0, 60.0);
+        assert_relative_eq!(
+            fov.project(&camera(tilted)),
+            Fpp::new(1.0 / 6.0, 0.0),
+            epsilon = 1e-6
+        );
+        assert_eq!(fov.project(&camera(Star::new(0.0, 0.0, 2.0))), Fpp::zeros());
+        // a hemisphere across the shorter side, and beyond it across the longer one
+        assert_eq!(fov.to_screen(&camera(horizon), 60, 60), None);
+        assert!(fov
+            .to_screen(&camera(Star::new(0.0, 1.0, 0.05)), 60, 60)
+            .is_some());
+        assert!(fov
+            .to_screen(&camera(Star::new(1.0, 0.0, -0.5)), 60, 60)
+            .is_some());
+        assert!(!fov.faces(&camera(Star::new(0.0, 0.0, -1.0))));
+        assert!(!FoV::new(1.0, 1.0).faces(&camera(Star::new(1.0, 0.0, -0.5))));
+    }
+
+    #[test]
+    fn test_proper_motion() {
+        // Barnard's star: 10.3"/yr, mostly to the north
+        let barnard = CatalogStar::new(direction_at(269.45, 4.69), Brightness::new(0.01), "")
+            .with_proper_motion(-0.80, 10.36);
+        let sky = Sky::from(std::slice::from_ref(&barnard));
+        let moved = sky.at_epoch(2_451_545.0 + 100.0 * 365.25);
+        let (ra, dec) = ra_dec(moved[0].position());
+        assert_relative_eq!(dec, 4.69 + 1036.0 / 3600.0, epsilon = 1e-3);
+        assert_relative_eq!(
+            ra,
+            269.45 - 80.0 / 3600.0 / 4.69f32.to_radians().cos(),
+            epsilon = 1e-3
+        );
+        assert_eq!(sky.at_epoch(2_451_545.0)[0].position(), barnard.position());
+
+        let still = CatalogStar::new(direction_at(10.0, 10.0), Brightness::new(0.5), "");
+        assert_eq!(still.moved_by(1000.0), still);
+        assert_eq!(julian_year(2_451_545.0), 2000.0);
+
+        let precessed = Sky::from(std::slice::from_ref(&still)).precessed(2000.0, 2050.0);
+        assert_eq!(
+            precessed[0].position(),
+            &precess(still.position(), 2000.0, 2050.0)
+        );
+    }
+
+    #[test]
+    fn test_precession() {
+        let star = star_at("000000.0", "+000000");
+        let moved = precess(&star, 2000.0, 2050.0);
+        // about 3.07 seconds of right ascension and 20 arcseconds of declination per year
+        assert_eq!(ra_str(moved), "000233.8");
+        assert_eq!(dec_str(moved), "+001642");
+        let back = precess(&moved, 2050.0, 2000.0);
+        assert!((back - star).norm() < 1e-5);
+
+        let betelgeuse = star_at("055510.3", "+072425");
+        assert_eq!(ra_str(betelgeuse), "055510.3");
+        assert_eq!(dec_str(betelgeuse), "+072425");
+        assert_eq!(precess(&betelgeuse, 2000.0, 2000.0), betelgeuse);
+    }
+
+    #[test]
+    fn test_edge_case_lines() {
+        let bsc = CatalogFormat::bsc();
+        // HR 92 (a globular cluster) has neither position nor magnitude
+        let no_position = "  92          BD-72 1520   1947                     ";
+        assert!(Sky::from_line(no_position, &bsc).is_err());
+
+        let bet_line = "2061 58Alp OriBD+07 1055  39801113271 224I   4506  Alp Ori  054945.4+072319055510.3+072425199.79-08.96 0.50  +1.85 +2.06 +1.28   M1-2Ia-Iab        e+0.026+0.009 +.005+021SB         9.9 174.4AE   6*";
+        let blank_mag = bet_line.replace("-08.96 0.50", "-08.96     ");
+        assert!(Sky::from_line(&blank_mag, &bsc).is_err());
+
+        let no_seconds = bet_line.replace("055510.3+072425", "055510.3+0724  ");
+        let star = *Sky::from_line(&no_seconds, &bsc).unwrap().position();
+        let exp = star_at("055510.3", "+072400");
+        (0..3).for_each(|i| assert_relative_eq!(star[i], exp[i], epsilon = f32::EPSILON));
+    }
+
+    #[test]
+    fn test_convert_edge_cases() {
+        let bet_line = "2061 58Alp OriBD+07 1055  39801113271 224I   4506  Alp Ori  054945.4+072319055510.3+072425199.79-08.96 0.50  +1.85 +2.06 +1.28   M1-2Ia-Iab        e+0.026+0.009 +.005+021SB         9.9 174.4AE   6*";
+        let nova = bet_line.replace("58Alp Ori", "  Nov Ori");
+        let blank_mag = bet_line.replace("-08.96 0.50", "-08.96     ");
+        let dir = std::env::temp_dir();
+        let infile = dir.join("cuyat_edge_cases.dat");
+        let outfile = dir.join("cuyat_edge_cases.csv");
+        std::fs::write(&infile, [nova, blank_mag, String::from("  92")].join("\n")).unwrap();
+        Sky::convert_catalog_file(infile.to_str().unwrap(), outfile.to_str().unwrap(), 6.5)
+            .unwrap();
+        let converted = std::fs::read_to_string(&outfile).unwrap();
+        assert_eq!(
+            converted,
+            "# epoch 2000.0\n  Ori,055510.3,+072425,0.50,1.85,M,0.026,0.009"
+        );
+    }
+
+    #[test]
+    fn test_gaia() {
+        let content = "source_id,phot_g_mean_mag,dec,ra\n\
+                       1,5.5,7.0,90.0\n\
+                       2,,10.0,10.0\n\
+                       3,-1.0,-16.7,101.3";
+        let gaia = CatalogFormat::gaia();
+        let (sky, skipped) = Sky::from_str_with_format(content, &gaia).unwrap();
+        let sky = sky.brightest_of(10);
+        assert_eq!(sky.len(), 2);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(
+            skipped[0].to_string(),
+            "line 3: no star in \"2,,10.0,10.0\""
+        );
+        assert_eq!(sky[1].brightness(), Brightness::from_magnitude(-1.0));
+        assert_eq!(sky[0].position(), &direction_at(90.0, 7.0));
+        assert_eq!(sky[0].name(), "");
+
+        let sky = sky.brightest_of(1);
+        assert_eq!(sky[0].brightness(), Brightness::from_magnitude(-1.0));
+
+        assert!(matches!(
+            Sky::from_str_with_format("ra,phot_g_mean_mag\n1,2", &gaia),
+            Err(SkyError::MissingColumn(c)) if c == "dec"
+        ));
+    }
+
+    #[test]
+    fn test_bad_lines() {
+        let dir = std::env::temp_dir();
+        let fname = dir.join("cuyat_bad_lines.csv");
+        let content =
+            "# epoch 2000.0\nα Ori,055510.3,+072425,0.50\ngarbage\nα CMa,064508.9,-164258,-1.46";
+        std::fs::write(&fname, content).unwrap();
+        let (sky, skipped) = Sky::from_converted_file(fname.to_str().unwrap(), 10).unwrap();
+        assert_eq!(sky.len(), 2);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].to_string(), "line 3: no star in \"garbage\"");
+
+        std::fs::write(&fname, "# epoch 2000.0\ngarbage").unwrap();
+        let fname = fname.to_str().unwrap();
+        assert!(matches!(
+            Sky::from_converted_file(fname, 10),
+            Err(SkyError::Empty)
+        ));
+        let missing = dir.join("cuyat_no_such_catalog.csv");
+        let missing = missing.to_str().unwrap();
+        assert!(matches!(
+            Sky::from_converted_file(missing, 10),
+            Err(SkyError::Io(_))
+        ));
+        let (sky, fallback) = Sky::new(&Some(missing.to_string()), 10);
+        assert_eq!(sky.len(), 10);
+        assert!(fallback.unwrap().starts_with("cannot read the catalog"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Write;
+
+        let fname = std::env::temp_dir().join("cuyat_gzip.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&fname).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder
+            .write_all(
+                "# epoch 2000.0\nα Ori,055510.3,+072425,0.50\nα CMa,064508.9,-164258,-1.46"
+                    .as_bytes(),
+            )
+            .unwrap();
+        encoder.finish().unwrap();
+        let (sky, skipped) = Sky::from_converted_file(fname.to_str().unwrap(), 10).unwrap();
+        assert_eq!(sky.len(), 2);
+        assert!(skipped.is_empty());
+        assert_eq!(sky[1].name(), "α CMa");
+    }
+
+    #[test]
+    fn test_validate_catalog() {
+        let fname = std::env::temp_dir().join("cuyat_validate.csv");
+        let content = "# epoch 2000.0\n\
+                       α Ori,055510.3,+072425,0.50\n\
+                       garbage\n\
+                       α Ori,064508.9,-164258,-1.46\n\
+                         Ori,055510.3,+072425,5.0\n\
+                         Ori,055510.3,+072425,45.0\n\
+                       Nowhere,000000.0,+000000,nan";
+        std::fs::write(&fname, content).unwrap();
+        let fname = fname.to_str().unwrap();
+        let format = Sky::catalog_format(fname).unwrap();
+        let report = Sky::validate_catalog(fname, &format).unwrap();
+        assert_eq!(report.stars, 5);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.out_of_range.len(), 2);
+        assert_eq!(report.out_of_range[0], (6, 45.0));
+        assert_eq!(report.duplicates, ["α Ori"]);
+        assert!(!report.is_clean());
+        assert!(report.to_string().starts_with("5 stars\nline 3: no star"));
+    }
+
+    #[cfg(feature = "embedded")]
+    #[test]
+    fn test_embedded() {
+        let sky = Sky::embedded(100);
+        assert_eq!(sky.len(), 100);
+        assert_eq!(sky[99].name(), "α CMa");
+        let missing = std::env::temp_dir().join("cuyat_no_such_catalog.csv");
+        let missing = Some(missing.to_string_lossy().into_owned());
+        let (fallback, why) = Sky::new(&missing, 100);
+        assert_eq!(fallback.stars, sky.stars);
+        assert!(why.unwrap().ends_with("using the embedded catalog"));
     }
 
     #[test]
     fn test_from_line() {
-        let sbn_re = Regex::new("^.{7}(.{7}).{61}(\\d\\d)(\\d\\d)(\\d\\d\\.\\d)([+-])(\\d\\d)(\\d\\d)(\\d\\d).{12}([+ -])([0-9. ]{4})").unwrap();
-        let sbn_re_conv = Regex::new("^(.{5}),(\\d\\d)(\\d\\d)(\\d\\d\\.\\d),([+-])(\\d\\d)(\\d\\d)(\\d\\d),(-?)([0-9. ]{4})").unwrap();
+        let bsc = CatalogFormat::bsc();
+        let converted = CatalogFormat::converted();
 
         let bet_line = "2061 58Alp OriBD+07 1055  39801113271 224I   4506  Alp Ori  054945.4+072319055510.3+072425199.79-08.96 0.50  +1.85 +2.06 +1.28   M1-2Ia-Iab        e+0.026+0.009 +.005+021SB         9.9 174.4AE   6*";
         let bet_line_conv = "α Ori,055510.3,+072425,0.50";
@@ -461,24 +2754,37 @@ mod test {
         let sir_line = "2491  9Alp CMaBD-16 1591  48915151881 257I   5423           064044.6-163444064508.9-164258227.22-08.88-1.46   0.00 -0.05 -0.03   A1Vm               -0.553-1.205 +.375-008SBO    13 10.3  11.2AB   4*";
         let sir_line_conv = "α CMa,064508.9,-164258,-1.46";
 
-        let betelgeuse = Sky::from_line(bet_line, &sbn_re);
-        let bet_conv = Sky::from_line(bet_line_conv, &sbn_re_conv);
+        let betelgeuse = Sky::from_line(bet_line, &bsc).unwrap();
+        let bet_conv = Sky::from_line(bet_line_conv, &converted).unwrap();
         let exp_bet = Star::new(0.0208902, 0.9914355, 0.1289158);
-        (0..3)
-            .for_each(|i| assert_relative_eq!(betelgeuse.0[i], exp_bet[i], epsilon = f32::EPSILON));
-        assert_eq!(betelgeuse.1, Brightness::for_magnitude(0.5));
-        assert_eq!(betelgeuse.2, "Alp Ori");
-        assert_eq!(bet_conv.0, betelgeuse.0);
-        assert_eq!(bet_conv.1, betelgeuse.1);
-
-        let sirius = Sky::from_line(sir_line, &sbn_re);
-        let sir_conv = Sky::from_line(sir_line_conv, &sbn_re_conv);
+        (0..3).for_each(|i| {
+            assert_relative_eq!(betelgeuse.position()[i], exp_bet[i], epsilon = f32::EPSILON)
+        });
+        assert_eq!(betelgeuse.brightness(), Brightness::from_magnitude(0.5));
+        assert_eq!(betelgeuse.name(), "Alp Ori");
+        assert_eq!(betelgeuse.color_index(), Some(1.85));
+        assert_eq!(betelgeuse.spectral_class(), Some('M'));
+        assert_eq!(betelgeuse.class_tint(), [1.0, 0.5, 0.4]);
+        assert_eq!(betelgeuse.proper_motion(), Some((0.026, 0.009)));
+        assert!(betelgeuse.is_variable());
+        assert!(!Sky::from_line(sir_line, &bsc).unwrap().is_variable());
+        assert_eq!(bet_conv.position(), betelgeuse.position());
+        assert_eq!(bet_conv.brightness(), betelgeuse.brightness());
+
+        let sirius = Sky::from_line(sir_line, &bsc).unwrap();
+        let sir_conv = Sky::from_line(sir_line_conv, &converted).unwrap();
         let exp_sir = Star::new(-0.18745413, 0.93921775, -0.2876299);
 
-        (0..3).for_each(|i| assert_relative_eq!(sirius.0[i], exp_sir[i], epsilon = f32::EPSILON));
-        assert_eq!(sirius.1, Brightness::for_magnitude(-1.46));
-        assert_eq!(sirius.2, "Alp CMa");
-        assert_eq!(sir_conv.0, sirius.0);
-        assert_eq!(sir_conv.1, sirius.1);
+        (0..3).for_each(|i| {
+            assert_relative_eq!(sirius.position()[i], exp_sir[i], epsilon = f32::EPSILON)
+        });
+        assert_eq!(sirius.brightness(), Brightness::from_magnitude(-1.46));
+        assert_eq!(sirius.name(), "Alp CMa");
+        assert_eq!(sir_conv.position(), sirius.position());
+        assert_eq!(sir_conv.brightness(), sirius.brightness());
+        assert_eq!(sirius.spectral_class(), Some('A'));
+        assert_eq!(sir_conv.spectral_class(), None);
+        assert_eq!(spectral_class("gK0"), Some('K'));
+        assert_eq!(spectral_class(""), None);
     }
 }
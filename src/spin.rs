@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+/// how long a key keeps its axis turning after it is pressed, in seconds:
+/// longer than terminals wait before repeating a key held down
+const HOLD: f32 = 0.6;
+/// how fast an axis starts turning, in steps per second
+const START_RATE: f32 = 2.0;
+/// the fastest an axis turns, in steps per second
+const MAX_RATE: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct AxisSpin {
+    /// which way it turns, 1 or -1
+    sign: f32,
+    /// in steps per second
+    rate: f32,
+    /// the seconds left until it stops, unless its key comes again
+    held: f32,
+}
+
+/// Continuous turns for a terminal, which tells when a key is pressed, and
+/// again and again while it is held, but not when it is released: each of
+/// pitch, yaw and roll turns while its key keeps coming, ever faster.
+#[derive(Clone, Debug, Default)]
+pub struct Spin {
+    axes: [Option<AxisSpin>; 3],
+}
+
+impl Spin {
+    /// A key turning `axis` (0 for pitch, 1 for yaw, 2 for roll) the way of
+    /// `sign` came: the axis keeps turning, or it starts, slowly, if it was
+    /// still or turning the other way.
+    pub fn press(&mut self, axis: usize, sign: f32) {
+        match &mut self.axes[axis] {
+            Some(spin) if spin.sign == sign => spin.held = HOLD,
+            other => {
+                *other = Some(AxisSpin {
+                    sign,
+                    rate: START_RATE,
+                    held: HOLD,
+                })
+            }
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.axes = [None; 3];
+    }
+
+    pub fn is_spinning(&self) -> bool {
+        self.axes.iter().any(Option::is_some)
+    }
+
+    /// The steps each axis turned in `elapsed`, while its key was held,
+    /// turning `acceleration` steps per second faster every second.
+    pub fn advance(&mut self, elapsed: Duration, acceleration: f32) -> [f32; 3] {
+        let elapsed = elapsed.as_secs_f32();
+        let mut steps = [0.0; 3];
+        for (axis, steps) in self.axes.iter_mut().zip(&mut steps) {
+            let Some(spin) = axis else {
+                continue;
+            };
+            let turning = elapsed.min(spin.held);
+            *steps = spin.sign * spin.rate * turning;
+            spin.rate = (spin.rate + acceleration * turning).min(MAX_RATE);
+            spin.held -= elapsed;
+            if spin.held <= 0.0 {
+                *axis = None;
+            }
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use approx::assert_relative_eq;
+
+    use super::{Spin, MAX_RATE, START_RATE};
+
+    #[test]
+    fn test_spin() {
+        let mut spin = Spin::default();
+        assert!(!spin.is_spinning());
+        assert_eq!(spin.advance(Duration::from_millis(100), 4.0), [0.0; 3]);
+
+        spin.press(1, -1.0);
+        let frame = Duration::from_millis(100);
+        let first = spin.advance(frame, 4.0);
+        assert_relative_eq!(first[1], -START_RATE * 0.1);
+        assert_eq!((first[0], first[2]), (0.0, 0.0));
+        // held down, it turns faster
+        spin.press(1, -1.0);
+        let second = spin.advance(frame, 4.0);
+        assert_relative_eq!(second[1], -(START_RATE + 0.4) * 0.1);
+
+        // the other way, it starts over
+        spin.press(1, 1.0);
+        assert_relative_eq!(spin.advance(frame, 4.0)[1], START_RATE * 0.1);
+
+        // once released, it stops within the time a key is held
+        let rest = spin.advance(Duration::from_secs(5), 4.0);
+        assert!(rest[1] > 0.0 && rest[1] < MAX_RATE);
+        assert!(!spin.is_spinning());
+
+        spin.press(0, 1.0);
+        spin.press(2, 1.0);
+        spin.stop();
+        assert!(!spin.is_spinning());
+    }
+}
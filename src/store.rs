@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::sky::Sky;
+
+/// The catalogs read so far, each parsed once and kept from the brightest
+/// star to the faintest, so that new skies are taken from memory instead of
+/// reading the files again.
+#[derive(Clone, Debug, Default)]
+pub struct CatalogStore {
+    /// all the stars of each catalog file, or none if it gave a random sky,
+    /// with why the file was not used if it was not
+    catalogs: HashMap<String, (Option<Sky>, Option<String>)>,
+}
+
+impl CatalogStore {
+    /// The same sky as `Sky::new`, but reading `catalog` only the first time.
+    pub fn sky(&mut self, catalog: &Option<String>, nstars: usize) -> (Sky, Option<String>) {
+        let Some(filename) = catalog else {
+            return (Sky::random_with_stars(nstars), None);
+        };
+        let (all, fallback) = self.catalogs.entry(filename.clone()).or_insert_with(|| {
+            let (all, fallback) = Sky::all_from(filename);
+            (all.map(|sky| sky.brightest(sky.len())), fallback)
+        });
+        let fallback = fallback.clone();
+        let Some(all) = all else {
+            return (Sky::random_with_stars(nstars), fallback);
+        };
+        // as `Sky::new` has them, the brightest last
+        let sky: Sky = all.iter().take(nstars).rev().cloned().collect();
+        (sky.tagged(), fallback)
+    }
+
+    /// whether `filename` was read already
+    pub fn has(&self, filename: &str) -> bool {
+        self.catalogs.contains_key(filename)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CatalogStore;
+    use crate::sky::Sky;
+
+    #[test]
+    fn test_catalog_store() {
+        let fname = std::env::temp_dir().join("cuyat_store.csv");
+        let content = "# epoch 2000.0\n\
+                       α Ori,055510.3,+072425,0.50\n\
+                       α CMa,064508.9,-164258,-1.46\n\
+                       δ Ori,053200.4,-001757,2.23";
+        std::fs::write(&fname, content).unwrap();
+        let catalog = Some(fname.to_str().unwrap().to_string());
+        let mut store = CatalogStore::default();
+        assert!(!store.has(catalog.as_ref().unwrap()));
+        let (sky, fallback) = store.sky(&catalog, 2);
+        assert!(fallback.is_none());
+        assert!(store.has(catalog.as_ref().unwrap()));
+        assert_eq!(
+            sky.iter().map(|s| s.name()).collect::<Vec<_>>(),
+            ["α Ori", "α CMa"]
+        );
+        assert!(sky.iter().eq(Sky::new(&catalog, 2).0.iter()));
+
+        // from memory, even once the file is gone
+        std::fs::remove_file(&fname).unwrap();
+        let (sky, _) = store.sky(&catalog, 5);
+        assert_eq!(sky.len(), 3);
+        assert_eq!(sky[2].name(), "α CMa");
+        assert_eq!(store.sky(&None, 7).0.len(), 7);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Whether the crate was built with the `profile` feature: without it
+/// nothing is timed, and there are no timings to show.
+pub const ENABLED: bool = cfg!(feature = "profile");
+
+/// The stages of making and drawing a sky.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    /// reading the catalog and picking the stars of a new sky
+    Parse,
+    /// turning the stars to the attitude they are seen from
+    Rotate,
+    /// projecting them on the screen
+    Project,
+    /// drawing them
+    Draw,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Parse, Stage::Rotate, Stage::Project, Stage::Draw];
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Parse => "parse",
+            Stage::Rotate => "rotate",
+            Stage::Project => "project",
+            Stage::Draw => "draw",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How long each stage takes: making the last sky, and for the others the
+/// average over the last frames, so that a slower frame pipeline shows.
+#[derive(Clone, Debug, Default)]
+pub struct Timings {
+    /// what each stage took so far in this frame
+    frame: [Duration; 4],
+    average: [Duration; 4],
+}
+
+impl Timings {
+    /// when a stage starts, if it is timed at all
+    pub fn start(&self) -> Option<Instant> {
+        ENABLED.then(Instant::now)
+    }
+
+    /// Adds the time since `started` to `stage`, in this frame.
+    pub fn stop(&mut self, stage: Stage, started: Option<Instant>) {
+        if let Some(started) = started {
+            self.frame[stage as usize] += started.elapsed();
+        }
+    }
+
+    /// Ends a frame, taking what each stage took in it into its average.
+    pub fn end_frame(&mut self) {
+        for stage in Stage::ALL {
+            let i = stage as usize;
+            let took = std::mem::take(&mut self.frame[i]);
+            if stage == Stage::Parse {
+                if !took.is_zero() {
+                    self.average[i] = took;
+                }
+            } else {
+                self.average[i] = (self.average[i] * 9 + took) / 10;
+            }
+        }
+    }
+
+    /// how long `stage` takes
+    pub fn get(&self, stage: Stage) -> Duration {
+        self.average[stage as usize]
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages = Stage::ALL
+            .map(|stage| format!("{stage}: {:.2} ms", self.get(stage).as_secs_f64() * 1e3));
+        write!(f, "{}", stages.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{Stage, Timings, ENABLED};
+
+    #[test]
+    fn test_timings() {
+        let mut timings = Timings::default();
+        let started = timings.start();
+        assert_eq!(started.is_some(), ENABLED);
+        std::thread::sleep(Duration::from_millis(2));
+        timings.stop(Stage::Parse, started);
+        timings.stop(Stage::Draw, timings.start());
+        timings.end_frame();
+        if ENABLED {
+            assert!(timings.get(Stage::Parse) >= Duration::from_millis(2));
+        } else {
+            assert!(timings.get(Stage::Parse).is_zero());
+        }
+        // parsing is not averaged, and it keeps its time in frames without it
+        let parse = timings.get(Stage::Parse);
+        timings.end_frame();
+        assert_eq!(timings.get(Stage::Parse), parse);
+
+        let mut timings = Timings::default();
+        timings.frame[Stage::Rotate as usize] = Duration::from_millis(10);
+        timings.end_frame();
+        assert_eq!(timings.get(Stage::Rotate), Duration::from_millis(1));
+        timings.end_frame();
+        assert_eq!(timings.get(Stage::Rotate), Duration::from_micros(900));
+        assert_eq!(
+            timings.to_string(),
+            "parse: 0.00 ms, rotate: 0.90 ms, project: 0.00 ms, draw: 0.00 ms"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use nalgebra::UnitQuaternion;
+
+use crate::sky::{Brightness, CatalogStar, Sky, Star};
+
+/// What the player does that the tutorial teaches.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Yaw,
+    Pitch,
+    Roll,
+    Zoom,
+    Score,
+}
+
+/// the lessons of the tutorial, in order: what to do and how
+const LESSONS: [(Action, &str); 5] = [
+    (Action::Yaw, "turn left and right, yawing, with y or Y"),
+    (Action::Pitch, "turn up and down, pitching, with p or P"),
+    (
+        Action::Roll,
+        "twist the view, rolling, with r or R, until it looks like the target",
+    ),
+    (Action::Zoom, "zoom in or out with z or Z"),
+    (
+        Action::Score,
+        "match the target as well as you can, then score with space: lower is better",
+    ),
+];
+
+/// A walk through the ways of playing, one lesson at a time: each goes on
+/// to the next once the player does what it asks.
+#[derive(Clone, Debug, Default)]
+pub struct Tutorial {
+    lesson: usize,
+}
+
+impl Tutorial {
+    /// Takes `action` as done, going on to the next lesson if it was the
+    /// one asked for. Tells whether it did.
+    pub fn did(&mut self, action: Action) -> bool {
+        let asked = LESSONS.get(self.lesson).is_some_and(|(a, _)| *a == action);
+        if asked {
+            self.lesson += 1;
+        }
+        asked
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.lesson >= LESSONS.len()
+    }
+
+    /// what the player should do now, and how far along the tutorial is
+    pub fn prompt(&self) -> String {
+        match LESSONS.get(self.lesson) {
+            Some((_, text)) => format!("tutorial {}/{}: {text}", self.lesson + 1, LESSONS.len()),
+            None => String::from("tutorial done: have fun with the whole sky!"),
+        }
+    }
+}
+
+/// A tiny sky of five stars around the boresight, easy to tell apart, to
+/// learn with.
+pub fn tutorial_sky() -> Sky {
+    let stars = [
+        ("Alpha", 0.0, 0.0, 0.5),
+        ("Beta", 0.5, 0.3, 1.5),
+        ("Gamma", -0.4, 0.5, 2.0),
+        ("Delta", 0.2, -0.6, 2.5),
+        ("Epsilon", -0.6, -0.2, 3.0),
+    ]
+    .map(|(name, x, y, magnitude)| {
+        CatalogStar::new(
+            Star::new(x, y, 1.0).normalize(),
+            Brightness::from_magnitude(magnitude),
+            name,
+        )
+    });
+    Sky::from(&stars)
+}
+
+/// where the view starts from in the tutorial: a little off the target at
+/// the identity, so that every way of turning is needed
+pub fn tutorial_start() -> UnitQuaternion<f32> {
+    UnitQuaternion::from_euler_angles(0.4, 0.25, -0.3)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tutorial_sky, Action, Tutorial};
+
+    #[test]
+    fn test_tutorial() {
+        let mut tutorial = Tutorial::default();
+        assert!(tutorial.prompt().starts_with("tutorial 1/5: "));
+        // what is not asked for yet does not count
+        assert!(!tutorial.did(Action::Pitch));
+        assert!(!tutorial.did(Action::Score));
+        for action in [
+            Action::Yaw,
+            Action::Pitch,
+            Action::Roll,
+            Action::Zoom,
+            Action::Score,
+        ] {
+            assert!(!tutorial.is_done());
+            assert!(tutorial.did(action));
+        }
+        assert!(tutorial.is_done());
+        assert!(!tutorial.did(Action::Yaw));
+        assert!(tutorial.prompt().starts_with("tutorial done"));
+    }
+
+    #[test]
+    fn test_tutorial_sky() {
+        let sky = tutorial_sky();
+        assert_eq!(sky.len(), 5);
+        assert!(sky.iter().all(|star| star.position().z > 0.7));
+    }
+}
@@ -0,0 +1,135 @@
+use std::f64::consts::PI;
+
+use crate::sky::{Brightness, CatalogStar};
+
+/// How the brightness of a variable star changes along its period.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LightCurve {
+    /// at its brightest but for eclipses lasting `duration` days, centered on the epoch
+    Eclipsing { duration: f64 },
+    /// brightest at the epoch and faintest half a period later
+    Pulsating,
+}
+
+/// A well known variable star: its HR number, its designation, its
+/// brightest and faintest magnitudes, its period in days, the julian date
+/// of a minimum (for eclipsing stars) or maximum and the shape of its light curve.
+struct Variable {
+    hr: u32,
+    name: &'static str,
+    brightest: f32,
+    faintest: f32,
+    period: f64,
+    epoch: f64,
+    curve: LightCurve,
+}
+
+/// Algol, Mira and δ Cephei, from the General Catalogue of Variable Stars
+const VARIABLES: [Variable; 3] = [
+    Variable {
+        hr: 936,
+        name: "β Per",
+        brightest: 2.12,
+        faintest: 3.39,
+        period: 2.867_304_3,
+        epoch: 2_445_641.513_5,
+        curve: LightCurve::Eclipsing { duration: 0.4 },
+    },
+    Variable {
+        hr: 681,
+        name: "ο Cet",
+        brightest: 2.0,
+        faintest: 10.1,
+        period: 331.96,
+        epoch: 2_444_839.0,
+        curve: LightCurve::Pulsating,
+    },
+    Variable {
+        hr: 8571,
+        name: "δ Cep",
+        brightest: 3.48,
+        faintest: 4.37,
+        period: 5.366_341,
+        epoch: 2_436_075.445,
+        curve: LightCurve::Pulsating,
+    },
+];
+
+impl Variable {
+    fn is(&self, star: &CatalogStar) -> bool {
+        match star.hr() {
+            Some(hr) => hr == self.hr,
+            None => star.name() == self.name,
+        }
+    }
+
+    /// how far from its brightest it is at julian date `jd`, from 0 to 1
+    fn dimming(&self, jd: f64) -> f64 {
+        let days = (jd - self.epoch).rem_euclid(self.period);
+        match self.curve {
+            LightCurve::Eclipsing { duration } => {
+                // days from the middle of the nearest eclipse
+                let from_middle = days.min(self.period - days);
+                if from_middle < duration / 2.0 {
+                    (from_middle / duration * PI).cos().powi(2)
+                } else {
+                    0.0
+                }
+            }
+            LightCurve::Pulsating => (1.0 - (2.0 * PI * days / self.period).cos()) / 2.0,
+        }
+    }
+
+    fn magnitude(&self, jd: f64) -> f32 {
+        self.brightest + (self.faintest - self.brightest) * self.dimming(jd) as f32
+    }
+}
+
+/// the magnitude of `star` at julian date `jd`, if it is a known variable
+pub fn magnitude_at(star: &CatalogStar, jd: f64) -> Option<f32> {
+    VARIABLES
+        .iter()
+        .find(|variable| variable.is(star))
+        .map(|variable| variable.magnitude(jd))
+}
+
+/// `star` as bright as it is at julian date `jd`
+pub fn at_date(star: &CatalogStar, jd: f64) -> CatalogStar {
+    match magnitude_at(star, jd) {
+        Some(m) => star.with_brightness(Brightness::from_magnitude(m)),
+        None => star.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::{magnitude_at, VARIABLES};
+    use crate::sky::{Brightness, CatalogStar, Star};
+
+    #[test]
+    fn test_variables() {
+        let star = |name| CatalogStar::new(Star::new(0.0, 0.0, 1.0), Brightness::new(0.1), name);
+        let algol = VARIABLES[0].epoch;
+        assert_relative_eq!(magnitude_at(&star("β Per"), algol).unwrap(), 3.39);
+        assert_relative_eq!(magnitude_at(&star("β Per"), algol + 1.0).unwrap(), 2.12);
+        let mid = magnitude_at(&star("β Per"), algol + 0.1).unwrap();
+        assert!(2.12 < mid && mid < 3.39);
+        // the previous eclipse is as deep
+        assert_relative_eq!(
+            magnitude_at(&star("β Per"), algol - VARIABLES[0].period).unwrap(),
+            3.39,
+            epsilon = 1e-4
+        );
+
+        let mira = VARIABLES[1].epoch;
+        assert_relative_eq!(magnitude_at(&star("ο Cet"), mira).unwrap(), 2.0);
+        let half = mira + VARIABLES[1].period / 2.0;
+        assert_relative_eq!(magnitude_at(&star("ο Cet"), half).unwrap(), 10.1);
+        let by_hr = star("").with_hr(681);
+        assert_eq!(magnitude_at(&by_hr, mira), Some(2.0));
+
+        assert_eq!(magnitude_at(&star("α Ori"), mira), None);
+    }
+}
@@ -1,208 +1,1496 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    f32::consts::FRAC_PI_4,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
+};
 
 use cursive::{
-    event::{Event, EventResult},
+    event::{Event, EventResult, Key, MouseButton, MouseEvent},
     theme::{Color, ColorStyle},
-    Printer, Vec2, View,
+    traits::Nameable,
+    views::{Dialog, EditView},
+    Cursive, Printer, Vec2, View,
 };
 use nalgebra::UnitQuaternion;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::sky::{quat_coords_str, random_quaternion, FoV, Sky};
+use crate::{
+    assist::{PairAssist, PENALTY},
+    attitude::{centering, ease, is_aligned, parse_attitude, slerp, AttitudeError, Metric},
+    boundaries::Boundaries,
+    cluster::Cluster,
+    command::Command,
+    config::{custom_stars, custom_stars_path, InvertConfig, Session, ViewConfig},
+    difficulty::{Difficulty, Sight},
+    ephemeris::julian_date,
+    extinction::SkyConditions,
+    frames::{Camera, Equatorial},
+    galactic::galactic_pole,
+    hint::{self, Hint},
+    level::{Control, Rules},
+    macros::{Macro, Turn},
+    names::NameStyle,
+    observer::Observer,
+    optics::Optics,
+    quiz::Quiz,
+    sky::{
+        angle_between, direction_at, equatorial_grid, great_circle, quat_coords_str, ra_dec,
+        random_quaternion, random_quaternion_with, CatalogStar, FoV, Occluder, Sky, SkyError,
+        SkyRotation, Star,
+    },
+    spin::Spin,
+    store::CatalogStore,
+    timing::{Stage, Timings, ENABLED},
+    tutorial::{tutorial_sky, tutorial_start, Action, Tutorial},
+};
 
-#[derive(Clone)]
+/// a glyph for a star, larger the brighter it is, as given by `Brightness::glyph_size`
+fn star_glyph(size: f32) -> &'static str {
+    match size {
+        s if s < 0.25 => "·",
+        s if s < 0.5 => "+",
+        s if s < 0.75 => "*",
+        _ => "✶",
+    }
+}
+
+/// how much taller than wide a cell of the terminal is
+const CELL_ASPECT: f32 = 2.0;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Options {
     pub(crate) show_distance: bool,
     pub(crate) show_star_names: bool,
+    pub(crate) name_style: NameStyle,
     pub(crate) catalog_filename: Option<String>,
     pub(crate) nstars: usize,
+    #[serde(skip)]
     pub(crate) show_help: bool,
     pub(crate) only_target: bool,
+    pub(crate) false_stars: usize,
+    pub(crate) occultation: bool,
+    pub(crate) solar_system: bool,
+    pub(crate) deep_sky: bool,
+    /// stars closer than this, in arcminutes, are shown as one
+    pub(crate) double_separation: f32,
+    pub(crate) observer: Observer,
+    /// only the stars above the horizon of `observer` are shown
+    pub(crate) horizon: bool,
+    /// with `horizon`, stars are raised by the refraction of the atmosphere
+    pub(crate) refraction: bool,
+    /// the target turns about the pole as the sky does, only faster (gui)
+    pub(crate) sidereal_drift: bool,
+    /// light pollution and extinction, as seen by `observer`
+    pub(crate) conditions: SkyConditions,
+    /// the galactic equator is drawn over the window
+    pub(crate) galactic_equator: bool,
+    /// lines of right ascension and declination are drawn over the window
+    pub(crate) grid: bool,
+    /// degrees of right ascension and of declination between the lines of the grid
+    pub(crate) grid_step: (f32, f32),
+    /// how the distance to the target is measured, and so scored
+    pub(crate) metric: Metric,
+    /// the faintest stars drawn; without it, the narrower the field the fainter
+    pub(crate) limiting_magnitude: Option<f32>,
+    /// the instrument looked through last, if any
+    pub(crate) optics: Option<Optics>,
+    /// turns are about the axes of the camera itself instead of the screen
+    pub(crate) body_axes: bool,
+    /// the rotation keys turn for as long as they are held (cli)
+    pub(crate) continuous: bool,
+    /// how much faster continuous turns get, in steps per second every second
+    pub(crate) acceleration: f32,
+    /// how close to the target, in degrees, solves the game; 0 to never
+    /// end it but with space
+    pub(crate) win_tolerance: f32,
+    /// the difficulty the number of stars and names were last set from, and
+    /// that the games start from and are scored with
+    pub(crate) difficulty: Difficulty,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            show_distance: false,
+            show_star_names: true,
+            name_style: NameStyle::Bayer,
+            catalog_filename: Some(String::from("assets/bsc5.csv")),
+            nstars: 400,
+            show_help: false,
+            only_target: false,
+            false_stars: 0,
+            occultation: false,
+            solar_system: false,
+            deep_sky: false,
+            double_separation: 0.0,
+            observer: Observer::default(),
+            horizon: false,
+            refraction: true,
+            sidereal_drift: false,
+            conditions: SkyConditions::default(),
+            galactic_equator: false,
+            grid: false,
+            grid_step: (15.0, 10.0),
+            metric: Metric::default(),
+            limiting_magnitude: None,
+            optics: None,
+            body_axes: false,
+            continuous: false,
+            acceleration: 4.0,
+            win_tolerance: 0.5,
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Shows from now on the number of stars and the names of `sight`.
+    pub(crate) fn set_sight(&mut self, sight: Sight) {
+        self.nstars = sight.nstars;
+        self.show_star_names = sight.show_star_names;
+    }
+    /// the axes turns are about, for the header
+    pub(crate) fn axes_str(&self) -> &'static str {
+        if self.body_axes {
+            "body"
+        } else {
+            "screen"
+        }
+    }
+    /// Looks through the next instrument: `fov` becomes its field of view,
+    /// out of the all-sky view if needed, and its faintest stars are shown.
+    pub(crate) fn next_optics(&mut self, fov: &mut FoV, narrow_fov: &mut Option<FoV>) {
+        let optics = self.optics.map_or(Optics::default(), Optics::next);
+        *fov = optics.fov().with_vignetting(fov.vignetting());
+        *narrow_fov = None;
+        self.optics = Some(optics);
+        self.limiting_magnitude = Some(optics.limiting_magnitude());
+    }
+
+    /// Shows stars `by` magnitudes fainter (or brighter, if negative) than
+    /// those shown now through `fov`, in steps of half a magnitude.
+    pub(crate) fn change_limiting_magnitude(&mut self, fov: &FoV, by: f32) {
+        let limit = fov
+            .with_limiting_magnitude(self.limiting_magnitude)
+            .limiting_magnitude();
+        self.limiting_magnitude = Some(((limit + by) * 2.0).round() / 2.0);
+    }
+
+    /// The sky the options ask for at julian date `jd`: the stars of the
+    /// catalog and the custom ones, with the Sun, the Moon and the planets
+    /// if they are shown. Without a catalog, its stars are drawn from `rng`.
+    /// If the catalog or the custom stars cannot be read, why comes along
+    /// with the sky.
+    pub(crate) fn catalog_sky<R: Rng>(
+        &self,
+        catalogs: &mut CatalogStore,
+        jd: f64,
+        rng: &mut R,
+    ) -> (Sky, Option<String>) {
+        let (mut sky, mut fallback) = match self.catalog_filename {
+            Some(_) => catalogs.sky(&self.catalog_filename, self.nstars),
+            None => (Sky::random_with_rng(self.nstars, rng), None),
+        };
+        if let Some(path) = custom_stars_path() {
+            let problem = match custom_stars(&path) {
+                Ok((custom, skipped)) => {
+                    sky.extend(custom);
+                    skipped.first().map(SkyError::to_string)
+                }
+                Err(e) => Some(e.to_string()),
+            };
+            if let Some(problem) = problem {
+                let problem = format!("{}: {problem}", path.display());
+                fallback = Some(match fallback {
+                    Some(fallback) => format!("{fallback}; {problem}"),
+                    None => problem,
+                });
+            }
+        }
+        if self.double_separation > 0.0 {
+            sky = sky.with_doubles_merged((self.double_separation / 60.0).to_radians());
+        }
+        if self.solar_system {
+            sky = sky.with_solar_system(jd);
+        }
+        if self.horizon {
+            if self.refraction {
+                sky = sky.refracted(&self.observer, jd);
+            }
+            sky = sky.above_horizon(&self.observer, jd);
+        }
+        if !self.conditions.is_perfect() {
+            sky = sky.under(&self.conditions, &self.observer, jd);
+        }
+        (sky, fallback)
+    }
+
+    pub(crate) fn false_stars_str(&self) -> String {
+        if self.false_stars > 0 {
+            format!(" (+{} false)", self.false_stars)
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Where the stars of one half of the window were last drawn, and for which
+/// attitude, field of view and size.
+#[derive(Clone, Default)]
+struct Drawn {
+    key: Option<(UnitQuaternion<f32>, FoV, u32, u32)>,
+    /// where each star goes, and its index in the sky
+    stars: Vec<(u32, u32, u8, usize)>,
 }
 
 #[derive(Clone)]
 pub struct SkyView {
     pub sky: Sky,
+    false_sky: Sky,
+    /// the catalogs read, to make new skies from
+    catalogs: CatalogStore,
+    /// where the stars are turned to draw them, kept between frames
+    rotation: RefCell<SkyRotation>,
+    /// the sky seen through the window, while it does not change
+    live: RefCell<Option<Sky>>,
+    /// the stars of each half of the window as last drawn
+    drawn: RefCell<[Drawn; 2]>,
+    /// how long making and drawing the sky takes
+    timings: RefCell<Timings>,
+    /// the axes turning while their keys are held, in continuous mode
+    spin: Spin,
+    /// the part of a move turned continuously, not counted yet
+    turning: f32,
+    /// the turns recorded so far, while recording a macro
+    recording: Option<Macro>,
+    /// the macro recorded last
+    recorded: Macro,
+    /// the turns of the macro still to replay
+    replaying: Option<std::vec::IntoIter<Turn>>,
+    /// when the view was last refreshed
+    refreshed: Instant,
+    /// the cell of the window the cursor is at, to select stars with
+    cursor: Option<(u32, u32)>,
+    /// the star of the sky selected, to show what it is
+    selected: Option<usize>,
+    /// the pairs of stars matched so far, while matching them
+    assist: Option<PairAssist>,
+    /// the lessons of the tutorial, while it is taken
+    tutorial: Option<Tutorial>,
+    /// when a hint was last asked for, to show it for a while
+    hint_shown: Option<Instant>,
+    /// the scoring of the games that count, kept aside while practicing:
+    /// meanwhile, moves and games go to a scoring of their own
+    practicing: Option<Rc<RefCell<Scoring>>>,
+    /// a planetarium: no target, nothing to score, the sky over the whole window
+    zen: bool,
+    /// the julian date the sky is made for, instead of now
+    date: Option<f64>,
+    /// the quiz on the names of the stars, while it is played
+    quiz: Option<Quiz>,
+    /// the cluster of stars the view moves in instead of turning, in the
+    /// games of finding where the target is seen from
+    cluster: Option<Cluster>,
+    /// in a cluster, the view turns as well as moves, and both its attitude
+    /// and position are to match those of the target
+    six_dof: bool,
+    /// the keys that turn the view move it instead, when it does both
+    moving: bool,
+    occluder: Occluder,
     fov: FoV,
+    /// the field of view to go back to, while showing the whole sky
+    narrow_fov: Option<FoV>,
     target_q: UnitQuaternion<f32>,
     real_q: UnitQuaternion<f32>,
     step: f32,
+    initial_step: f32,
     scoring: Rc<RefCell<Scoring>>,
     options: Options,
+    /// the ways of turning that go the other way
+    invert: InvertConfig,
     headers: usize,
     vmargin: usize,
+    rules: Rules,
+    started: Instant,
+    pointing_result: Option<String>,
+    /// what happened with the last command, save or restore, or what could
+    /// not be read to make the sky
+    status: Option<String>,
+    slew: Option<Slew>,
+    /// where targets, attitudes and random skies come from
+    rng: ChaCha8Rng,
 }
 
 impl SkyView {
     pub fn new(catalog: Option<String>, nstars: usize, scoring: Rc<RefCell<Scoring>>) -> Self {
-        let target_q = random_quaternion();
-        let sky = Sky::new(&catalog, nstars).with_attitude(target_q);
+        Self::with_attitudes(
+            catalog,
+            nstars,
+            scoring,
+            random_quaternion(),
+            random_quaternion(),
+        )
+    }
+
+    pub fn with_attitudes(
+        catalog: Option<String>,
+        nstars: usize,
+        scoring: Rc<RefCell<Scoring>>,
+        target_q: UnitQuaternion<f32>,
+        real_q: UnitQuaternion<f32>,
+    ) -> Self {
+        let mut catalogs = CatalogStore::default();
+        let (sky, fallback) = catalogs.sky(&catalog, nstars);
+        let sky = sky.with_attitude(target_q);
         let options = Options {
-            show_distance: false,
-            show_star_names: true,
             catalog_filename: catalog,
             nstars,
-            show_help: false,
-            only_target: false,
+            ..Options::default()
         };
         let fov = FoV::new(2.0, 2.0);
-        let real_q = random_quaternion();
         Self {
             sky,
+            false_sky: Sky::from(&[]),
+            catalogs,
+            rotation: RefCell::default(),
+            live: RefCell::default(),
+            drawn: RefCell::default(),
+            timings: RefCell::default(),
+            spin: Spin::default(),
+            turning: 0.0,
+            recording: None,
+            recorded: Macro::default(),
+            replaying: None,
+            refreshed: Instant::now(),
+            cursor: None,
+            selected: None,
+            assist: None,
+            tutorial: None,
+            hint_shown: None,
+            practicing: None,
+            zen: false,
+            date: None,
+            quiz: None,
+            cluster: None,
+            six_dof: false,
+            moving: false,
+            occluder: Occluder::random(),
             fov,
+            narrow_fov: None,
             target_q,
             real_q,
             step: 0.125,
+            initial_step: 0.125,
             scoring: Rc::clone(&scoring),
             options,
+            invert: InvertConfig::default(),
             headers: 3,
             vmargin: 1,
+            rules: Rules::default(),
+            started: Instant::now(),
+            pointing_result: None,
+            status: fallback.map(|fallback| format!(",   {fallback}")),
+            slew: None,
+            rng: ChaCha8Rng::from_entropy(),
         }
     }
 
+    /// The same view, with its games drawn from a generator seeded with
+    /// `seed`: the same seed gives the same sequence of skies and targets,
+    /// on every platform and version.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.new_game();
+        self
+    }
+
+    /// The same view, with its sky drawn from a generator seeded with
+    /// `seed`, but keeping its attitudes.
+    pub fn with_sky_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.make_sky();
+        self
+    }
+
+    /// the same view, playing at `difficulty` from a new game
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.set_difficulty(difficulty);
+        self.new_game();
+        self
+    }
+
+    /// the same view, showing what `sight` does from the next sky
+    pub fn with_sight(mut self, sight: Sight) -> Self {
+        self.set_sight(sight);
+        self
+    }
+
+    /// The same view, taking the tutorial: its first game is on a tiny sky
+    /// of named stars, a little off the target.
+    pub fn with_tutorial(mut self) -> Self {
+        self.target_q = UnitQuaternion::identity();
+        self.real_q = tutorial_start();
+        self.sky = tutorial_sky();
+        self.false_sky = Sky::from(&[]);
+        self.options.show_star_names = true;
+        self.tutorial = Some(Tutorial::default());
+        self
+    }
+
+    /// Takes `action` as done in the tutorial, if it is being taken.
+    fn learned(&mut self, action: Action) {
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.did(action);
+        }
+    }
+
+    /// The same view, practicing from the start.
+    pub fn with_practice(mut self) -> Self {
+        self.practicing = Some(std::mem::take(&mut self.scoring));
+        self
+    }
+
+    /// Starts practicing after scoring the game played, or stops and goes
+    /// back to the games that count with a new one.
+    fn toggle_practice(&mut self) {
+        match self.practicing.take() {
+            Some(scoring) => self.scoring = scoring,
+            None => {
+                self.score();
+                self.practicing = Some(std::mem::take(&mut self.scoring));
+            }
+        }
+        self.new_game();
+    }
+
+    /// The same view as a planetarium: the sky as it is, without a target,
+    /// with the names of the stars and where it points. With an `observer`,
+    /// only the stars above their horizon at `date`, or now, are shown,
+    /// starting at their zenith.
+    pub fn with_zen(mut self, observer: Option<Observer>, date: Option<f64>) -> Self {
+        self.zen = true;
+        self.date = date;
+        self.target_q = UnitQuaternion::identity();
+        self.options.show_star_names = true;
+        self.options.horizon = observer.is_some();
+        if let Some(observer) = observer {
+            self.options.observer = observer;
+        }
+        self.make_sky();
+        self.real_q = match observer {
+            Some(observer) => centering(&observer.zenith(self.sky_date())),
+            None => UnitQuaternion::identity(),
+        };
+        self
+    }
+
+    /// The same view as a quiz on the names of the stars: a planetarium
+    /// without names, where one star after another in sight is marked to be
+    /// named.
+    pub fn with_quiz(self) -> Self {
+        let mut view = self.with_zen(None, None);
+        view.options.show_star_names = false;
+        view.options.name_style = NameStyle::Proper;
+        view.quiz = Some(Quiz::default());
+        view
+    }
+
+    /// Asks about one of the stars in sight, unless a question is waiting.
+    fn keep_quizzing(&mut self) {
+        let Some(quiz) = &mut self.quiz else {
+            return;
+        };
+        if quiz.question().is_some() {
+            return;
+        }
+        let in_sight: Vec<usize> = self.drawn.get_mut()[0]
+            .stars
+            .iter()
+            .map(|&(_, _, _, i)| i)
+            .filter(|&i| i < self.sky.len())
+            .collect();
+        quiz.ask(&self.sky, &in_sight, self.options.name_style, &mut self.rng);
+    }
+
+    /// Answers the question of the quiz with `answer`, and tells how it went.
+    pub fn answer_quiz(&mut self, answer: &str) {
+        let Some(quiz) = &mut self.quiz else {
+            return;
+        };
+        let Some(star) = quiz.question().map(|question| question.star) else {
+            return;
+        };
+        let right = quiz.answer(&self.sky, answer, self.options.name_style);
+        let name = self.sky[star].display_name(self.options.name_style);
+        self.status = right.map(|right| match right {
+            true => format!(",   right, it is {name}!"),
+            false => format!(",   wrong, it is {name}"),
+        });
+    }
+
+    /// The same view in a cluster of random stars: the view points as the
+    /// target does but from somewhere else, and the keys that turn it move
+    /// it instead, to find where the target is seen from.
+    pub fn with_cluster(mut self) -> Self {
+        self.cluster = Some(Cluster::random_with(self.options.nstars, &mut self.rng));
+        self.target_q = UnitQuaternion::identity();
+        self.real_q = self.target_q;
+        self.make_sky();
+        self
+    }
+
+    /// The same view in a cluster where it turns as well as moves: the
+    /// target is seen from somewhere else with another attitude, and `J`
+    /// switches the keys between turning and moving.
+    pub fn with_six_dof(mut self) -> Self {
+        self = self.with_cluster();
+        self.six_dof = true;
+        self.target_q = random_quaternion_with(&mut self.rng);
+        self.real_q = self
+            .options
+            .difficulty
+            .starting_attitude(self.target_q, &mut self.rng);
+        self
+    }
+
+    /// Plays at `difficulty`, with its field of view, from the next game.
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.options.difficulty = difficulty;
+        self.set_sight(difficulty.sight());
+    }
+
+    /// Shows the stars, names and field of view of `sight`.
+    fn set_sight(&mut self, sight: Sight) {
+        self.options.set_sight(sight);
+        let half_fov = 2.0 * sight.fov_scale;
+        self.fov = FoV::new(half_fov, half_fov);
+        self.narrow_fov = None;
+    }
+
+    /// the same view with the options, field of view and step of a previous run
+    pub fn with_config(mut self, config: &ViewConfig) -> Self {
+        self.options = config.options.clone();
+        self.fov = FoV::new(config.fov.0, config.fov.1);
+        self.step = config.step;
+        self.initial_step = config.step;
+        self.make_sky();
+        self
+    }
+
+    pub fn config(&self) -> ViewConfig {
+        ViewConfig {
+            options: self.options.clone(),
+            fov: self.narrow_fov.as_ref().unwrap_or(&self.fov).half_fov(),
+            step: self.step,
+        }
+    }
+
+    /// the game as it is now, to be saved
+    pub fn session(&self) -> Session {
+        Session {
+            options: self.options.clone(),
+            fov: self.fov.clone(),
+            step: self.step,
+            target_q: self.target_q,
+            real_q: self.real_q,
+            scoring: (*self.scoring).borrow().clone(),
+            sky: self.sky.clone(),
+        }
+    }
+
+    /// goes back to a saved game, score included
+    pub fn with_session(mut self, session: Session) -> Self {
+        self.restore(session);
+        self
+    }
+
+    fn restore(&mut self, session: Session) {
+        self.options = session.options;
+        self.fov = session.fov;
+        self.narrow_fov = None;
+        self.step = session.step;
+        self.target_q = session.target_q;
+        self.real_q = session.real_q;
+        *(*self.scoring).borrow_mut() = session.scoring;
+        self.sky = session.sky;
+        self.false_sky = Sky::false_stars_with(self.options.false_stars, &mut self.rng);
+        self.sky_changed();
+        self.slew = None;
+        self.pointing_result = None;
+        self.started = Instant::now();
+    }
+
+    /// Saves the game to `path`, or to the usual file without one.
+    fn save_session(&mut self, path: Option<PathBuf>) {
+        let result = path
+            .or_else(Session::path)
+            .ok_or(String::from("no config directory"))
+            .and_then(|path| self.session().save(&path).map(|_| path));
+        self.status = Some(match result {
+            Ok(path) => format!(",   saved to {}", path.display()),
+            Err(e) => format!(",   not saved: {e}"),
+        });
+    }
+
+    /// Goes back to the game saved in `path`, or in the usual file without one.
+    fn restore_session(&mut self, path: Option<PathBuf>) {
+        let result = path
+            .or_else(Session::path)
+            .ok_or(String::from("no config directory"))
+            .and_then(|path| Session::load(&path));
+        self.status = Some(match result {
+            Ok(session) => {
+                self.restore(session);
+                String::from(",   restored")
+            }
+            Err(e) => format!(",   not restored: {e}"),
+        });
+    }
+
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// the same view, turning each way the other way if `invert` says so
+    pub fn with_invert(mut self, invert: &InvertConfig) -> Self {
+        self.invert = invert.clone();
+        self
+    }
+
     fn rotate(&mut self, x: f32, y: f32, z: f32) {
-        self.real_q =
-            UnitQuaternion::from_euler_angles(x * self.step, y * self.step, z * self.step)
-                * self.real_q;
-        (*self.scoring).borrow_mut().add_move();
+        let moves = (*self.scoring).borrow().moves;
+        if !self.rules.allows_move(moves, self.started.elapsed()) {
+            return;
+        }
+        let steps = self.invert.apply([x, y, z]);
+        self.turn(Turn::of_steps(steps, self.step, self.options.body_axes));
     }
 
-    fn draw_portion(&self, quat: UnitQuaternion<f32>, p: &Printer, x_max: u8, y_max: u8) {
-        for fps in self
-            .fov
-            .project_sky_to_screen(self.sky.with_attitude(quat), x_max, y_max)
-            .into_iter()
-            .flatten()
-        {
-            let (px, py, b, n) = fps;
-            let style = ColorStyle::new(Color::Rgb(b, b, b), Color::Rgb(0, 0, 32));
-            let id = if self.options.show_star_names {
-                n.as_str()
+    /// Turns the view by `turn`, recording it while a macro is recorded,
+    /// and counts its moves.
+    fn turn(&mut self, turn: Turn) {
+        match &mut self.cluster {
+            // sideways with yaw, up and down with pitch and ahead with roll,
+            // as the camera looks, turned back from its frame to the sky's
+            Some(cluster) if !self.six_dof || self.moving => {
+                let [pitch, yaw, roll] = turn.angles;
+                let offset = self.real_q.inverse() * Star::new(yaw, pitch, roll);
+                cluster.translate(offset);
+                self.sky_changed();
+            }
+            _ => self.real_q = turn.apply(self.real_q),
+        }
+        let [pitch, yaw, roll] = turn.angles;
+        for (angle, action) in [
+            (pitch, Action::Pitch),
+            (yaw, Action::Yaw),
+            (roll, Action::Roll),
+        ] {
+            if angle != 0.0 {
+                self.learned(action);
+            }
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.push(turn);
+        }
+        self.turning += turn.moves;
+        while self.turning >= 1.0 {
+            (*self.scoring).borrow_mut().add_move();
+            self.turning -= 1.0;
+        }
+    }
+
+    /// Turns as far as the keys held in continuous mode turned since the
+    /// last refresh, counting a move for each step turned.
+    fn keep_spinning(&mut self) {
+        let elapsed = std::mem::replace(&mut self.refreshed, Instant::now()).elapsed();
+        if !self.spin.is_spinning() {
+            return;
+        }
+        let moves = (*self.scoring).borrow().moves;
+        if !self.rules.allows_move(moves, self.started.elapsed()) {
+            self.spin.stop();
+            return;
+        }
+        let steps = self
+            .invert
+            .apply(self.spin.advance(elapsed, self.options.acceleration));
+        self.turn(Turn::of_steps(steps, self.step, self.options.body_axes));
+    }
+
+    /// Starts recording the turns of the view, or stops and keeps them to
+    /// be replayed.
+    fn toggle_recording(&mut self) {
+        self.status = Some(match self.recording.take() {
+            Some(recorded) => {
+                let status = format!(",   recorded {} turns", recorded.len());
+                self.recorded = recorded;
+                status
+            }
+            None => {
+                self.recording = Some(Macro::default());
+                String::from(",   recording")
+            }
+        });
+    }
+
+    /// Once the view is within the tolerance of the target, scores the game,
+    /// tells how it was solved and goes on to the next one.
+    fn check_solved(&mut self) {
+        if !self.is_solved() {
+            return;
+        }
+        let metadata = self.game_metadata();
+        self.restart();
+        self.status = Some(format!(
+            ",   *** solved in {:.1} s and {} moves! ***",
+            metadata.seconds, metadata.moves
+        ));
+    }
+
+    /// Plays the game with nobody at the keys, and scores it: turns straight
+    /// to the target, a step in each move, until it is solved. The same game
+    /// is always played the same way.
+    pub(crate) fn play_headless(&mut self) {
+        loop {
+            let hint = Hint::between(self.target_q, self.real_q);
+            if self.is_solved() || hint.angle < 1e-4 {
+                break;
+            }
+            let turn = UnitQuaternion::from_axis_angle(&hint.axis, hint.angle.min(self.step));
+            self.turn(Turn::of_rotation(turn, 1.0));
+        }
+        self.score();
+    }
+
+    /// Replays the turns recorded, each `scale` times as large, one in each refresh.
+    fn replay(&mut self, scale: f32) {
+        if let Some(recorded) = self.recording.take() {
+            self.recorded = recorded;
+        }
+        self.replaying = Some(self.recorded.replay(scale));
+        self.status = self
+            .recorded
+            .is_empty()
+            .then(|| String::from(",   nothing recorded"));
+    }
+
+    /// Turns as the next turn replayed, while the level allows it.
+    fn keep_replaying(&mut self) {
+        let moves = (*self.scoring).borrow().moves;
+        if !self.rules.allows_move(moves, self.started.elapsed()) {
+            self.replaying = None;
+        }
+        let Some(turn) = self.replaying.as_mut().and_then(Iterator::next) else {
+            self.replaying = None;
+            return;
+        };
+        self.turn(turn);
+    }
+
+    /// how the `half` of the window (0 for the window, 1 for the target) was
+    /// last drawn: the attitude, the field of view and its size in cells
+    fn drawn_view(&self, half: usize) -> Option<(UnitQuaternion<f32>, FoV, u32, u32)> {
+        self.drawn.borrow()[half].key.clone()
+    }
+
+    /// The star nearest to the cell (`x`, `y`) of the `half` of the window,
+    /// and where the camera of that half sees it.
+    fn star_at(&self, half: usize, x: u32, y: u32) -> Option<(usize, Star)> {
+        let (quat, fov, x_max, y_max) = self.drawn_view(half)?;
+        let direction = fov.screen_to_direction(x as f32, y as f32, x_max as f32, y_max as f32);
+        let (i, _) = self.sky.nearest_star(&(quat.inverse() * direction), &fov)?;
+        Some((i, quat * self.sky[i].position()))
+    }
+
+    /// Selects the star nearest to the cell (`x`, `y`) of the window.
+    fn select_at(&mut self, x: u32, y: u32) {
+        self.selected = self.star_at(0, x, y).map(|(i, _)| i);
+    }
+
+    /// Moves the cursor by `dx` and `dy` cells, within the window.
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let (Some((x, y)), Some((_, _, x_max, y_max))) = (self.cursor, self.drawn_view(0)) else {
+            return;
+        };
+        let x = (x as i32 + dx).clamp(0, x_max as i32 - 1) as u32;
+        let y = (y as i32 + dy).clamp(0, y_max as i32 - 1) as u32;
+        self.cursor = Some((x, y));
+    }
+
+    /// Selects the star clicked on in the window or, matching pairs of
+    /// stars, picks it in the window or the target: `position` is where on
+    /// the terminal, and `offset` where this view starts.
+    fn click(&mut self, position: Vec2, offset: Vec2) {
+        let (Some(x), Some(y)) = (
+            position.x.checked_sub(offset.x),
+            position.y.checked_sub(offset.y + self.headers),
+        ) else {
+            return;
+        };
+        let Some((_, _, x_max, y_max)) = self.drawn_view(0) else {
+            return;
+        };
+        let (x_max, y_max) = (x_max as usize, y_max as usize);
+        let (half, x) = match x.checked_sub(x_max + self.vmargin) {
+            _ if x < x_max => (0, x),
+            Some(x) if x < x_max => (1, x),
+            _ => return,
+        };
+        if y >= y_max {
+            return;
+        }
+        let (x, y) = (x as u32, y as u32);
+        if self.assist.is_none() {
+            if half == 0 {
+                self.cursor = Some((x, y));
+                self.select_at(x, y);
+            }
+            return;
+        }
+        let Some((_, direction)) = self.star_at(half, x, y) else {
+            return;
+        };
+        let rotation = self.assist.as_mut().and_then(|assist| {
+            if half == 0 {
+                assist.pick_window(direction);
+                None
             } else {
-                "*"
+                assist.pick_target(direction)
+            }
+        });
+        if let Some(rotation) = rotation {
+            self.real_q = rotation * self.real_q;
+            self.assist = None;
+            (*self.scoring).borrow_mut().moves += PENALTY;
+            self.status = Some(format!(
+                ",   aligned by two pairs of stars: {PENALTY} moves"
+            ));
+        }
+    }
+
+    /// whether the level lets the view jump to any attitude at once
+    fn allows_jump(&self) -> bool {
+        let moves = (*self.scoring).borrow().moves;
+        // in a cluster, the view points where the target does
+        self.cluster.is_none()
+            && [Control::Roll, Control::Pitch, Control::Yaw]
+                .into_iter()
+                .all(|c| self.rules.allows(c))
+            && self.rules.allows_move(moves, self.started.elapsed())
+    }
+
+    /// Marks the cursor and the selected star on the window, and tells what the star is.
+    fn draw_selection(&self, p: &Printer, x_max: u32, y_max: u32) {
+        let style = ColorStyle::new(Color::Rgb(0, 0, 32), Color::Rgb(230, 230, 80));
+        if let Some((x, y)) = self.cursor {
+            p.with_color(style, |printer| {
+                printer.print((x as usize, y as usize), "+")
+            });
+        }
+        let Some(star) = self.selected.map(|i| &self.sky[i]) else {
+            return;
+        };
+        self.mark_star(star, style, p, x_max, y_max);
+        let info = star_info(
+            star,
+            self.options.name_style,
+            self.target_q,
+            &self.boresight(),
+        );
+        let top = (y_max as usize).saturating_sub(self.headers + info.len());
+        for (i, line) in info.iter().enumerate() {
+            p.with_color(style, |printer| printer.print((1, top + i), line));
+        }
+    }
+
+    /// Marks the star the quiz asks about, without telling what it is.
+    fn draw_quiz(&self, p: &Printer, x_max: u32, y_max: u32) {
+        let Some(question) = self.quiz.as_ref().and_then(Quiz::question) else {
+            return;
+        };
+        let style = ColorStyle::new(Color::Rgb(0, 0, 32), Color::Rgb(80, 230, 230));
+        self.mark_star(&self.sky[question.star], style, p, x_max, y_max);
+    }
+
+    /// Marks `star` on the window or, out of sight, an arrow on the edge
+    /// that points the way to it.
+    fn mark_star(
+        &self,
+        star: &CatalogStar,
+        style: ColorStyle,
+        p: &Printer,
+        x_max: u32,
+        y_max: u32,
+    ) {
+        let fov = self.fov_for(x_max, y_max);
+        let direction = Equatorial::new(*star.position())
+            .to_body(self.real_q)
+            .to_camera();
+        if let Some((x, y)) = fov.to_screen(&direction, x_max, y_max) {
+            p.with_color(style, |printer| {
+                printer.print((x as usize, y as usize), "◎")
+            });
+        } else {
+            let height = y_max.saturating_sub(self.headers as u32) as f32 * CELL_ASPECT;
+            let (x, y, angle) = edge_arrow(&direction, x_max as f32, height, 1.0);
+            let octant = (angle / FRAC_PI_4).round().rem_euclid(8.0) as usize;
+            let arrow = ["→", "↘", "↓", "↙", "←", "↖", "↑", "↗"][octant];
+            let at = (x as usize, (y / CELL_ASPECT) as usize);
+            p.with_color(style, |printer| printer.print(at, arrow));
+        }
+    }
+
+    /// the field of view on `x_max` by `y_max` cells, which are taller than wide
+    fn fov_for(&self, x_max: u32, y_max: u32) -> FoV {
+        self.fov
+            .with_aspect(x_max as f32, y_max as f32 * CELL_ASPECT)
+            .with_limiting_magnitude(self.options.limiting_magnitude)
+    }
+
+    /// Draws `sky` turned by `quat` on the `half` of the window in `p`,
+    /// projecting it again only if it moved or the window changed.
+    fn draw_portion(
+        &self,
+        half: usize,
+        sky: &Sky,
+        quat: UnitQuaternion<f32>,
+        p: &Printer,
+        x_max: u32,
+        y_max: u32,
+    ) {
+        let fov = self.fov_for(x_max, y_max);
+        let key = (quat, fov, x_max, y_max);
+        let mut drawn = self.drawn.borrow_mut();
+        let drawn = &mut drawn[half];
+        let mut timings = self.timings.borrow_mut();
+        if drawn.key.as_ref() != Some(&key) {
+            let started = timings.start();
+            let mut rotation = self.rotation.borrow_mut();
+            let positions = rotation.rotate(&sky.stars, quat);
+            timings.stop(Stage::Rotate, started);
+            let started = timings.start();
+            key.1
+                .screen_stars_into(&sky.stars, positions, x_max, y_max, &mut drawn.stars);
+            // the occluder turns with the window, so it hides the stars as they are drawn
+            if half == 0 && self.options.occultation {
+                drawn
+                    .stars
+                    .retain(|&(_, _, _, i)| !self.occluder.hides(sky[i].position(), quat));
+            }
+            timings.stop(Stage::Project, started);
+            drawn.key = Some(key);
+        }
+        let started = timings.start();
+        for &(px, py, b, i) in &drawn.stars {
+            let star = &sky[i];
+            let [r, g, bl] = star.class_tint().map(|c| (b as f32 * c) as u8);
+            let style = ColorStyle::new(Color::Rgb(r, g, bl), Color::Rgb(0, 0, 32));
+            let name = star.display_name(self.options.name_style);
+            let id = if self.options.show_star_names && !name.is_empty() {
+                &*name
+            } else {
+                star_glyph(star.brightness().glyph_size())
             };
             p.with_color(style, |printer| {
-                printer.print((px, py), id);
+                printer.print((px as usize, py as usize), id);
             });
         }
+        timings.stop(Stage::Draw, started);
+    }
+
+    /// Marks with dots the great circle 90° away from `pole`, as seen through the window.
+    fn draw_great_circle(&self, pole: Equatorial, p: &Printer, x_max: u32, y_max: u32) {
+        let style = ColorStyle::new(Color::Rgb(120, 60, 140), Color::Rgb(0, 0, 32));
+        self.draw_sky_line(
+            &great_circle(&pole.direction(), 720),
+            style,
+            p,
+            x_max,
+            y_max,
+        );
+    }
+
+    /// Marks with dots the line through `points`, given in catalog coordinates,
+    /// as seen through the window.
+    fn draw_sky_line(
+        &self,
+        points: &[Star],
+        style: ColorStyle,
+        p: &Printer,
+        x_max: u32,
+        y_max: u32,
+    ) {
+        let attitude = self.real_q * self.target_q;
+        let fov = self.fov_for(x_max, y_max);
+        for point in points {
+            let point = Equatorial::new(*point).to_body(attitude).to_camera();
+            if let Some((px, py)) = fov.to_screen(&point, x_max, y_max) {
+                p.with_color(style, |printer| {
+                    printer.print((px as usize, py as usize), "·")
+                });
+            }
+        }
     }
 
     fn draw_header(&self, p: &Printer, style: ColorStyle) {
+        // a planetarium has no games
+        let games = if self.zen {
+            String::new()
+        } else {
+            let scoring = (*self.scoring).borrow();
+            format!(
+                ", moves: {}, games: {}, score: {:.6}",
+                scoring.moves,
+                scoring.total.len(),
+                scoring.get_score()
+            )
+        };
         let header_1 = format!(
-            "Stars: {}, catalog: {}. Step: {:.4}, zoom: {:.3}, moves: {}, games: {}, score: {:.6}",
+            "Stars: {}{}, catalog: {}, {}{}. Step: {:.4} ({} axes{}{}), zoom: {:.3}{}, limit: {:.1}{}",
             self.options.nstars,
+            self.options.false_stars_str(),
             self.options
                 .catalog_filename
                 .clone()
                 .unwrap_or("random".to_string()),
+            self.options.difficulty,
+            if self.practicing.is_some() {
+                ", practice"
+            } else {
+                ""
+            },
             self.step,
+            self.options.axes_str(),
+            if self.options.continuous {
+                ", continuous"
+            } else {
+                ""
+            },
+            if self.moving { ", moving" } else { "" },
             self.fov.zoom(),
-            (*self.scoring).borrow().moves,
-            (*self.scoring).borrow().total.len(),
-            (*self.scoring).borrow().get_score(),
+            self.options
+                .optics
+                .map(|optics| format!(" ({optics})"))
+                .unwrap_or_default(),
+            self.fov
+                .with_limiting_magnitude(self.options.limiting_magnitude)
+                .limiting_magnitude(),
+            games,
         );
         p.with_color(style, |printer| printer.print((1, 0), header_1.as_str()));
-        let (real_q, difference, distance) = if self.options.show_distance {
-            (
-                format!("State:  {}", quat_coords_str(self.real_q)),
-                format!(",   t/s: {}", quat_coords_str(self.target_q / self.real_q)),
-                format!(",   distance: {:.6}", self.distance()),
-            )
+        let (real_q, difference, distance) =
+            if self.options.show_distance || self.practicing.is_some() {
+                (
+                    format!(
+                        "State:  {},   near: {}",
+                        quat_coords_str(self.real_q),
+                        self.sky
+                            .nearest_constellation(&self.boresight())
+                            .unwrap_or("?")
+                    ),
+                    format!(",   t/s: {}", quat_coords_str(self.target_q / self.real_q)),
+                    format!(
+                        ",   distance: {:.6},   {}",
+                        self.distance(),
+                        AttitudeError::between(self.target_q, self.real_q)
+                    ),
+                )
+            } else {
+                (String::from(""), String::from(""), String::from(""))
+            };
+        let header_2 = if self.zen {
+            self.pointing_str()
         } else {
-            (String::from(""), String::from(""), String::from(""))
+            format!("Target: {}{}", quat_coords_str(self.target_q), distance)
         };
-        let header_2 = format!("Target: {}{}", quat_coords_str(self.target_q), distance);
         p.with_color(style, |printer| printer.print((1, 1), header_2.as_str()));
-        let header_3 = format!("{}{}", real_q, difference);
+        let pointing = self.pointing_result.clone().unwrap_or_default();
+        let status = self.status.clone().unwrap_or_default();
+        let assist = self
+            .assist
+            .as_ref()
+            .map(|assist| format!(",   {}", assist.prompt()))
+            .unwrap_or_default();
+        let hint = self
+            .hint_shown
+            .filter(|shown| shown.elapsed() < hint::SHOWN)
+            .map(|_| {
+                format!(
+                    ",   hint ({} moves): {}",
+                    hint::PENALTY,
+                    Hint::between(self.target_q, self.real_q)
+                )
+            })
+            .unwrap_or_default();
+        let tutorial = self
+            .tutorial
+            .as_ref()
+            .map(|tutorial| format!(",   {}", tutorial.prompt()))
+            .or_else(|| {
+                self.quiz
+                    .as_ref()
+                    .map(|quiz| format!(",   {}", quiz.prompt()))
+            })
+            .unwrap_or_default();
+        let timings = if ENABLED {
+            format!("   {}", self.timings.borrow())
+        } else {
+            String::new()
+        };
+        let header_3 = format!(
+            "{}{}{}{}{}{}{}{}",
+            real_q, difference, pointing, status, assist, hint, tutorial, timings
+        );
         p.with_color(style, |printer| printer.print((1, 2), header_3.as_str()));
     }
 
-    fn show_help(&self, p: &Printer, style: ColorStyle) {
+    /// Shows the help from `x` on, or farther left if it takes more than a
+    /// column to fit in the height of `p`.
+    fn show_help(&self, p: &Printer, x: usize, style: ColorStyle) {
         let help_lines = get_help_lines();
-        let max_len = help_lines.iter().map(|l| l.len()).max().unwrap();
+        let max_len = help_lines.iter().map(|l| l.chars().count()).max().unwrap();
+        let rows = p.size.y.max(1);
+        let columns = help_lines.len().div_ceil(rows);
+        let x = x.min(p.size.x.saturating_sub(columns * (max_len + 1)));
         for (i, line) in help_lines.iter().enumerate() {
-            let padded_line = format!("{}{}", line, " ".repeat(max_len - line.len()));
-            p.with_color(style, |printer| printer.print((0, i), padded_line.as_str()));
+            let padded_line = format!("{:max_len$} ", line);
+            let at = (x + i / rows * (max_len + 1), i % rows);
+            p.with_color(style, |printer| printer.print(at, padded_line.as_str()));
         }
     }
 
-    fn distance(&self) -> f32 {
-        let (roll, pitch, yaw) = (self.target_q / self.real_q).euler_angles();
-        (roll.powi(2) + pitch.powi(2) + yaw.powi(2)).sqrt()
+    /// where the planetarium points, and from where and when it is seen
+    fn pointing_str(&self) -> String {
+        let boresight = self.boresight();
+        let (ra, dec) = ra_dec(&(self.target_q.inverse() * boresight));
+        let seen_from = if self.options.horizon {
+            let observer = &self.options.observer;
+            format!(",   from {:.2}, {:.2}", observer.lat, observer.lon)
+        } else {
+            String::new()
+        };
+        format!(
+            "Pointing at ra {ra:.2}, dec {dec:.2},   near: {}{seen_from},   jd {:.3}",
+            self.sky.nearest_constellation(&boresight).unwrap_or("?"),
+            self.sky_date()
+        )
+    }
+
+    fn game_metadata(&self) -> GameMetadata {
+        GameMetadata {
+            false_stars: self.options.false_stars,
+            occultation: self.options.occultation,
+            difficulty: self.options.difficulty,
+            solved: self.is_solved(),
+            seconds: self.started.elapsed().as_secs_f32(),
+            moves: (*self.scoring).borrow().moves,
+        }
+    }
+    #[cfg(test)]
+    pub(crate) fn sky(&self) -> &Sky {
+        &self.sky
+    }
+    pub(crate) fn distance(&self) -> f32 {
+        let angle = self.options.metric.distance(self.target_q, self.real_q);
+        match &self.cluster {
+            Some(cluster) if self.six_dof => cluster.pose_distance(angle),
+            Some(cluster) => cluster.distance(),
+            None => angle,
+        }
+    }
+    /// whether the view is within the tolerance of the target
+    fn is_solved(&self) -> bool {
+        match &self.cluster {
+            Some(cluster) if !self.six_dof => cluster.is_reached(),
+            Some(cluster) => {
+                cluster.is_reached()
+                    && is_aligned(self.target_q, self.real_q, self.options.win_tolerance)
+            }
+            None => is_aligned(self.target_q, self.real_q, self.options.win_tolerance),
+        }
     }
     fn make_sky(&mut self) {
-        self.sky = Sky::new(&self.options.catalog_filename, self.options.nstars)
-            .with_attitude(self.target_q);
+        if let Some(cluster) = &self.cluster {
+            self.sky = cluster.target_sky();
+            self.false_sky = Sky::from(&[]);
+            self.sky_changed();
+            return;
+        }
+        let started = self.timings.get_mut().start();
+        let jd = self.sky_date();
+        let (sky, fallback) = self
+            .options
+            .catalog_sky(&mut self.catalogs, jd, &mut self.rng);
+        self.sky = sky.with_attitude(self.target_q);
+        self.timings.get_mut().stop(Stage::Parse, started);
+        if let Some(fallback) = fallback {
+            self.status = Some(format!(",   {fallback}"));
+        }
+        self.false_sky = Sky::false_stars_with(self.options.false_stars, &mut self.rng);
+        self.sky_changed();
+    }
+    /// the julian date the sky is seen at
+    fn sky_date(&self) -> f64 {
+        self.date.unwrap_or_else(|| julian_date(SystemTime::now()))
+    }
+    /// Forgets the stars last drawn, as what is in the sky changed.
+    fn sky_changed(&mut self) {
+        *self.live.get_mut() = None;
+        *self.drawn.get_mut() = Default::default();
+        self.selected = None;
+    }
+    /// the sky seen through the window, false stars included
+    fn live_sky(&self) -> Sky {
+        let view_sky;
+        let sky = match &self.cluster {
+            Some(cluster) => {
+                view_sky = cluster.view_sky();
+                &view_sky
+            }
+            None => &self.sky,
+        };
+        sky.iter().chain(&self.false_sky).cloned().collect()
     }
     fn restart(&mut self) {
-        (*self.scoring)
-            .borrow_mut()
-            .score_and_reset(self.distance());
-        self.target_q = random_quaternion();
-        self.make_sky();
-        self.real_q = random_quaternion();
-        self.step = 0.125;
+        self.score();
+        self.new_game();
     }
-
-    fn zoom(&mut self, direction: f32) {
-        let fov = self.fov.rescale(direction);
-        self.fov = fov;
+    /// Scores the game as it is, by how far the view is from the target:
+    /// not in a planetarium, which has no games.
+    pub(crate) fn score(&mut self) {
+        if self.zen {
+            return;
+        }
+        // before borrowing the scoring to change it, as the metadata reads it
+        let metadata = self.game_metadata();
+        (*self.scoring).borrow_mut().score_and_reset(
+            self.distance() * self.options.difficulty.score_weight(),
+            metadata,
+        );
+        self.learned(Action::Score);
+    }
+    /// Starts a game with a new target, or in a planetarium only makes the
+    /// sky again, still pointing where it did.
+    fn new_game(&mut self) {
+        if self.zen {
+            self.make_sky();
+            return;
+        }
+        if let Some(cluster) = &mut self.cluster {
+            *cluster = Cluster::random_with(self.options.nstars, &mut self.rng);
+            self.make_sky();
+            if self.six_dof {
+                self.target_q = random_quaternion_with(&mut self.rng);
+                self.real_q = self
+                    .options
+                    .difficulty
+                    .starting_attitude(self.target_q, &mut self.rng);
+            }
+        } else {
+            self.target_q = random_quaternion_with(&mut self.rng);
+            self.occluder = Occluder::random_with(&mut self.rng);
+            self.make_sky();
+            self.real_q = self
+                .options
+                .difficulty
+                .starting_attitude(self.target_q, &mut self.rng);
+        }
+        self.step = self.initial_step;
+        self.started = Instant::now();
+        self.spin.stop();
+        self.replaying = None;
+        self.hint_shown = None;
     }
-}
 
-pub fn get_help_lines() -> [String; 13] {
-    [
-        "y/Y  : yaw".to_owned(),
-        "p/P  : pitch".to_owned(),
-        "r/R  : roll".to_owned(),
-        "z/Z  : zoom".to_owned(),
-        "s/S  : scale".to_owned(),
-        "d    : show/hide distance".to_owned(),
-        "n    : show/hide star names".to_owned(),
-        "c    : use real/random catalog".to_owned(),
-        "v/V  : number of stars".to_owned(),
-        "space: score and restart".to_owned(),
-        "t    : show only target".to_owned(),
-        "h    : show/hide this help".to_owned(),
-        "q    : end playing the game".to_owned(),
-    ]
-}
+    /// Shows for a while the turn still needed to reach the target, for
+    /// the moves of a hint.
+    fn show_hint(&mut self) {
+        // it tells how to turn, and in a cluster the view may not
+        if self.cluster.is_some() && !self.six_dof {
+            return;
+        }
+        self.hint_shown = Some(Instant::now());
+        (*self.scoring).borrow_mut().moves += hint::PENALTY;
+    }
 
-impl View for SkyView {
-    fn draw(&self, p: &Printer) {
-        let x_max = p.size.x as u8;
-        let x_mid = x_max / 2;
-        let y_max = p.size.y as u8;
+    /// the direction the spacecraft points at, in the frame of `self.sky`
+    fn boresight(&self) -> Star {
+        Camera::boresight()
+            .to_body()
+            .to_equatorial(self.real_q)
+            .direction()
+    }
 
-        let left = cursive::Vec2::new(0, self.headers);
-        let left_printer = p.offset(left);
-        self.draw_portion(self.real_q, &left_printer, x_mid, y_max);
+    /// Selects the star called `text` and turns the view to center it, as one
+    /// move, or tells there is none. Where the level does not allow jumps, it
+    /// is only selected, with an arrow on the edge while it is out of sight.
+    pub fn find_star(&mut self, text: &str) {
+        self.selected = self.sky.find(text);
+        if self.selected.is_none() {
+            self.status = Some(format!(",   no star called {}", text.trim()));
+            return;
+        }
+        self.status = None;
+        if self.allows_jump() {
+            self.center_selected();
+        }
+    }
 
-        let style = ColorStyle::new(Color::Rgb(20, 200, 200), Color::Rgb(0, 0, 0));
-        for y in 0..y_max {
-            p.with_color(style, |printer| printer.print((x_mid, y), "|"))
+    /// Turns the view to center the selected star, as one move.
+    fn center_selected(&mut self) {
+        let Some(i) = self.selected else {
+            return;
+        };
+        if !self.allows_jump() {
+            self.status = Some(String::from(",   not in this level"));
+            return;
         }
+        self.real_q = centering(&(self.real_q * self.sky[i].position())) * self.real_q;
+        (*self.scoring).borrow_mut().add_move();
+    }
 
-        let right = cursive::Vec2::new(x_mid as usize + self.vmargin, self.headers);
-        let right_printer = p.offset(right);
-        self.draw_portion(self.target_q, &right_printer, x_mid, y_max);
+    /// Jumps to the attitude typed in `text`, as one move, or tells why it
+    /// cannot: the text makes no attitude, or the level does not allow it.
+    pub fn enter_attitude(&mut self, text: &str) {
+        let allowed = self.allows_jump();
+        self.status = match parse_attitude(text) {
+            Ok(_) if !allowed => Some(String::from(",   not in this level")),
+            Ok(q) => {
+                self.real_q = q;
+                (*self.scoring).borrow_mut().add_move();
+                None
+            }
+            Err(e) => Some(format!(",   no attitude: {e}")),
+        };
+    }
 
-        let header_offset = cursive::Vec2::new(1, 0);
-        let header_printer = p.offset(header_offset);
-        self.draw_header(&header_printer, style);
-        if self.options.show_help {
-            let help_c = cursive::Vec2::new(x_mid as usize * 2 / 3 + self.vmargin, self.headers);
-            let help_printer = p.offset(help_c);
-            let style = ColorStyle::new(Color::Rgb(200, 200, 20), Color::Rgb(60, 60, 60));
-            self.show_help(&help_printer, style);
+    /// Runs the command typed in `text`, or tells why it cannot.
+    pub fn run_command(&mut self, text: &str) {
+        let command = match Command::parse(text) {
+            Ok(command) => command,
+            Err(e) => {
+                self.status = Some(format!(",   {e}"));
+                return;
+            }
+        };
+        self.status = None;
+        match command {
+            Command::Catalog(Some(path)) if !Path::new(&path).exists() => {
+                self.status = Some(format!(",   no catalog {path}"));
+            }
+            Command::Catalog(catalog) => {
+                self.options.catalog_filename = catalog;
+                self.restart();
+            }
+            Command::NStars(nstars) => {
+                self.options.nstars = nstars;
+                self.make_sky();
+            }
+            Command::Seed(seed) => {
+                self.score();
+                self.rng = ChaCha8Rng::seed_from_u64(seed);
+                self.new_game();
+            }
+            Command::Save(path) => self.save_session(path),
+            Command::Load(path) => self.restore_session(path),
+            Command::Go(text) => self.enter_attitude(&text),
+            Command::Find(text) => self.find_star(&text),
+            Command::Record => self.toggle_recording(),
+            Command::Replay(scale) => self.replay(scale),
         }
     }
-    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
-        Vec2::new(121, 36)
+
+    /// The constellation the view points at: the one whose boundaries the
+    /// boresight is within, or without them that of the nearest star.
+    fn constellation_pointed(&self, boundaries: Option<&Boundaries>) -> Option<&str> {
+        let boresight = self.boresight();
+        match boundaries {
+            Some(boundaries) => boundaries.constellation_at(&(self.target_q.inverse() * boresight)),
+            None => self.sky.nearest_constellation(&boresight),
+        }
     }
 
-    fn on_event(&mut self, event: Event) -> EventResult {
-        // TODO: add key for changing random/real stars
+    /// Checks where the player thinks the spacecraft points at: either a
+    /// constellation abbreviation or right ascension and declination in degrees.
+    pub fn check_pointing(&mut self, answer: &str) {
+        let boresight = self.boresight();
+        let (ra, dec) = ra_dec(&(self.target_q.inverse() * boresight));
+        let coords: Vec<f32> = answer
+            .split([' ', ','])
+            .filter_map(|c| c.parse().ok())
+            .collect();
+        let constellation = self.constellation_pointed(Boundaries::shared());
+        let right = match coords[..] {
+            [a_ra, a_dec] => {
+                angle_between(&direction_at(a_ra, a_dec), &direction_at(ra, dec)).to_degrees()
+                    < POINTING_TOLERANCE
+            }
+            _ => constellation.is_some_and(|c| c.eq_ignore_ascii_case(answer.trim())),
+        };
+        let constellation = constellation.unwrap_or("?");
+        self.pointing_result = Some(format!(
+            "   {}: pointing at {} (ra {:.1}, dec {:.1})",
+            if right { "right" } else { "wrong" },
+            constellation,
+            ra,
+            dec
+        ));
+    }
+
+    fn zoom(&mut self, direction: f32) {
+        let fov = self.fov.rescale(direction);
+        self.fov = fov;
+        self.learned(Action::Zoom);
+    }
+
+    /// What the keys answering the quiz do, while it is played: the others
+    /// do as usual.
+    fn handle_quiz(&mut self, event: &Event) -> Option<EventResult> {
+        self.quiz.as_ref()?;
         match event {
+            Event::Char(digit @ '1'..='9') => self.answer_quiz(&digit.to_string()),
+            Event::Char('/') => return Some(EventResult::with_cb(ask_name)),
+            // passing counts as a wrong answer
+            Event::Char(' ') => self.answer_quiz(""),
+            // the names would tell the answer
+            Event::Char('n' | 'N' | ';') | Event::Mouse { .. } => {}
+            _ => return None,
+        }
+        Some(EventResult::Consumed(None))
+    }
+
+    /// what the keys do, with the arrows turning unless they move the cursor
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        let event = letter_for(event);
+        if let Some(result) = self.handle_quiz(&event) {
+            return result;
+        }
+        if control_for(&event).is_some_and(|c| !self.rules.allows(c)) {
+            return EventResult::Consumed(None);
+        }
+        if self.options.continuous {
+            if let Some((axis, sign)) = spin_axis(&event) {
+                self.spin.press(axis, sign);
+                return EventResult::Consumed(None);
+            }
+        }
+        match event {
+            Event::Refresh => {
+                self.keep_spinning();
+                self.keep_replaying();
+                if !self.zen {
+                    self.check_solved();
+                }
+                self.keep_quizzing();
+                return EventResult::Ignored;
+            }
             Event::Char('P') => {
                 self.rotate(-1.0, 0.0, 0.0);
             }
@@ -221,12 +1509,25 @@ impl View for SkyView {
             Event::Char('r') => {
                 self.rotate(0.0, 0.0, -1.0);
             }
+            Event::Char('j') => {
+                self.options.body_axes = !self.options.body_axes;
+            }
+            Event::Char('J') if self.six_dof => {
+                self.moving = !self.moving;
+            }
+            Event::Char('C') => {
+                self.options.continuous = !self.options.continuous;
+                self.spin.stop();
+            }
             Event::Char('s') => {
                 self.step /= 2.0;
             }
             Event::Char('S') => {
                 self.step *= 2.0;
             }
+            Event::Char(digit @ '1'..='9') => {
+                self.step = preset_step(digit as u32 - '0' as u32);
+            }
             Event::Char('Z') => {
                 self.zoom(1.25);
             }
@@ -236,12 +1537,43 @@ impl View for SkyView {
             Event::Char(' ') => {
                 self.restart();
             }
+            // a planetarium has no target to go to, point at or hint
+            Event::Char('a' | 'w' | 'M' | '?') if self.zen => {}
+            Event::Char('T') => {
+                self.toggle_practice();
+            }
+            Event::Char('D') => {
+                self.score();
+                self.set_difficulty(self.options.difficulty.next());
+                self.new_game();
+            }
             Event::Char('d') => {
                 self.options.show_distance = !self.options.show_distance;
             }
+            Event::Char('l') => {
+                self.options.horizon = !self.options.horizon;
+                self.make_sky();
+            }
+            Event::Char('i') => {
+                self.options.galactic_equator = !self.options.galactic_equator;
+            }
+            Event::Char('I') => {
+                self.options.grid = !self.options.grid;
+            }
+            Event::Char('u') => {
+                self.options.refraction = !self.options.refraction;
+                self.make_sky();
+            }
+            Event::Char('x') => {
+                self.options.conditions = self.options.conditions.next();
+                self.make_sky();
+            }
             Event::Char('n') => {
                 self.options.show_star_names = !self.options.show_star_names;
             }
+            Event::Char('N') => {
+                self.options.name_style = self.options.name_style.next();
+            }
             Event::Char('c') => {
                 self.options.catalog_filename = match self.options.catalog_filename {
                     None => Some(String::from("assets/bsc5.csv")),
@@ -257,22 +1589,445 @@ impl View for SkyView {
                 self.options.nstars = (self.options.nstars as f32 * 1.25) as usize;
                 self.make_sky();
             }
+            Event::Char('f') => {
+                self.options.false_stars = self.options.false_stars.saturating_sub(5);
+                self.false_sky = Sky::false_stars_with(self.options.false_stars, &mut self.rng);
+                self.sky_changed();
+            }
+            Event::Char('F') => {
+                self.options.false_stars += 5;
+                self.false_sky = Sky::false_stars_with(self.options.false_stars, &mut self.rng);
+                self.sky_changed();
+            }
+            Event::Char('e') => {
+                self.fov = self.fov.with_vignetting(!self.fov.vignetting());
+            }
+            Event::Char('E') => {
+                self.options
+                    .next_optics(&mut self.fov, &mut self.narrow_fov);
+            }
+            Event::Char('+') => {
+                self.options.change_limiting_magnitude(&self.fov, 0.5);
+            }
+            Event::Char('-') => {
+                self.options.change_limiting_magnitude(&self.fov, -0.5);
+            }
+            Event::Key(Key::Tab) => {
+                toggle_all_sky(&mut self.fov, &mut self.narrow_fov);
+            }
+            Event::Char('o') => {
+                self.options.occultation = !self.options.occultation;
+                self.sky_changed();
+            }
+            Event::Char('b') => {
+                self.options.solar_system = !self.options.solar_system;
+                self.make_sky();
+            }
+            Event::Char('a') => {
+                self.score();
+                self.slew = Some(Slew::new(self.real_q));
+            }
+            // a jump as any other, to a new game: the one played is scored
+            Event::Char('w') => {
+                if !self.allows_jump() {
+                    return EventResult::Consumed(None);
+                }
+                self.score();
+                self.new_game();
+                self.pointing_result = None;
+                return EventResult::with_cb(ask_pointing);
+            }
+            Event::Char(':') => {
+                return EventResult::with_cb(ask_command);
+            }
+            Event::Char('/') => {
+                return EventResult::with_cb(ask_star);
+            }
+            Event::Char('G') => {
+                self.center_selected();
+            }
+            Event::Char('M') => {
+                self.assist = match self.assist {
+                    None if self.allows_jump() => Some(PairAssist::default()),
+                    _ => None,
+                };
+            }
+            Event::Char(';') => {
+                self.cursor = match (self.cursor, self.drawn_view(0)) {
+                    (None, Some((_, _, x_max, y_max))) => Some((x_max / 2, y_max / 2)),
+                    _ => None,
+                };
+            }
+            Event::Mouse {
+                offset,
+                position,
+                event: MouseEvent::Press(MouseButton::Left),
+            } => {
+                self.click(position, offset);
+            }
+            Event::Key(Key::Esc) => {
+                self.selected = None;
+                self.cursor = None;
+                self.replaying = None;
+            }
             Event::Char('q') => {
-                self.restart();
+                self.score();
                 return EventResult::Ignored;
             }
             Event::Char('h') => {
                 self.options.show_help = !self.options.show_help;
             }
+            Event::Char('?') => {
+                self.show_hint();
+            }
+            Event::Char('k') => {
+                self.save_session(None);
+            }
+            Event::Char('K') => {
+                self.restore_session(None);
+            }
             _ => return EventResult::Ignored,
         }
         EventResult::Consumed(None)
     }
 }
 
-#[derive(Debug, Default)]
+/// What is shown of a selected star: its name, magnitude, right ascension
+/// and declination, and how far it is from `boresight`, in a sky turned by
+/// `target_q` from the catalog.
+pub(crate) fn star_info(
+    star: &CatalogStar,
+    style: NameStyle,
+    target_q: UnitQuaternion<f32>,
+    boresight: &Star,
+) -> [String; 4] {
+    let name = match star.display_name(style) {
+        name if name.is_empty() => String::from("a star with no name"),
+        name => name.into_owned(),
+    };
+    let (ra, dec) = ra_dec(&(target_q.inverse() * star.position()));
+    [
+        name,
+        format!("magnitude {:.2}", star.magnitude()),
+        format!("ra {ra:.2}°, dec {dec:+.2}°"),
+        format!(
+            "{:.2}° from the boresight",
+            angle_between(star.position(), boresight).to_degrees()
+        ),
+    ]
+}
+
+/// Where an arrow on the edge of a window `width` by `height`, less
+/// `margin`, points to `direction` (in the frame of the camera) when it is
+/// out of sight, and the angle it points at, clockwise from the right.
+pub(crate) fn edge_arrow(
+    direction: &Camera,
+    width: f32,
+    height: f32,
+    margin: f32,
+) -> (f32, f32, f32) {
+    let direction = direction.direction();
+    let angle = direction[1].atan2(direction[0]);
+    let (half_width, half_height) = (
+        (width / 2.0 - margin).max(0.0),
+        (height / 2.0 - margin).max(0.0),
+    );
+    let (dx, dy) = (angle.cos(), angle.sin());
+    let reach = (half_width / dx.abs()).min(half_height / dy.abs());
+    (width / 2.0 + dx * reach, height / 2.0 + dy * reach, angle)
+}
+
+/// The step of the preset `digit`, from 1 to 9: from a thousandth of a
+/// radian to a whole one, each about 2.4 times the one before.
+pub(crate) fn preset_step(digit: u32) -> f32 {
+    1e-3 * 1e3f32.powf((digit as f32 - 1.0) / 8.0)
+}
+
+/// Switches between a fisheye overview of the whole sky and the narrow field
+/// of view, which is kept in `narrow_fov` meanwhile.
+pub(crate) fn toggle_all_sky(fov: &mut FoV, narrow_fov: &mut Option<FoV>) {
+    match narrow_fov.take() {
+        Some(narrow) => *fov = narrow,
+        None => *narrow_fov = Some(std::mem::replace(fov, fov.all_sky())),
+    }
+}
+
+/// The letter an arrow or page key stands for: up and down pitch, left and
+/// right yaw, page up and page down roll, each way without shift.
+fn letter_for(event: Event) -> Event {
+    match event {
+        Event::Key(Key::Up) => Event::Char('p'),
+        Event::Key(Key::Down) => Event::Char('P'),
+        Event::Key(Key::Left) => Event::Char('y'),
+        Event::Key(Key::Right) => Event::Char('Y'),
+        Event::Key(Key::PageUp) => Event::Char('r'),
+        Event::Key(Key::PageDown) => Event::Char('R'),
+        event => event,
+    }
+}
+
+/// the axis (pitch, yaw or roll) a rotation key turns, and which way
+fn spin_axis(event: &Event) -> Option<(usize, f32)> {
+    match event {
+        Event::Char('p') => Some((0, 1.0)),
+        Event::Char('P') => Some((0, -1.0)),
+        Event::Char('y') => Some((1, -1.0)),
+        Event::Char('Y') => Some((1, 1.0)),
+        Event::Char('r') => Some((2, -1.0)),
+        Event::Char('R') => Some((2, 1.0)),
+        _ => None,
+    }
+}
+
+fn control_for(event: &Event) -> Option<Control> {
+    match event {
+        Event::Char('r' | 'R') => Some(Control::Roll),
+        Event::Char('p' | 'P') => Some(Control::Pitch),
+        Event::Char('y' | 'Y') => Some(Control::Yaw),
+        Event::Char('z' | 'Z') => Some(Control::Zoom),
+        Event::Char('s' | 'S' | '1'..='9') => Some(Control::Scale),
+        _ => None,
+    }
+}
+
+/// An animated rotation from some attitude to the target one,
+/// along the shortest path
+#[derive(Clone)]
+pub(crate) struct Slew {
+    from: UnitQuaternion<f32>,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Slew {
+    const DURATION: Duration = Duration::from_secs(3);
+
+    pub(crate) fn new(from: UnitQuaternion<f32>) -> Self {
+        Self {
+            from,
+            started: Instant::now(),
+            duration: Self::DURATION,
+        }
+    }
+
+    /// the same slew, lasting `duration`
+    pub(crate) fn lasting(self, duration: Duration) -> Self {
+        Self { duration, ..self }
+    }
+
+    /// the attitude at this moment, or `None` once the slew is over
+    pub(crate) fn at(&self, target_q: UnitQuaternion<f32>) -> Option<UnitQuaternion<f32>> {
+        let t = self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+        if t >= 1.0 {
+            None
+        } else {
+            Some(slerp(self.from, target_q, ease(t)))
+        }
+    }
+}
+
+/// how far, in degrees, a right ascension and declination answer can be
+const POINTING_TOLERANCE: f32 = 10.0;
+
+fn ask_pointing(siv: &mut Cursive) {
+    let answer = EditView::new().on_submit(|s, text| {
+        s.pop_layer();
+        s.call_on_name("sky", |v: &mut SkyView| v.check_pointing(text));
+    });
+    siv.add_layer(Dialog::around(answer).title("Where are we pointing? (constellation or ra dec)"));
+}
+
+fn ask_command(siv: &mut Cursive) {
+    let answer = EditView::new().on_submit(|s, text| {
+        s.pop_layer();
+        s.call_on_name("sky", |v: &mut SkyView| v.run_command(text));
+    });
+    siv.add_layer(Dialog::around(answer).title(
+        "Command: catalog, nstars, seed, save/load [game.json], go, find, record, replay (or an attitude)",
+    ));
+}
+
+fn ask_star(siv: &mut Cursive) {
+    let answer = EditView::new().on_submit(|s, text| {
+        s.pop_layer();
+        s.call_on_name("sky", |v: &mut SkyView| v.find_star(text));
+    });
+    siv.add_layer(Dialog::around(answer).title("Find a star (name, proper name or HR number)"));
+}
+
+fn ask_name(siv: &mut Cursive) {
+    let answer = EditView::new().on_submit(|s, text| {
+        s.pop_layer();
+        s.call_on_name("sky", |v: &mut SkyView| v.answer_quiz(text));
+    });
+    siv.add_layer(Dialog::around(answer).title("Which is the star marked?"));
+}
+
+/// Plays in the terminal until `q` is pressed.
+/// Returns the configuration the view ended with.
+pub fn cursive_window(sky_view: SkyView) -> Option<ViewConfig> {
+    let mut siv = cursive::default();
+    siv.add_layer(sky_view.with_name("sky"));
+    siv.add_global_callback('q', |s| s.quit());
+    siv.set_fps(30);
+    siv.run();
+    siv.call_on_name("sky", |v: &mut SkyView| v.config())
+}
+
+pub fn get_help_lines() -> [String; 44] {
+    [
+        "y/Y  : yaw".to_owned(),
+        "p/P  : pitch".to_owned(),
+        "r/R  : roll".to_owned(),
+        "arrows, PgUp/PgDn: pitch, yaw, roll".to_owned(),
+        "j    : turn about screen/camera axes".to_owned(),
+        "J    : turn/move the view (6-DOF)".to_owned(),
+        "C    : keep turning while a key is held (cli)".to_owned(),
+        ":    : go to an attitude, or a command (cli)".to_owned(),
+        "click, ;: select a star with the mouse/cursor".to_owned(),
+        "M    : match two pairs of stars to align".to_owned(),
+        "/, G : find and center a star/center the selected one".to_owned(),
+        "F5/F6: record/replay turns (gui; :record, :replay in the cli)".to_owned(),
+        "z/Z  : zoom".to_owned(),
+        "s/S  : scale".to_owned(),
+        "1-9  : step from 0.001 to 1 rad".to_owned(),
+        "d    : show/hide distance".to_owned(),
+        "D    : difficulty: easy/medium/hard".to_owned(),
+        "T    : practice: not scored, distance shown".to_owned(),
+        "n    : show/hide star names".to_owned(),
+        "N    : star names: designation/proper/HR".to_owned(),
+        "c    : use real/random catalog".to_owned(),
+        "v/V  : number of stars".to_owned(),
+        "f/F  : number of false stars".to_owned(),
+        "o    : hide stars behind a planet".to_owned(),
+        "b    : show/hide the Sun, Moon and planets".to_owned(),
+        "m    : show/hide Messier objects (gui)".to_owned(),
+        "l    : show only stars above the horizon".to_owned(),
+        "u    : refraction near the horizon".to_owned(),
+        "g    : the target drifts as the sky turns (gui)".to_owned(),
+        "x    : light pollution (Bortle class)".to_owned(),
+        "i/I  : show/hide the galactic equator/RA-Dec grid".to_owned(),
+        "e/E  : dim stars at the edges/next optics".to_owned(),
+        "tab  : all-sky overview/narrow field".to_owned(),
+        "+/-  : fainter/only brighter stars".to_owned(),
+        "w    : new game, guessing where it points".to_owned(),
+        "space: score and restart".to_owned(),
+        "a    : score and show the solution".to_owned(),
+        "t    : show only target".to_owned(),
+        "h    : show/hide this help".to_owned(),
+        format!(
+            "?    : show the turn to the target, for {} moves",
+            hint::PENALTY
+        ),
+        "k/K  : save/restore the game".to_owned(),
+        "F11  : fullscreen (gui)".to_owned(),
+        "F3   : frames per second (gui)".to_owned(),
+        "q    : end playing the game".to_owned(),
+    ]
+}
+
+impl View for SkyView {
+    fn draw(&self, p: &Printer) {
+        let x_max = p.size.x as u32;
+        // without a target, the window takes the whole width
+        let x_mid = if self.zen { x_max } else { x_max / 2 };
+        let y_max = p.size.y as u32;
+
+        let left = cursive::Vec2::new(0, self.headers);
+        let left_printer = p.offset(left);
+        if self.options.grid {
+            let style = ColorStyle::new(Color::Rgb(50, 70, 120), Color::Rgb(0, 0, 32));
+            let (ra_step, dec_step) = self.options.grid_step;
+            for line in equatorial_grid(ra_step, dec_step, 720) {
+                self.draw_sky_line(&line, style, &left_printer, x_mid, y_max);
+            }
+        }
+        if self.options.galactic_equator {
+            self.draw_great_circle(
+                Equatorial::new(galactic_pole()),
+                &left_printer,
+                x_mid,
+                y_max,
+            );
+        }
+        let mut live = self.live.borrow_mut();
+        let live = live.get_or_insert_with(|| self.live_sky());
+        self.draw_portion(0, live, self.real_q, &left_printer, x_mid, y_max);
+        self.draw_selection(&left_printer, x_mid, y_max);
+        self.draw_quiz(&left_printer, x_mid, y_max);
+
+        let style = ColorStyle::new(Color::Rgb(20, 200, 200), Color::Rgb(0, 0, 0));
+        if !self.zen {
+            for y in 0..y_max as usize {
+                p.with_color(style, |printer| printer.print((x_mid as usize, y), "|"))
+            }
+            let right = cursive::Vec2::new(x_mid as usize + self.vmargin, self.headers);
+            let right_printer = p.offset(right);
+            self.draw_portion(1, &self.sky, self.target_q, &right_printer, x_mid, y_max);
+        }
+
+        let header_offset = cursive::Vec2::new(1, 0);
+        let header_printer = p.offset(header_offset);
+        self.draw_header(&header_printer, style);
+        if self.options.show_help {
+            let help_printer = p.offset(cursive::Vec2::new(0, self.headers));
+            let style = ColorStyle::new(Color::Rgb(200, 200, 20), Color::Rgb(60, 60, 60));
+            self.show_help(&help_printer, x_mid as usize * 2 / 3 + self.vmargin, style);
+        }
+        self.timings.borrow_mut().end_frame();
+    }
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        Vec2::new(121, 36)
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if let Some(slew) = &self.slew {
+            match slew.at(self.target_q) {
+                Some(q) => self.real_q = q,
+                None => {
+                    self.slew = None;
+                    self.new_game();
+                }
+            }
+            return match event {
+                Event::Char('q') => EventResult::Ignored,
+                _ => EventResult::Consumed(None),
+            };
+        }
+        // TODO: add key for changing random/real stars
+        if let Some((x, y)) = self.cursor {
+            match event {
+                Event::Key(Key::Up) => self.move_cursor(0, -1),
+                Event::Key(Key::Down) => self.move_cursor(0, 1),
+                Event::Key(Key::Left) => self.move_cursor(-1, 0),
+                Event::Key(Key::Right) => self.move_cursor(1, 0),
+                Event::Key(Key::Enter) => self.select_at(x, y),
+                _ => return self.handle_event(event),
+            }
+            return EventResult::Consumed(None);
+        }
+        self.handle_event(event)
+    }
+}
+
+/// How a scored game was played
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct GameMetadata {
+    pub false_stars: usize,
+    pub occultation: bool,
+    pub difficulty: Difficulty,
+    /// whether the view came within the tolerance of the target
+    pub solved: bool,
+    /// how long the game took
+    pub seconds: f32,
+    pub moves: usize,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Scoring {
     pub total: Vec<f32>,
+    pub metadata: Vec<GameMetadata>,
     pub moves: usize,
     pub counted_moves: usize,
 }
@@ -282,8 +2037,9 @@ impl Scoring {
         self.moves += 1;
     }
 
-    pub fn score_and_reset(&mut self, add: f32) {
+    pub fn score_and_reset(&mut self, add: f32, metadata: GameMetadata) {
         self.total.push(add * (self.moves as f32 + 20.0));
+        self.metadata.push(metadata);
         self.counted_moves += self.moves;
         self.moves = 0;
     }
@@ -296,3 +2052,99 @@ impl Scoring {
         self.total.iter().sum::<f32>() / (self.total.len() as f32)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use approx::assert_relative_eq;
+    use nalgebra::UnitQuaternion;
+
+    use super::{preset_step, Scoring, SkyView};
+    use crate::{
+        attitude::centering,
+        boundaries::Boundaries,
+        frames::Camera,
+        macros::Turn,
+        sky::{direction_at, Brightness, CatalogStar, Sky},
+    };
+
+    #[test]
+    fn test_preset_step() {
+        assert_relative_eq!(preset_step(1), 1e-3);
+        assert_relative_eq!(preset_step(9), 1.0, max_relative = 1e-6);
+        for digit in 1..9 {
+            assert_relative_eq!(
+                preset_step(digit + 1) / preset_step(digit),
+                1e3f32.powf(1.0 / 8.0),
+                max_relative = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn test_move_in_cluster() {
+        let scoring = Rc::new(RefCell::new(Scoring::default()));
+        let mut view = SkyView::new(None, 50, scoring).with_six_dof();
+        view.moving = true;
+        view.real_q = UnitQuaternion::from_euler_angles(0.4, -1.1, 2.0);
+        let before = view.cluster.as_ref().unwrap().position;
+        // ahead, with roll, along where the camera points in the sky
+        view.turn(Turn::of_steps([0.0, 0.0, 1.0], 0.1, false));
+        let moved = view.cluster.as_ref().unwrap().position - before;
+        let boresight = Camera::boresight()
+            .to_body()
+            .to_equatorial(view.real_q)
+            .direction();
+        assert_relative_eq!(moved, boresight * 0.1, epsilon = 1e-6);
+        assert!((boresight - Camera::boresight().direction()).norm() > 0.1);
+    }
+
+    #[test]
+    fn test_score() {
+        let scoring = Rc::new(RefCell::new(Scoring::default()));
+        let mut view = SkyView::new(None, 50, Rc::clone(&scoring));
+        view.rotate(1.0, 0.0, 0.0);
+        view.score();
+        let scoring = scoring.borrow();
+        assert_eq!(scoring.games(), 1);
+        assert_eq!(scoring.metadata[0].moves, 1);
+        assert_eq!(scoring.moves, 0);
+    }
+
+    #[test]
+    fn test_constellation_pointed() {
+        let scoring = Rc::new(RefCell::new(Scoring::default()));
+        let identity = UnitQuaternion::identity();
+        let mut view = SkyView::with_attitudes(None, 10, scoring, identity, identity);
+        // pointing at Taurus, just over the border from a star of Orion
+        view.sky =
+            Sky::from(&[
+                CatalogStar::new(direction_at(76.0, 5.0), Brightness::new(0.5), "")
+                    .with_constellation("Ori"),
+            ]);
+        view.real_q = centering(&direction_at(74.0, 5.0));
+        let boundaries = Boundaries::parse(
+            "4.0 0.0 TAU\n5.0 0.0 TAU\n5.0 10.0 TAU\n4.0 10.0 TAU\n\
+             5.0 0.0 ORI\n6.0 0.0 ORI\n6.0 10.0 ORI\n5.0 10.0 ORI\n",
+        )
+        .unwrap();
+        assert_eq!(view.constellation_pointed(Some(&boundaries)), Some("Tau"));
+        assert_eq!(view.constellation_pointed(None), Some("Ori"));
+    }
+
+    #[test]
+    fn test_zen() {
+        let scoring = Rc::new(RefCell::new(Scoring::default()));
+        let mut view = SkyView::new(None, 50, Rc::clone(&scoring)).with_zen(None, None);
+        let real_q = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+        view.real_q = real_q;
+        // a new catalog makes the sky again, but starts no game to score
+        view.options.nstars = 20;
+        view.restart();
+        assert_eq!(scoring.borrow().games(), 0);
+        assert_eq!(view.target_q, UnitQuaternion::identity());
+        assert_eq!(view.real_q, real_q);
+        assert_eq!(view.sky.len(), 20);
+    }
+}